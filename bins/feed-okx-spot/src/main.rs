@@ -1 +0,0 @@
-fn main() { println!("TODO: implement"); }
@@ -0,0 +1,87 @@
+//! feed-runner — streams exchange top of book into the Price Store, one
+//! feed per source. Long-running: see `feeds::ws` for sharding, health and
+//! shutdown.
+//!
+//! Usage: feed-runner --source <name> [--config config/config.toml]
+//!        feed-runner --sources <name>,<name>,... [--config config/config.toml]
+//!
+//! Sources are named as `SourceId::name()` ("okx_spot", "binance_futures",
+//! ...) and their parsers come from `feeds::parser::REGISTRY`. Several
+//! sources share one process and one tokio runtime; each claims its own
+//! HealthTable slot, and the process exits if any feed fails to start or
+//! another live process already serves one of the sources.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use tracing::{info, Level};
+
+use common::types::SourceId;
+use feeds::parser::{parser_factory, REGISTRY};
+
+struct Args {
+    config_path: PathBuf,
+    sources: Vec<SourceId>,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut config_path = None;
+        let mut names = Vec::new();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--config" => {
+                    config_path = Some(args.next().context("--config requires a path")?.into());
+                }
+                "--source" => names.push(args.next().context("--source requires a name")?),
+                "--sources" => {
+                    let list = args.next().context("--sources requires a list")?;
+                    names.extend(list.split(',').map(|name| name.trim().to_string()));
+                }
+                other => anyhow::bail!("unknown argument: {}", other),
+            }
+        }
+        if names.is_empty() {
+            anyhow::bail!("no source given; use --source or --sources");
+        }
+
+        let mut sources = Vec::new();
+        for name in &names {
+            let source = parser_factory(name)
+                .and_then(|_| SourceId::from_name(name))
+                .with_context(|| format!("unknown source {:?} (known: {})", name, known()))?;
+            if sources.contains(&source) {
+                anyhow::bail!("source {} given twice", name);
+            }
+            sources.push(source);
+        }
+        Ok(Self {
+            config_path: config_path.unwrap_or_else(|| PathBuf::from("config/config.toml")),
+            sources,
+        })
+    }
+}
+
+fn known() -> String {
+    let names: Vec<_> = REGISTRY.iter().map(|(name, _)| *name).collect();
+    names.join(", ")
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt().with_max_level(Level::INFO).init();
+
+    let args = Args::parse()?;
+    let names: Vec<_> = args.sources.iter().map(|s| s.name()).collect();
+    info!("Starting feeds: {}", names.join(", "));
+
+    let config_path = &args.config_path;
+    let feeds = args.sources.iter().map(|&source| async move {
+        feeds::ws::run_feed(source, config_path)
+            .await
+            .with_context(|| format!("{} feed failed", source.name()))
+    });
+    futures_util::future::try_join_all(feeds).await?;
+    Ok(())
+}
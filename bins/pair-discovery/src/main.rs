@@ -1 +1,826 @@
-fn main() { println!("TODO: implement"); }
+//! pair-discovery — Builds the symbol universe from exchange REST APIs.
+//! Oneshot: fetches instruments from all 8 sources, tolerating up to 2 failures,
+//! normalizes them to canonical BASE-QUOTE names, drops listings that are
+//! not trading or delist within discovery.delist_horizon_hours, drops
+//! listings below the 24h volume / book width thresholds, validates every candidate
+//! pair over WebSocket with the feed parsers and writes symbols.bin /
+//! directions.bin (plus metadata.json and text mirrors, each atomically),
+//! keeping symbol_ids stable across runs. diff.json/diff.txt describe the
+//! change against the previous generation (diff.rejected.* for a run held
+//! back by the diff limits). Pairs whose venues quote wildly different
+//! prices (ticker collisions) are quarantined out of their direction until
+//! they agree again for discovery.quarantine_release_runs runs
+//! (generated/quarantine.json, quarantine.txt).
+//!
+//! Usage: pair-discovery [--config config/config.toml] [--output generated] [--force]
+//!                       [--daemon | --record <dir> | --replay <dir>]
+//! exchanges.toml, directions.toml and the optional overrides.toml are read
+//! from the same directory as config.toml; --output defaults to general.generated_dir. --force publishes
+//! even when the diff exceeds the configured limits.
+//!
+//! --daemon reruns the pipeline every discovery.cron_interval_hours (± jitter),
+//! rereading the configs each time. A generation is published only when the
+//! diff is non-empty, and then ControlStore::config_version is bumped so the
+//! running processes reload. Failed runs are logged and retried next cycle.
+//! Between runs the daemon polls the instrument lists every
+//! discovery.listing_poll_interval_sec and runs at once when a pair newly
+//! lists on both sources of a direction (generated/listings.json). A pair
+//! still missing from the published generation after that run is retried on
+//! later polls, up to FAST_TRACK_ATTEMPTS runs, then waits for the schedule.
+//!
+//! --record <dir> runs once and saves every REST response and validation WS
+//! frame, the config files and the previous generation state to <dir>.
+//! --replay <dir> reruns that capture without network, with the recorded
+//! configs unless --config is given, writing to --output or <dir>/replay
+//! (never to general.generated_dir, whose state it would overwrite); the
+//! same capture always yields the same generation.
+//!
+//! Exit codes (oneshot): 0 ok, 1 generic failure, 2 fewer than 6 sources
+//! answered, 3 diff limits exceeded (nothing published), 4 fewer than 6
+//! sources passed WS validation.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use tracing::{debug, error, info, warn, Level};
+
+use common::config::{AppConfig, DirectionsConfig, ExchangesConfig};
+use common::types::SourceId;
+use discovery::capture::Capture;
+use discovery::diff::{DiffLimits, GenerationDiff, DIFF_STEM, REJECTED_DIFF_STEM};
+use discovery::direction_builder::build_directions;
+use discovery::error::DiscoveryError;
+use discovery::generator::{Generation, Previous};
+use discovery::liquidity::LiquidityFilter;
+use discovery::listings::{self, NewListing};
+use discovery::normalizer::Normalizer;
+use discovery::overrides::Overrides;
+use discovery::quarantine::{Quarantine, QuarantinePolicy};
+use discovery::registry::{assign_ids, build_candidates, Pairing, Tombstones};
+use discovery::rest_client::{RestClient, RetryPolicy};
+use discovery::status::StatusFilter;
+use discovery::validator::{candidate_table, ValidationConfig, Validator};
+use shm::control::ControlStore;
+
+/// Immediate runs a new pair gets before it waits for the schedule. A fresh
+/// listing often fails WS validation at first (empty book), so one is not
+/// enough; a pair that never validates must not rerun the pipeline forever.
+const FAST_TRACK_ATTEMPTS: u32 = 3;
+
+struct Args {
+    config_path: PathBuf,
+    output_dir: Option<PathBuf>,
+    force: bool,
+    daemon: bool,
+    record: Option<PathBuf>,
+    replay: Option<PathBuf>,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut config_path = None;
+        let mut output_dir = None;
+        let mut force = false;
+        let mut daemon = false;
+        let mut record: Option<PathBuf> = None;
+        let mut replay: Option<PathBuf> = None;
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--config" => {
+                    config_path = Some(args.next().context("--config requires a path")?.into());
+                }
+                "--output" => {
+                    output_dir = Some(args.next().context("--output requires a path")?.into());
+                }
+                "--force" => force = true,
+                "--daemon" => daemon = true,
+                "--record" => {
+                    record = Some(args.next().context("--record requires a path")?.into());
+                }
+                "--replay" => {
+                    replay = Some(args.next().context("--replay requires a path")?.into());
+                }
+                other => anyhow::bail!("unknown argument: {}", other),
+            }
+        }
+        if (daemon && (record.is_some() || replay.is_some()))
+            || (record.is_some() && replay.is_some())
+        {
+            anyhow::bail!("--daemon, --record and --replay are mutually exclusive");
+        }
+        // A replay is self-contained: recorded configs, its own output
+        let config_path = match (config_path, &replay) {
+            (Some(path), _) => path,
+            (None, Some(dir)) => dir.join("config").join("config.toml"),
+            (None, None) => PathBuf::from("config/config.toml"),
+        };
+        if let (None, Some(dir)) = (&output_dir, &replay) {
+            output_dir = Some(dir.join("replay"));
+        }
+        Ok(Self {
+            config_path,
+            output_dir,
+            force,
+            daemon,
+            record,
+            replay,
+        })
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    tracing_subscriber::fmt().with_max_level(Level::INFO).init();
+
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("pair-discovery failed: {:#}", e);
+            match e.downcast_ref::<DiscoveryError>() {
+                Some(de) => ExitCode::from(de.exit_code()),
+                None => ExitCode::from(1),
+            }
+        }
+    }
+}
+
+async fn run() -> Result<()> {
+    let args = Args::parse()?;
+    if !args.daemon {
+        let capture = match (&args.record, &args.replay) {
+            (Some(dir), _) => Capture::record(dir)?,
+            (_, Some(dir)) => Capture::replay(dir)?,
+            _ => Capture::Live,
+        };
+        let config = AppConfig::load(&args.config_path)?;
+        run_once(&args, &config, &capture).await?;
+        return Ok(());
+    }
+
+    let mut config = AppConfig::load(&args.config_path)?;
+    let mut fast_tracked = HashMap::new();
+    loop {
+        match run_once(&args, &config, &Capture::Live).await {
+            Ok(true) => bump_config_version(&config.general.shm_control),
+            Ok(false) => {}
+            Err(e) => error!("Discovery run failed: {:#}", e),
+        }
+
+        let delay = jittered(
+            Duration::from_secs(config.discovery.cron_interval_hours.max(1) * 3600),
+            config.discovery.cron_jitter_pct,
+        );
+        info!("Next discovery run in {}s", delay.as_secs());
+        wait_next_run(&args, &config, delay, &mut fast_tracked).await;
+
+        match AppConfig::load(&args.config_path) {
+            Ok(c) => config = c,
+            Err(e) => error!("Keeping previous config: {:#}", e),
+        }
+    }
+}
+
+/// Sleep until the next scheduled run, polling for new listings meanwhile.
+/// Returns early, for an immediate run, once a pair lists on both sources
+/// of a direction and has not used up its fast-track attempts.
+async fn wait_next_run(
+    args: &Args,
+    config: &AppConfig,
+    delay: Duration,
+    fast_tracked: &mut HashMap<(u8, String), u32>,
+) {
+    let deadline = tokio::time::Instant::now() + delay;
+    let poll = Duration::from_secs(config.discovery.listing_poll_interval_sec);
+    loop {
+        let next = tokio::time::Instant::now() + poll;
+        if poll.is_zero() || next >= deadline {
+            tokio::time::sleep_until(deadline).await;
+            return;
+        }
+        tokio::time::sleep_until(next).await;
+        match poll_listings(args, config).await {
+            Ok(new) => {
+                let fresh = fast_track(new, fast_tracked);
+                if !fresh.is_empty() {
+                    for (n, attempt) in &fresh {
+                        info!(
+                            "Fast-tracking {} (attempt {}/{})",
+                            n, attempt, FAST_TRACK_ATTEMPTS
+                        );
+                    }
+                    return;
+                }
+            }
+            Err(e) => warn!("Listing poll failed: {:#}", e),
+        }
+    }
+}
+
+/// The pairs of `new` that still get an immediate run, with its attempt
+/// number. `new` is detected against the published generation, so a pair
+/// absent from it was published (or delisted again) and is forgotten; a
+/// later relisting starts over.
+fn fast_track(
+    new: Vec<NewListing>,
+    fast_tracked: &mut HashMap<(u8, String), u32>,
+) -> Vec<(NewListing, u32)> {
+    let pending: HashSet<_> = new
+        .iter()
+        .map(|n| (n.direction_id, n.name.clone()))
+        .collect();
+    fast_tracked.retain(|key, _| pending.contains(key));
+    new.into_iter()
+        .filter_map(|n| {
+            let attempts = fast_tracked
+                .entry((n.direction_id, n.name.clone()))
+                .or_insert(0);
+            (*attempts < FAST_TRACK_ATTEMPTS).then(|| {
+                *attempts += 1;
+                (n, *attempts)
+            })
+        })
+        .collect()
+}
+
+/// Lightweight pass: instrument lists only, no tickers and no WS
+/// validation. Returns the new pairs against the published generation.
+async fn poll_listings(args: &Args, config: &AppConfig) -> Result<Vec<NewListing>> {
+    let config_dir = args.config_path.parent().unwrap_or(Path::new("."));
+    let exchanges = ExchangesConfig::load(&config_dir.join("exchanges.toml"))?;
+    let direction_defs = DirectionsConfig::load(&config_dir.join("directions.toml"))?;
+    let overrides = Overrides::load(&config_dir.join("overrides.toml"))?;
+    let output_dir = args
+        .output_dir
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(&config.general.generated_dir));
+
+    let fetched = RestClient::new(RetryPolicy::default())?
+        .fetch_all(&exchanges)
+        .await?;
+    let (mut normalized, mut report) =
+        Normalizer::new(&config.discovery.quote_filter).normalize_all(&fetched);
+    overrides.apply(&fetched, &mut normalized, &mut report);
+    StatusFilter::from_config(&config.discovery).apply(&mut normalized, unix_now() * 1000);
+
+    let previous = Previous::load(&output_dir)?;
+    Ok(listings::detect(
+        &previous.symbols,
+        &previous.directions,
+        &normalized,
+        &direction_defs,
+    ))
+}
+
+/// One pass of the pipeline. Returns whether a generation was published;
+/// in daemon mode an unchanged generation is not.
+async fn run_once(args: &Args, config: &AppConfig, capture: &Capture) -> Result<bool> {
+    let config_dir = args.config_path.parent().unwrap_or(Path::new("."));
+    let exchanges = ExchangesConfig::load(&config_dir.join("exchanges.toml"))?;
+    let direction_defs = DirectionsConfig::load(&config_dir.join("directions.toml"))?;
+    let overrides = Overrides::load(&config_dir.join("overrides.toml"))?;
+    let output_dir = args
+        .output_dir
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(&config.general.generated_dir));
+    capture.save_inputs(config_dir, &output_dir)?;
+    capture.restore_previous(&output_dir, Path::new(&config.general.generated_dir))?;
+
+    let client = RestClient::new(RetryPolicy::default())?.with_capture(capture.clone());
+    let fetched = client.fetch_all(&exchanges).await?;
+
+    for source in SourceId::ALL {
+        match fetched.get(source) {
+            Some(instruments) => info!("{:<16} {} instruments", source.name(), instruments.len()),
+            None => info!("{:<16} unavailable", source.name()),
+        }
+    }
+    info!("{}/8 sources fetched", fetched.successful());
+    let tickers = client.fetch_tickers(&exchanges).await;
+
+    let normalizer = Normalizer::new(&config.discovery.quote_filter);
+    let (mut normalized, mut report) = normalizer.normalize_all(&fetched);
+    let mut applied = overrides.apply(&fetched, &mut normalized, &mut report);
+    for r in &report.rejections {
+        debug!("{} {}: {}", r.source.name(), r.exchange_symbol, r.error);
+    }
+    for ((source, kind), count) in report.counts() {
+        info!("{:<16} rejected {:<18} {}", source, kind, count);
+    }
+    info!(
+        "Normalized {} instruments, rejected {}",
+        normalized.len(),
+        report.rejections.len()
+    );
+
+    // Halted, pre-launch and soon-delisted listings cannot be tracked
+    let status = StatusFilter::from_config(&config.discovery)
+        .apply(&mut normalized, capture.unix_now() * 1000);
+    for u in &status.excluded {
+        debug!("{} {}: {}", u.source.name(), u.exchange_symbol, u.reason);
+    }
+    for ((source, kind), count) in status.counts() {
+        info!("{:<16} status   {:<18} {}", source, kind, count);
+    }
+    for d in &status.scheduled {
+        info!(
+            "{} {} ({}) scheduled to delist at {}",
+            d.source.name(),
+            d.exchange_symbol,
+            d.name,
+            d.delist_at
+        );
+    }
+
+    // Stable IDs on top of the previous generation
+    let previous = Previous::load(&output_dir)?;
+    if previous.symbols.is_empty() {
+        info!(
+            "No previous symbols.bin in {}, assigning fresh IDs",
+            output_dir.display()
+        );
+    }
+    let new_listings = listings::detect(
+        &previous.symbols,
+        &previous.directions,
+        &normalized,
+        &direction_defs,
+    );
+    for n in &new_listings {
+        info!("{}", n);
+    }
+
+    // Dead listings would only show empty-book "spreads"; a fresh listing
+    // has no 24h volume yet
+    let mut exempt = overrides.pin.clone();
+    exempt.extend(new_listings.iter().map(|n| n.name.clone()));
+    let liquidity =
+        LiquidityFilter::from_config(&config.discovery).apply(&tickers, &mut normalized, &exempt);
+    for r in &liquidity.rejected {
+        debug!("{} {}: {}", r.source.name(), r.exchange_symbol, r.reason);
+    }
+    for ((source, kind), count) in liquidity.counts() {
+        info!("{:<16} illiquid {:<18} {}", source, kind, count);
+    }
+
+    let previous_tombstones = Tombstones::load(&output_dir)?;
+    let previous_quarantine = Quarantine::load(&output_dir)?;
+    let pairing = Pairing::from_config(config);
+    let mut candidates = build_candidates(&normalized, &direction_defs, &pairing);
+
+    // Only pairs that actually stream reach the engine
+    let validator =
+        Validator::new(ValidationConfig::from_config(config)).with_capture(capture.clone());
+    let mut validation = validator
+        .validate_all(&exchanges, &candidate_table(&candidates))
+        .await?;
+    applied.extend(overrides.protect_pinned(&mut validation));
+    for sv in &validation.sources {
+        for pair in &sv.invalid {
+            debug!(
+                "{} {}: {}",
+                sv.source.name(),
+                pair.exchange_symbol,
+                pair.reason
+            );
+        }
+    }
+    for ((source, kind), count) in validation.counts() {
+        info!("{:<16} invalid  {:<18} {}", source, kind, count);
+    }
+    let before = candidates.len();
+    validation.apply(&mut candidates, &direction_defs, &pairing);
+    info!(
+        "Validated: {} invalid pairs, {} of {} symbols left",
+        validation.num_invalid(),
+        candidates.len(),
+        before
+    );
+    applied.extend(overrides.missing_pins(&candidates));
+    for o in &applied {
+        info!("Override: {}", o);
+    }
+
+    let assignment = assign_ids(
+        &previous.symbols,
+        &previous_tombstones,
+        candidates,
+        capture.unix_now(),
+        config.discovery.tombstone_grace_hours * 3600,
+    )?;
+    info!(
+        "Symbols: {} active, {} added, {} restored, {} tombstoned, {} dropped (MAX_SYMBOLS)",
+        assignment.num_active(),
+        assignment.added.len(),
+        assignment.restored.len(),
+        assignment.removed.len(),
+        assignment.dropped.len()
+    );
+
+    let mut directions = build_directions(&assignment.records, &direction_defs);
+
+    // Same ticker, different token: hold the pair out of its direction
+    let (quarantine, changes) = previous_quarantine.update(
+        &directions,
+        &assignment.records,
+        &validation,
+        &QuarantinePolicy::from_config(config),
+        capture.unix_now(),
+    );
+    for c in &changes {
+        warn!("Collision: {}", c);
+    }
+    quarantine.apply(&mut directions, &assignment.records);
+    if !quarantine.entries.is_empty() {
+        info!("{} pairs quarantined", quarantine.entries.len());
+    }
+    let listing_records = listings::records(
+        &new_listings,
+        &assignment.records,
+        &directions,
+        capture.unix_now() * 1000,
+    );
+    for d in &directions {
+        info!(
+            "Direction {} {}: {} symbols",
+            d.direction_id,
+            d.name,
+            d.symbols.len()
+        );
+    }
+
+    // Diff against the previous generation; hold back suspicious ones
+    let diff = GenerationDiff::compute(
+        &previous.symbols,
+        &previous.directions,
+        &assignment.records,
+        &directions,
+    );
+    if args.daemon && diff.is_empty() && !previous.legacy {
+        info!("No changes since the previous generation, nothing published");
+        quarantine.save(&output_dir)?;
+        return Ok(false);
+    }
+    for line in diff.summary().lines().filter(|l| !l.is_empty()) {
+        info!("{}", line);
+    }
+    let violations = diff.check_limits(&DiffLimits::from_config(&config.discovery));
+    if !violations.is_empty() {
+        if !args.force {
+            // diff.json stays with the generation that is still live
+            diff.write(&output_dir, REJECTED_DIFF_STEM)?;
+            return Err(DiscoveryError::DiffLimitsExceeded { violations }.into());
+        }
+        warn!(
+            "Diff limits exceeded, publishing anyway (--force): {}",
+            violations.join("; ")
+        );
+    }
+
+    diff.write(&output_dir, DIFF_STEM)?;
+    // State the next run starts from goes out before metadata.json, which
+    // marks the generation as published
+    assignment.tombstones.save(&output_dir)?;
+    quarantine.save(&output_dir)?;
+    let metadata = Generation {
+        symbols: &assignment.records,
+        directions: &directions,
+        validation: &validation,
+        status: &status,
+        liquidity: &liquidity,
+        overrides: &applied,
+        quarantine: &quarantine,
+        listings: &listing_records,
+        generated_at: capture.unix_now(),
+    }
+    .write(&output_dir)?;
+    info!(
+        "Wrote {} (symbols.bin v{}, {} symbols)",
+        output_dir.display(),
+        metadata.symbols_version,
+        metadata.num_symbols
+    );
+    Ok(true)
+}
+
+/// Tell running processes a new generation is out. The control segment may
+/// not exist yet (shm-init not run), which is not an error for discovery.
+fn bump_config_version(shm_name: &str) {
+    match ControlStore::open(shm_name) {
+        Ok(control) => info!("config_version -> {}", control.increment_config_version()),
+        Err(e) => warn!("Could not open control store {}: {:#}", shm_name, e),
+    }
+}
+
+/// `base` shifted by a pseudo-random offset within ±`pct`%, so several
+/// deployments do not hit the exchange APIs at the same moment.
+fn jittered(base: Duration, pct: f64) -> Duration {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    // [-1, 1) from the low bits of the clock
+    let unit = (nanos % 2_000_001) as f64 / 1_000_000.0 - 1.0;
+    base.mul_f64((1.0 + unit * pct.clamp(0.0, 100.0) / 100.0).max(0.0))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::symbols::SymbolTable;
+    use discovery::test_http::{Response, TestServer};
+    use discovery::test_ws::TestWsServer;
+
+    fn fixture(name: &str) -> String {
+        let path = format!(
+            "{}/../../crates/discovery/fixtures/rest/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("pair-discovery-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Every REST route of the four exchanges, served from the discovery
+    /// fixtures under /<exchange>.
+    fn exchange_routes(server: &TestServer) {
+        let routes = [
+            ("/binance/api/v3/exchangeInfo", "binance_spot.json"),
+            ("/binance/fapi/v1/exchangeInfo", "binance_futures.json"),
+            ("/binance/api/v3/ticker/24hr", "binance_spot_ticker.json"),
+            (
+                "/binance/fapi/v1/ticker/24hr",
+                "binance_futures_ticker.json",
+            ),
+            (
+                "/bybit/v5/market/instruments-info?category=spot&limit=1000",
+                "bybit_spot.json",
+            ),
+            (
+                "/bybit/v5/market/instruments-info?category=linear&limit=1000",
+                "bybit_linear_page1.json",
+            ),
+            (
+                "/bybit/v5/market/instruments-info?category=linear&limit=1000&cursor=page2",
+                "bybit_linear_page2.json",
+            ),
+            (
+                "/bybit/v5/market/tickers?category=spot",
+                "bybit_spot_ticker.json",
+            ),
+            (
+                "/bybit/v5/market/tickers?category=linear",
+                "bybit_linear_ticker.json",
+            ),
+            (
+                "/okx/api/v5/public/instruments?instType=SPOT",
+                "okx_spot.json",
+            ),
+            (
+                "/okx/api/v5/public/instruments?instType=SWAP",
+                "okx_swap.json",
+            ),
+            (
+                "/okx/api/v5/market/tickers?instType=SPOT",
+                "okx_spot_ticker.json",
+            ),
+            (
+                "/okx/api/v5/market/tickers?instType=SWAP",
+                "okx_swap_ticker.json",
+            ),
+            ("/mexc/api/v3/exchangeInfo", "mexc_spot.json"),
+            ("/mexc/api/v1/contract/detail", "mexc_futures.json"),
+            ("/mexc/api/v3/ticker/24hr", "mexc_spot_ticker.json"),
+            ("/mexc/api/v1/contract/ticker", "mexc_futures_ticker.json"),
+        ];
+        for (target, name) in routes {
+            server.route(target, Response::ok(fixture(name)));
+        }
+    }
+
+    /// exchanges.toml against the stand-ins: Binance streams from
+    /// `binance_ws`, the other exchanges connect to `silent_ws`.
+    fn exchanges_toml(rest: &str, binance_ws: &str, silent_ws: &str) -> String {
+        let paths = [
+            (
+                "binance",
+                binance_ws,
+                "/api/v3/exchangeInfo",
+                "/fapi/v1/exchangeInfo",
+                "/api/v3/ticker/24hr",
+                "/fapi/v1/ticker/24hr",
+            ),
+            (
+                "bybit",
+                silent_ws,
+                "/v5/market/instruments-info?category=spot",
+                "/v5/market/instruments-info?category=linear",
+                "/v5/market/tickers?category=spot",
+                "/v5/market/tickers?category=linear",
+            ),
+            (
+                "okx",
+                silent_ws,
+                "/api/v5/public/instruments?instType=SPOT",
+                "/api/v5/public/instruments?instType=SWAP",
+                "/api/v5/market/tickers?instType=SPOT",
+                "/api/v5/market/tickers?instType=SWAP",
+            ),
+            (
+                "mexc",
+                silent_ws,
+                "/api/v3/exchangeInfo",
+                "/api/v1/contract/detail",
+                "/api/v3/ticker/24hr",
+                "/api/v1/contract/ticker",
+            ),
+        ];
+        paths
+            .iter()
+            .map(|(name, ws, spot, futures, tspot, tfut)| {
+                format!(
+                    "[[exchange]]\n\
+                     name = \"{name}\"\n\
+                     rest_spot = \"{rest}/{name}\"\n\
+                     rest_futures = \"{rest}/{name}\"\n\
+                     ws_spot = \"{ws}\"\n\
+                     ws_futures = \"{ws}\"\n\
+                     max_ws_subscriptions = 200\n\
+                     validation_batch_size = 50\n\
+                     instruments_path_spot = \"{spot}\"\n\
+                     instruments_path_futures = \"{futures}\"\n\
+                     ticker_path_spot = \"{tspot}\"\n\
+                     ticker_path_futures = \"{tfut}\"\n\n"
+                )
+            })
+            .collect()
+    }
+
+    /// The repository config with `generated_dir` moved to `live` and
+    /// validation shortened to test speed.
+    fn config_toml(live: &Path) -> String {
+        let mut config = include_str!("../../../config/config.toml").to_string();
+        for (from, to) in [
+            (
+                "generated_dir = \"generated\"".to_string(),
+                format!("generated_dir = {:?}", live.display().to_string()),
+            ),
+            (
+                "validation_timeout_sec = 30".to_string(),
+                "validation_timeout_sec = 2".to_string(),
+            ),
+            (
+                "validation_idle_timeout_sec = 10".to_string(),
+                "validation_idle_timeout_sec = 1".to_string(),
+            ),
+            (
+                "validation_batch_pause_ms = 500".to_string(),
+                "validation_batch_pause_ms = 0".to_string(),
+            ),
+        ] {
+            assert!(config.contains(&from), "config.toml lost {}", from);
+            config = config.replace(&from, &to);
+        }
+        config
+    }
+
+    fn args(config_path: PathBuf, output_dir: Option<PathBuf>) -> Args {
+        Args {
+            config_path,
+            output_dir,
+            force: false,
+            daemon: false,
+            record: None,
+            replay: None,
+        }
+    }
+
+    /// Every file of a generation directory with its content.
+    fn generation(dir: &Path) -> Vec<(String, Vec<u8>)> {
+        let mut files: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
+                (name, std::fs::read(&path).unwrap())
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_fast_track_retries_until_published() {
+        let listing = |name: &str| NewListing {
+            direction_id: 0,
+            direction: "okx_spot_bybit_futures".to_string(),
+            name: name.to_string(),
+            listed_at: None,
+        };
+        let names = |fresh: Vec<(NewListing, u32)>| -> Vec<(String, u32)> {
+            fresh.into_iter().map(|(n, a)| (n.name, a)).collect()
+        };
+        let mut fast_tracked = HashMap::new();
+
+        // A run that fails to publish XYZ leaves it detected: retried up to the cap.
+        for attempt in 1..=FAST_TRACK_ATTEMPTS {
+            let fresh = fast_track(vec![listing("XYZ-USDT")], &mut fast_tracked);
+            assert_eq!(names(fresh), vec![("XYZ-USDT".to_string(), attempt)]);
+        }
+        assert!(fast_track(vec![listing("XYZ-USDT")], &mut fast_tracked).is_empty());
+
+        // Another new pair still gets its own attempts.
+        let fresh = fast_track(
+            vec![listing("XYZ-USDT"), listing("ABC-USDT")],
+            &mut fast_tracked,
+        );
+        assert_eq!(names(fresh), vec![("ABC-USDT".to_string(), 1)]);
+
+        // Once published neither is detected; a relisting starts over.
+        assert!(fast_track(Vec::new(), &mut fast_tracked).is_empty());
+        assert!(fast_tracked.is_empty());
+        let fresh = fast_track(vec![listing("XYZ-USDT")], &mut fast_tracked);
+        assert_eq!(names(fresh), vec![("XYZ-USDT".to_string(), 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_same_capture_same_generation() {
+        let rest = TestServer::start().await;
+        exchange_routes(&rest);
+        let quote = |stream: &str, symbol: &str, bid: &str, ask: &str| {
+            format!(
+                r#"{{"stream":"{}@bookTicker","data":{{"e":"bookTicker","u":1,"s":"{}","b":"{}","B":"1","a":"{}","A":"1","T":1,"E":1}}}}"#,
+                stream, symbol, bid, ask
+            )
+        };
+        let binance_ws = TestWsServer::start(vec![
+            quote("btcusdt", "BTCUSDT", "66897.98", "66897.99"),
+            quote("ethusdt", "ETHUSDT", "3513.39", "3513.40"),
+        ])
+        .await;
+        let silent_ws = TestWsServer::start(Vec::new()).await;
+
+        let root = temp_dir("capture");
+        let config_dir = root.join("config");
+        let live = root.join("generated");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        std::fs::write(config_dir.join("config.toml"), config_toml(&live)).unwrap();
+        std::fs::write(
+            config_dir.join("exchanges.toml"),
+            exchanges_toml(&rest.base_url(), &binance_ws.url(), &silent_ws.url()),
+        )
+        .unwrap();
+        std::fs::write(
+            config_dir.join("directions.toml"),
+            "[[direction]]\nid = 0\nspot_source = 0\nfutures_source = 1\n\
+             name = \"binance_spot_binance_futures\"\n",
+        )
+        .unwrap();
+
+        // Record a live run into the live generation
+        let capture_dir = root.join("capture");
+        let recording = Capture::record(&capture_dir).unwrap();
+        let record_args = args(config_dir.join("config.toml"), None);
+        let config = AppConfig::load(&record_args.config_path).unwrap();
+        assert!(run_once(&record_args, &config, &recording).await.unwrap());
+        let recorded = generation(&live);
+        let symbols = SymbolTable::load(&live).unwrap();
+        assert!(symbols.resolve(SourceId::BinanceSpot, "BTCUSDT").is_some());
+
+        // Replay twice with the recorded configs
+        let replay_config = capture_dir.join("config").join("config.toml");
+        let config = AppConfig::load(&replay_config).unwrap();
+        for run in ["first", "second"] {
+            let output = root.join(run);
+            let replay = Capture::replay(&capture_dir).unwrap();
+            let replay_args = args(replay_config.clone(), Some(output.clone()));
+            assert!(run_once(&replay_args, &config, &replay).await.unwrap());
+            assert_eq!(generation(&output), recorded, "{} replay", run);
+        }
+
+        // The live generation is not a replay target
+        let replay = Capture::replay(&capture_dir).unwrap();
+        let into_live = args(replay_config, Some(live.clone()));
+        assert!(run_once(&into_live, &config, &replay).await.is_err());
+        assert_eq!(generation(&live), recorded);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,190 @@
+//! Framing for the binary artifacts in generated/ (symbols.bin, directions.bin).
+//!
+//! Layout (little-endian), followed by the bincode payload:
+//!   magic     [u8; 4]   b"SSYM" / b"SDIR"
+//!   version   u16       SYMBOLS_VERSION / DIRECTIONS_VERSION
+//!   reserved  u16       0
+//!   len       u64       payload length
+//!   checksum  u64       FNV-1a 64 over the payload
+//!
+//! A file that is truncated, has the wrong magic (including pre-header files,
+//! which start straight with the bincode payload), a different version or a
+//! bad checksum is rejected with `ArtifactError` instead of being decoded.
+//! Each artifact is versioned on its own; bump the version whenever its
+//! record layout changes.
+
+use std::fmt;
+
+pub const HEADER_LEN: usize = 24;
+
+pub const SYMBOLS_MAGIC: [u8; 4] = *b"SSYM";
+pub const DIRECTIONS_MAGIC: [u8; 4] = *b"SDIR";
+
+/// v2: SymbolRecord::price_multiplier, v3: SymbolRecord::specs,
+/// v4: InstrumentSpec::delist_at
+pub const SYMBOLS_VERSION: u16 = 4;
+pub const DIRECTIONS_VERSION: u16 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArtifactError {
+    /// Shorter than the header or than the length it declares.
+    Truncated {
+        expected: u64,
+        actual: u64,
+    },
+    /// Not an artifact of this kind (or written before headers existed).
+    BadMagic {
+        expected: [u8; 4],
+        found: [u8; 4],
+    },
+    UnsupportedVersion {
+        found: u16,
+        supported: u16,
+    },
+    ChecksumMismatch {
+        expected: u64,
+        actual: u64,
+    },
+}
+
+impl fmt::Display for ArtifactError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArtifactError::Truncated { expected, actual } => write!(
+                f,
+                "truncated artifact: {} bytes, expected {}",
+                actual, expected
+            ),
+            ArtifactError::BadMagic { expected, found } => write!(
+                f,
+                "bad magic {:?}, expected {:?} (stale format or wrong file, re-run pair-discovery)",
+                String::from_utf8_lossy(found),
+                String::from_utf8_lossy(expected)
+            ),
+            ArtifactError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "unsupported format version {}, this build reads version {}",
+                found, supported
+            ),
+            ArtifactError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "checksum mismatch: header {:016x}, payload {:016x}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ArtifactError {}
+
+/// FNV-1a 64.
+pub fn checksum(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &b in data {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// Prepend the header to `payload`.
+pub fn encode(magic: [u8; 4], version: u16, payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_LEN + payload.len());
+    out.extend_from_slice(&magic);
+    out.extend_from_slice(&version.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    out.extend_from_slice(&checksum(payload).to_le_bytes());
+    out.extend_from_slice(payload);
+    out
+}
+
+/// Verify the header and return the payload; only `version` is accepted.
+pub fn decode(magic: [u8; 4], version: u16, data: &[u8]) -> Result<&[u8], ArtifactError> {
+    let (found, payload) = read(magic, data)?;
+    if found != version {
+        return Err(ArtifactError::UnsupportedVersion {
+            found,
+            supported: version,
+        });
+    }
+    Ok(payload)
+}
+
+/// Verify magic, length and checksum; return the version and the payload.
+pub fn read(magic: [u8; 4], data: &[u8]) -> Result<(u16, &[u8]), ArtifactError> {
+    if data.len() < HEADER_LEN {
+        return Err(ArtifactError::Truncated {
+            expected: HEADER_LEN as u64,
+            actual: data.len() as u64,
+        });
+    }
+    let found: [u8; 4] = data[0..4].try_into().unwrap();
+    if found != magic {
+        return Err(ArtifactError::BadMagic {
+            expected: magic,
+            found,
+        });
+    }
+    let version = u16::from_le_bytes(data[4..6].try_into().unwrap());
+    let len = u64::from_le_bytes(data[8..16].try_into().unwrap());
+    let expected = u64::from_le_bytes(data[16..24].try_into().unwrap());
+    let payload = &data[HEADER_LEN..];
+    if payload.len() as u64 != len {
+        return Err(ArtifactError::Truncated {
+            expected: HEADER_LEN as u64 + len,
+            actual: data.len() as u64,
+        });
+    }
+    let actual = checksum(payload);
+    if actual != expected {
+        return Err(ArtifactError::ChecksumMismatch { expected, actual });
+    }
+    Ok((version, payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_and_rejections() {
+        let data = encode(SYMBOLS_MAGIC, 1, b"payload");
+        assert_eq!(data.len(), HEADER_LEN + 7);
+        assert_eq!(decode(SYMBOLS_MAGIC, 1, &data).unwrap(), b"payload");
+        assert_eq!(read(SYMBOLS_MAGIC, &data).unwrap(), (1, &b"payload"[..]));
+
+        // Wrong kind
+        assert!(matches!(
+            decode(DIRECTIONS_MAGIC, 1, &data),
+            Err(ArtifactError::BadMagic { .. })
+        ));
+
+        // Half-written file
+        assert!(matches!(
+            decode(SYMBOLS_MAGIC, 1, &data[..data.len() - 2]),
+            Err(ArtifactError::Truncated { .. })
+        ));
+        assert!(matches!(
+            decode(SYMBOLS_MAGIC, 1, &data[..10]),
+            Err(ArtifactError::Truncated { .. })
+        ));
+
+        // Flipped payload byte
+        let mut corrupt = data.clone();
+        *corrupt.last_mut().unwrap() ^= 0xff;
+        assert!(matches!(
+            decode(SYMBOLS_MAGIC, 1, &corrupt),
+            Err(ArtifactError::ChecksumMismatch { .. })
+        ));
+
+        // Other version
+        assert_eq!(
+            decode(SYMBOLS_MAGIC, 2, &data),
+            Err(ArtifactError::UnsupportedVersion {
+                found: 1,
+                supported: 2
+            })
+        );
+    }
+}
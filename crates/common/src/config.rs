@@ -2,6 +2,8 @@ use anyhow::{Context, Result};
 use serde::Deserialize;
 use std::path::Path;
 
+use crate::types::SourceId;
+
 /// Top-level application config — loaded from config/config.toml
 #[derive(Debug, Deserialize)]
 pub struct AppConfig {
@@ -32,6 +34,13 @@ pub struct SpreadConfig {
     pub min_spread_threshold_pct: f64,
     pub staleness_max_ms: u64,
     pub converge_threshold_pct: f64,
+    /// Max ratio between the per-unit mids of one symbol on the two sides of
+    /// a direction before it is treated as a ticker collision.
+    pub collision_max_price_ratio: f64,
+    /// Also pair a base across quotes (e.g. a USDC spot listing against a
+    /// USDT perp), converting through the live stablecoin rate. Discovery
+    /// then keeps listings that only pair across quotes.
+    pub cross_quote: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -60,10 +69,40 @@ pub struct EngineConfig {
 
 #[derive(Debug, Deserialize)]
 pub struct DiscoveryConfig {
+    /// How long one WS validation batch collects top-of-book data.
     pub validation_timeout_sec: u64,
+    /// Hard cap on one batch, connect and subscribe included.
+    pub validation_batch_timeout_sec: u64,
+    /// Stop collecting early after this long without a data frame.
+    pub validation_idle_timeout_sec: u64,
+    /// Pause between consecutive batches on the same source.
+    pub validation_batch_pause_ms: u64,
+    /// Accepted quote assets. The first is the reference quote: for every
+    /// other one, the "<QUOTE>-<REFERENCE>" spot symbol is kept as its rate.
     pub quote_filter: Vec<String>,
     pub min_status: String,
+    /// Interval between runs in `pair-discovery --daemon`.
     pub cron_interval_hours: u64,
+    /// Between daemon runs, poll the instrument lists this often for new
+    /// listings; 0 disables polling.
+    pub listing_poll_interval_sec: u64,
+    /// Randomize each daemon interval by up to ± this %.
+    pub cron_jitter_pct: f64,
+    /// How long a delisted symbol_id stays retired before it may be reused.
+    pub tombstone_grace_hours: u64,
+    /// Hold back a generation that removes more than this % of symbols.
+    pub diff_max_removed_pct: f64,
+    /// Same, for the symbols listed on any single source.
+    pub diff_max_source_removed_pct: f64,
+    /// Consecutive clean runs before a quarantined ticker collision is
+    /// released back into its direction.
+    pub quarantine_release_runs: u32,
+    /// Drop a source's listing below this 24h volume, in quote currency.
+    pub min_quote_volume_24h: f64,
+    /// Drop a source's listing whose book is wider than this, in % of mid.
+    pub max_book_width_pct: f64,
+    /// Drop a source's listing scheduled to delist within this many hours.
+    pub delist_horizon_hours: u64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -96,9 +135,23 @@ pub struct ExchangeEntry {
     pub rest_futures: String,
     pub ws_spot: String,
     pub ws_futures: String,
+    /// Hot-standby endpoints: when set, every feed shard also streams from
+    /// here and the fresher copy of each update wins. May repeat the
+    /// primary URL for a second socket to the same endpoint.
+    pub ws_spot_standby: Option<String>,
+    pub ws_futures_standby: Option<String>,
     pub max_ws_subscriptions: usize,
+    /// Symbols per WS validation batch (one connection each).
+    pub validation_batch_size: usize,
     pub instruments_path_spot: String,
     pub instruments_path_futures: String,
+    /// 24h ticker statistics, used by the discovery liquidity filter.
+    pub ticker_path_spot: String,
+    pub ticker_path_futures: String,
+    /// Market data channel, for exchanges that offer a choice (okx:
+    /// "tickers" or "bbo-tbt"; mexc futures: "ticker" or "depth.full").
+    /// The parser's default when unset.
+    pub ws_channel: Option<String>,
 }
 
 impl ExchangesConfig {
@@ -109,6 +162,48 @@ impl ExchangesConfig {
             .with_context(|| format!("failed to parse exchanges config: {}", path.display()))?;
         Ok(config)
     }
+
+    /// Exchange entry serving the given source, if configured.
+    pub fn entry(&self, source: SourceId) -> Option<&ExchangeEntry> {
+        self.exchange.iter().find(|e| e.name == source.exchange())
+    }
+}
+
+impl ExchangeEntry {
+    /// Full instruments URL for the spot or futures market of this exchange.
+    pub fn instruments_url(&self, source: SourceId) -> String {
+        if source.is_spot() {
+            format!("{}{}", self.rest_spot, self.instruments_path_spot)
+        } else {
+            format!("{}{}", self.rest_futures, self.instruments_path_futures)
+        }
+    }
+
+    /// Full 24h ticker URL for the spot or futures market of this exchange.
+    pub fn ticker_url(&self, source: SourceId) -> String {
+        if source.is_spot() {
+            format!("{}{}", self.rest_spot, self.ticker_path_spot)
+        } else {
+            format!("{}{}", self.rest_futures, self.ticker_path_futures)
+        }
+    }
+
+    pub fn ws_url(&self, source: SourceId) -> &str {
+        if source.is_spot() {
+            &self.ws_spot
+        } else {
+            &self.ws_futures
+        }
+    }
+
+    /// Hot-standby WS endpoint for the spot or futures market, if any.
+    pub fn ws_standby_url(&self, source: SourceId) -> Option<&str> {
+        if source.is_spot() {
+            self.ws_spot_standby.as_deref()
+        } else {
+            self.ws_futures_standby.as_deref()
+        }
+    }
 }
 
 // === Direction Config ===
@@ -158,6 +253,8 @@ shm_control = "spread-scanner-control"
 min_spread_threshold_pct = 0.3
 staleness_max_ms = 5000
 converge_threshold_pct = 0.05
+collision_max_price_ratio = 1.5
+cross_quote = false
 
 [tracker]
 snapshot_interval_ms = 200
@@ -179,9 +276,21 @@ eventfd_coalesce_us = 200
 
 [discovery]
 validation_timeout_sec = 30
-quote_filter = ["USDT"]
+validation_batch_timeout_sec = 90
+validation_idle_timeout_sec = 10
+validation_batch_pause_ms = 500
+quote_filter = ["USDT", "USDC", "FDUSD"]
 min_status = "TRADING"
 cron_interval_hours = 6
+listing_poll_interval_sec = 60
+cron_jitter_pct = 10.0
+tombstone_grace_hours = 72
+diff_max_removed_pct = 10.0
+diff_max_source_removed_pct = 25.0
+quarantine_release_runs = 3
+min_quote_volume_24h = 100000.0
+max_book_width_pct = 1.0
+delist_horizon_hours = 24
 
 [monitoring]
 prometheus_enabled = false
@@ -190,6 +299,13 @@ stats_log_interval_sec = 10
         let config: AppConfig = toml::from_str(toml_str).unwrap();
         assert_eq!(config.spread.min_spread_threshold_pct, 0.3);
         assert_eq!(config.ws.max_subscriptions_per_conn, 200);
-        assert_eq!(config.discovery.quote_filter, vec!["USDT"]);
+        assert_eq!(config.discovery.quote_filter, vec!["USDT", "USDC", "FDUSD"]);
+        assert!(!config.spread.cross_quote);
+        assert_eq!(config.discovery.tombstone_grace_hours, 72);
+        assert_eq!(config.discovery.validation_batch_timeout_sec, 90);
+        assert_eq!(config.discovery.cron_jitter_pct, 10.0);
+        assert_eq!(config.discovery.quarantine_release_runs, 3);
+        assert_eq!(config.discovery.min_quote_volume_24h, 100000.0);
+        assert_eq!(config.spread.collision_max_price_ratio, 1.5);
     }
 }
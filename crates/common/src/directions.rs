@@ -2,6 +2,7 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
+use crate::artifact::{self, ArtifactError, DIRECTIONS_MAGIC, DIRECTIONS_VERSION};
 use crate::types::{DirectionEntry, NUM_SOURCES};
 
 /// A single direction record — stored in generated/directions.bin
@@ -20,14 +21,42 @@ pub struct DirectionTable {
 }
 
 impl DirectionTable {
+    /// Load from generated/directions.bin, header verified.
     pub fn load(generated_dir: &Path) -> Result<Self> {
         let path = generated_dir.join("directions.bin");
         let data = std::fs::read(&path)
             .with_context(|| format!("failed to read {}", path.display()))?;
-        let records: Vec<DirectionRecord> = bincode::deserialize(&data)
-            .with_context(|| format!("failed to deserialize {}", path.display()))?;
+        let records = Self::decode(&data)
+            .with_context(|| format!("failed to load {}", path.display()))?;
         Ok(Self { records })
     }
+
+    /// directions.bin contents for `records`, header included.
+    pub fn encode(records: &[DirectionRecord]) -> Result<Vec<u8>> {
+        Ok(artifact::encode(
+            DIRECTIONS_MAGIC,
+            DIRECTIONS_VERSION,
+            &bincode::serialize(records)?,
+        ))
+    }
+
+    /// Parse directions.bin contents.
+    pub fn decode(data: &[u8]) -> Result<Vec<DirectionRecord>> {
+        let payload = artifact::decode(DIRECTIONS_MAGIC, DIRECTIONS_VERSION, data)?;
+        Ok(bincode::deserialize(payload)?)
+    }
+
+    /// Like `decode`, but also reads unheadered files; the flag is true for
+    /// those. For discovery only, see `SymbolTable::decode_compat`.
+    pub fn decode_compat(data: &[u8]) -> Result<(Vec<DirectionRecord>, bool)> {
+        match Self::decode(data) {
+            Ok(records) => Ok((records, false)),
+            Err(e) if matches!(e.downcast_ref(), Some(ArtifactError::BadMagic { .. })) => {
+                Ok((bincode::deserialize(data)?, true))
+            }
+            Err(e) => Err(e),
+        }
+    }
 }
 
 // === SourceSymbolIndex — flat array for Engine hot path ===
@@ -159,8 +188,8 @@ mod tests {
             symbols: vec![0, 1, 2, 3],
         }];
 
-        let data = bincode::serialize(&records).unwrap();
-        let decoded: Vec<DirectionRecord> = bincode::deserialize(&data).unwrap();
+        let data = DirectionTable::encode(&records).unwrap();
+        let decoded = DirectionTable::decode(&data).unwrap();
         assert_eq!(decoded[0].symbols.len(), 4);
         assert_eq!(decoded[0].name, "okx_spot_mexc_futures");
     }
@@ -1,4 +1,6 @@
+pub mod artifact;
 pub mod config;
 pub mod directions;
+pub mod listings;
 pub mod symbols;
 pub mod types;
@@ -0,0 +1,46 @@
+//! New listings of a generation — generated/listings.json.
+//!
+//! Discovery records every pair that became tradable on both sources of a
+//! direction since the previous generation; the engine turns each record into
+//! a NewListing event when it loads the generation. The file is rewritten
+//! with every generation, so it only ever holds that generation's listings.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+pub const LISTINGS_FILE: &str = "listings.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListingRecord {
+    pub direction_id: u8,
+    pub symbol_id: u16,
+    /// Canonical name, e.g. "XYZ-USDT".
+    pub name: String,
+    pub spot_source: u8,
+    pub futures_source: u8,
+    /// Unix milliseconds.
+    pub detected_at: u64,
+    /// Listing time of the newer leg, unix milliseconds, if the venue says.
+    pub listed_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Listings {
+    pub listings: Vec<ListingRecord>,
+}
+
+impl Listings {
+    /// Load generated/listings.json; a missing file means no new listings.
+    pub fn load(generated_dir: &Path) -> Result<Self> {
+        let path = generated_dir.join(LISTINGS_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse {}", path.display()))
+    }
+}
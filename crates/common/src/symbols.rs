@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
 
+use crate::artifact::{self, ArtifactError, SYMBOLS_MAGIC, SYMBOLS_VERSION};
 use crate::types::{SourceId, NUM_SOURCES};
 
 /// A single symbol record — stored in generated/symbols.bin
@@ -13,6 +14,205 @@ pub struct SymbolRecord {
     pub source_names: [Option<String>; NUM_SOURCES as usize],
     pub min_qty: [Option<f64>; NUM_SOURCES as usize],
     pub tick_size: [Option<f64>; NUM_SOURCES as usize],
+    /// Base units one exchange price is quoted for (1000 for Binance
+    /// "1000PEPEUSDT", 1 almost everywhere else). Exchange prices are divided
+    /// by it before they reach PriceStore, so all sources hold per-unit prices.
+    pub price_multiplier: [f64; NUM_SOURCES as usize],
+    /// Remaining instrument specs, per source.
+    pub specs: [InstrumentSpec; NUM_SOURCES as usize],
+}
+
+impl SymbolRecord {
+    /// A record listed on no source yet: no exchange names or specs, unit
+    /// price multipliers.
+    pub fn new(symbol_id: u16, name: impl Into<String>) -> Self {
+        Self {
+            symbol_id,
+            name: name.into(),
+            source_names: Default::default(),
+            min_qty: [None; NUM_SOURCES as usize],
+            tick_size: [None; NUM_SOURCES as usize],
+            price_multiplier: [1.0; NUM_SOURCES as usize],
+            specs: Default::default(),
+        }
+    }
+
+    /// The same record, listed on `source` as `exchange_name`.
+    pub fn with_source(mut self, source: SourceId, exchange_name: impl Into<String>) -> Self {
+        self.source_names[source.index()] = Some(exchange_name.into());
+        self
+    }
+}
+
+/// Discovery publishes only `Spot` and `LinearPerpetual` today; the other
+/// variants keep the format stable should dated or inverse contracts be
+/// admitted later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContractType {
+    Spot,
+    LinearPerpetual,
+    InversePerpetual,
+    LinearDated,
+    InverseDated,
+}
+
+impl ContractType {
+    pub fn is_perpetual(self) -> bool {
+        matches!(
+            self,
+            ContractType::LinearPerpetual | ContractType::InversePerpetual
+        )
+    }
+
+    pub fn is_inverse(self) -> bool {
+        matches!(
+            self,
+            ContractType::InversePerpetual | ContractType::InverseDated
+        )
+    }
+}
+
+/// Instrument specs of one listing beyond `min_qty`/`tick_size`. Quantities
+/// are in base units (contracts already scaled by their size), notionals in
+/// the quote asset. `None` where the source does not publish the field.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InstrumentSpec {
+    pub max_qty: Option<f64>,
+    pub min_notional: Option<f64>,
+    /// Quantity step.
+    pub lot_size: Option<f64>,
+    pub contract_type: Option<ContractType>,
+    /// Settlement / margin asset of a derivative, e.g. "USDT".
+    pub settle_asset: Option<String>,
+    /// Unix milliseconds.
+    pub listed_at: Option<u64>,
+    /// Scheduled delisting / delivery, unix milliseconds. `None` when the
+    /// venue has not announced one.
+    pub delist_at: Option<u64>,
+}
+
+/// `InstrumentSpec` before `delist_at` (format version 3).
+#[derive(Deserialize)]
+struct InstrumentSpecV3 {
+    max_qty: Option<f64>,
+    min_notional: Option<f64>,
+    lot_size: Option<f64>,
+    contract_type: Option<ContractType>,
+    settle_asset: Option<String>,
+    listed_at: Option<u64>,
+}
+
+impl From<InstrumentSpecV3> for InstrumentSpec {
+    fn from(s: InstrumentSpecV3) -> Self {
+        Self {
+            max_qty: s.max_qty,
+            min_notional: s.min_notional,
+            lot_size: s.lot_size,
+            contract_type: s.contract_type,
+            settle_asset: s.settle_asset,
+            listed_at: s.listed_at,
+            delist_at: None,
+        }
+    }
+}
+
+/// symbols.bin record layout before `InstrumentSpec::delist_at` (format
+/// version 3). Only read to migrate the previous generation.
+#[derive(Deserialize)]
+struct SymbolRecordV3 {
+    symbol_id: u16,
+    name: String,
+    source_names: [Option<String>; NUM_SOURCES as usize],
+    min_qty: [Option<f64>; NUM_SOURCES as usize],
+    tick_size: [Option<f64>; NUM_SOURCES as usize],
+    price_multiplier: [f64; NUM_SOURCES as usize],
+    specs: [InstrumentSpecV3; NUM_SOURCES as usize],
+}
+
+impl From<SymbolRecordV3> for SymbolRecord {
+    fn from(r: SymbolRecordV3) -> Self {
+        Self {
+            symbol_id: r.symbol_id,
+            name: r.name,
+            source_names: r.source_names,
+            min_qty: r.min_qty,
+            tick_size: r.tick_size,
+            price_multiplier: r.price_multiplier,
+            specs: r.specs.map(InstrumentSpec::from),
+        }
+    }
+}
+
+/// symbols.bin record layout before `specs` (format version 2). Only read to
+/// migrate the previous generation.
+#[derive(Deserialize)]
+struct SymbolRecordV2 {
+    symbol_id: u16,
+    name: String,
+    source_names: [Option<String>; NUM_SOURCES as usize],
+    min_qty: [Option<f64>; NUM_SOURCES as usize],
+    tick_size: [Option<f64>; NUM_SOURCES as usize],
+    price_multiplier: [f64; NUM_SOURCES as usize],
+}
+
+impl From<SymbolRecordV2> for SymbolRecord {
+    fn from(r: SymbolRecordV2) -> Self {
+        Self {
+            symbol_id: r.symbol_id,
+            name: r.name,
+            source_names: r.source_names,
+            min_qty: r.min_qty,
+            tick_size: r.tick_size,
+            price_multiplier: r.price_multiplier,
+            specs: Default::default(),
+        }
+    }
+}
+
+/// symbols.bin record layout before `price_multiplier` (unheadered files
+/// and format version 1). Only read to migrate the previous generation.
+#[derive(Deserialize)]
+struct SymbolRecordV1 {
+    symbol_id: u16,
+    name: String,
+    source_names: [Option<String>; NUM_SOURCES as usize],
+    min_qty: [Option<f64>; NUM_SOURCES as usize],
+    tick_size: [Option<f64>; NUM_SOURCES as usize],
+}
+
+impl From<SymbolRecordV1> for SymbolRecord {
+    fn from(r: SymbolRecordV1) -> Self {
+        Self {
+            symbol_id: r.symbol_id,
+            name: r.name,
+            source_names: r.source_names,
+            min_qty: r.min_qty,
+            tick_size: r.tick_size,
+            price_multiplier: [1.0; NUM_SOURCES as usize],
+            specs: Default::default(),
+        }
+    }
+}
+
+/// Split a canonical "BASE-QUOTE" name into (base, quote). None unless both
+/// halves are valid assets (see `is_asset`).
+pub fn split_name(name: &str) -> Option<(&str, &str)> {
+    let (base, quote) = name.rsplit_once('-')?;
+    (is_asset(base) && is_asset(quote)).then_some((base, quote))
+}
+
+/// Whether `asset` may appear in a canonical name: uppercase ASCII letters
+/// and digits only.
+pub fn is_asset(asset: &str) -> bool {
+    !asset.is_empty()
+        && asset
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+}
+
+/// Name of the symbol that prices `quote` in `reference`, e.g. "USDC-USDT".
+pub fn rate_symbol(quote: &str, reference: &str) -> String {
+    format!("{}-{}", quote, reference)
 }
 
 /// Subscription entry for a source — symbol_id + exchange-specific name.
@@ -20,6 +220,8 @@ pub struct SymbolRecord {
 pub struct SymbolSub {
     pub symbol_id: u16,
     pub exchange_name: String,
+    /// See `SymbolRecord::price_multiplier`.
+    pub price_multiplier: f64,
 }
 
 /// Global symbol table — loaded from generated/symbols.bin.
@@ -34,14 +236,65 @@ pub struct SymbolTable {
 }
 
 impl SymbolTable {
-    /// Load from generated/symbols.bin
+    /// Load from generated/symbols.bin; a missing header, bad checksum or
+    /// other format version is an error, never a panic.
     pub fn load(generated_dir: &Path) -> Result<Self> {
         let path = generated_dir.join("symbols.bin");
-        let data = std::fs::read(&path)
-            .with_context(|| format!("failed to read {}", path.display()))?;
-        let records: Vec<SymbolRecord> = bincode::deserialize(&data)
-            .with_context(|| format!("failed to deserialize {}", path.display()))?;
+        let data =
+            std::fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        let records =
+            Self::decode(&data).with_context(|| format!("failed to load {}", path.display()))?;
+        Ok(Self::from_records(records))
+    }
 
+    /// symbols.bin contents for `records`, header included.
+    pub fn encode(records: &[SymbolRecord]) -> Result<Vec<u8>> {
+        Ok(artifact::encode(
+            SYMBOLS_MAGIC,
+            SYMBOLS_VERSION,
+            &bincode::serialize(records)?,
+        ))
+    }
+
+    /// Parse symbols.bin contents.
+    pub fn decode(data: &[u8]) -> Result<Vec<SymbolRecord>> {
+        let payload = artifact::decode(SYMBOLS_MAGIC, SYMBOLS_VERSION, data)?;
+        Ok(bincode::deserialize(payload)?)
+    }
+
+    /// Like `decode`, but also migrates older layouts (unheadered files and
+    /// earlier versions). The flag is true if the data was in an old layout.
+    /// For discovery only; feeds must not run on a stale generation.
+    pub fn decode_compat(data: &[u8]) -> Result<(Vec<SymbolRecord>, bool)> {
+        let v1 = |payload: &[u8]| -> Result<Vec<SymbolRecord>> {
+            let records: Vec<SymbolRecordV1> = bincode::deserialize(payload)?;
+            Ok(records.into_iter().map(SymbolRecord::from).collect())
+        };
+        let v2 = |payload: &[u8]| -> Result<Vec<SymbolRecord>> {
+            let records: Vec<SymbolRecordV2> = bincode::deserialize(payload)?;
+            Ok(records.into_iter().map(SymbolRecord::from).collect())
+        };
+        let v3 = |payload: &[u8]| -> Result<Vec<SymbolRecord>> {
+            let records: Vec<SymbolRecordV3> = bincode::deserialize(payload)?;
+            Ok(records.into_iter().map(SymbolRecord::from).collect())
+        };
+        match artifact::read(SYMBOLS_MAGIC, data) {
+            Ok((SYMBOLS_VERSION, payload)) => Ok((bincode::deserialize(payload)?, false)),
+            Ok((3, payload)) => Ok((v3(payload)?, true)),
+            Ok((2, payload)) => Ok((v2(payload)?, true)),
+            Ok((1, payload)) => Ok((v1(payload)?, true)),
+            Ok((found, _)) => Err(ArtifactError::UnsupportedVersion {
+                found,
+                supported: SYMBOLS_VERSION,
+            }
+            .into()),
+            Err(ArtifactError::BadMagic { .. }) => Ok((v1(data)?, true)),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Build the lookup tables from dense records (`records[i].symbol_id == i`).
+    pub fn from_records(records: Vec<SymbolRecord>) -> Self {
         let num_symbols = records.len() as u16;
 
         let mut exchange_to_id: [HashMap<String, u16>; NUM_SOURCES as usize] =
@@ -51,20 +304,20 @@ impl SymbolTable {
 
         for record in &records {
             id_to_name.push(record.name.clone());
-            for source_idx in 0..NUM_SOURCES as usize {
-                if let Some(ref exch_name) = record.source_names[source_idx] {
+            for (source_idx, source_name) in record.source_names.iter().enumerate() {
+                if let Some(exch_name) = source_name {
                     exchange_to_id[source_idx]
                         .insert(exch_name.clone(), record.symbol_id);
                 }
             }
         }
 
-        Ok(Self {
+        Self {
             records,
             num_symbols,
             exchange_to_id,
             id_to_name,
-        })
+        }
     }
 
     /// Resolve exchange-specific symbol name to global symbol_id.
@@ -81,6 +334,58 @@ impl SymbolTable {
         self.num_symbols
     }
 
+    /// See `SymbolRecord::price_multiplier`.
+    pub fn price_multiplier(&self, source: SourceId, symbol_id: u16) -> f64 {
+        self.records[symbol_id as usize].price_multiplier[source.index()]
+    }
+
+    pub fn min_qty(&self, source: SourceId, symbol_id: u16) -> Option<f64> {
+        self.records[symbol_id as usize].min_qty[source.index()]
+    }
+
+    pub fn tick_size(&self, source: SourceId, symbol_id: u16) -> Option<f64> {
+        self.records[symbol_id as usize].tick_size[source.index()]
+    }
+
+    /// Full specs of the listing, `None` if `source` does not list the symbol.
+    pub fn spec(&self, source: SourceId, symbol_id: u16) -> Option<&InstrumentSpec> {
+        let record = &self.records[symbol_id as usize];
+        record.source_names[source.index()]
+            .as_ref()
+            .map(|_| &record.specs[source.index()])
+    }
+
+    pub fn max_qty(&self, source: SourceId, symbol_id: u16) -> Option<f64> {
+        self.spec(source, symbol_id).and_then(|s| s.max_qty)
+    }
+
+    pub fn min_notional(&self, source: SourceId, symbol_id: u16) -> Option<f64> {
+        self.spec(source, symbol_id).and_then(|s| s.min_notional)
+    }
+
+    pub fn lot_size(&self, source: SourceId, symbol_id: u16) -> Option<f64> {
+        self.spec(source, symbol_id).and_then(|s| s.lot_size)
+    }
+
+    pub fn contract_type(&self, source: SourceId, symbol_id: u16) -> Option<ContractType> {
+        self.spec(source, symbol_id).and_then(|s| s.contract_type)
+    }
+
+    pub fn settle_asset(&self, source: SourceId, symbol_id: u16) -> Option<&str> {
+        self.spec(source, symbol_id)
+            .and_then(|s| s.settle_asset.as_deref())
+    }
+
+    /// Unix milliseconds.
+    pub fn listed_at(&self, source: SourceId, symbol_id: u16) -> Option<u64> {
+        self.spec(source, symbol_id).and_then(|s| s.listed_at)
+    }
+
+    /// Scheduled delisting, unix milliseconds.
+    pub fn delist_at(&self, source: SourceId, symbol_id: u16) -> Option<u64> {
+        self.spec(source, symbol_id).and_then(|s| s.delist_at)
+    }
+
     /// Build subscription list for a specific source — all symbols present on that source.
     pub fn subscription_list(&self, source: SourceId) -> Vec<SymbolSub> {
         let idx = source.index();
@@ -90,6 +395,7 @@ impl SymbolTable {
                 rec.source_names[idx].as_ref().map(|name| SymbolSub {
                     symbol_id: rec.symbol_id,
                     exchange_name: name.clone(),
+                    price_multiplier: rec.price_multiplier[idx],
                 })
             })
             .collect()
@@ -102,7 +408,7 @@ mod tests {
 
     #[test]
     fn test_symbol_table_roundtrip() {
-        let records = vec![
+        let mut records = vec![
             SymbolRecord {
                 symbol_id: 0,
                 name: "BTC-USDT".to_string(),
@@ -118,13 +424,122 @@ mod tests {
                 ],
                 min_qty: [None; 8],
                 tick_size: [None; 8],
+                price_multiplier: [1.0; 8],
+                specs: Default::default(),
             },
         ];
 
         // Serialize + deserialize
-        let data = bincode::serialize(&records).unwrap();
-        let decoded: Vec<SymbolRecord> = bincode::deserialize(&data).unwrap();
+        records[0].specs[7] = InstrumentSpec {
+            max_qty: Some(1_000_000.0),
+            lot_size: Some(0.0001),
+            contract_type: Some(ContractType::LinearPerpetual),
+            settle_asset: Some("USDT".to_string()),
+            listed_at: Some(1_573_557_408_000),
+            delist_at: Some(1_900_000_000_000),
+            ..Default::default()
+        };
+        let data = SymbolTable::encode(&records).unwrap();
+        let decoded = SymbolTable::decode(&data).unwrap();
         assert_eq!(decoded[0].name, "BTC-USDT");
         assert_eq!(decoded[0].source_names[0], Some("BTCUSDT".to_string()));
+
+        let table = SymbolTable::from_records(decoded);
+        let okx = SourceId::OkxFutures;
+        assert_eq!(table.lot_size(okx, 0), Some(0.0001));
+        assert_eq!(table.settle_asset(okx, 0), Some("USDT"));
+        assert!(table.contract_type(okx, 0).unwrap().is_perpetual());
+        assert_eq!(table.min_notional(okx, 0), None);
+        assert_eq!(table.delist_at(okx, 0), Some(1_900_000_000_000));
+        assert_eq!(table.contract_type(SourceId::BinanceSpot, 0), None);
+
+        // Pre-header files are rejected, not misparsed
+        let legacy = bincode::serialize(&records).unwrap();
+        let err = SymbolTable::decode(&legacy).unwrap_err();
+        assert!(err.downcast_ref::<ArtifactError>().is_some());
+    }
+
+    #[test]
+    fn test_split_name() {
+        assert_eq!(split_name("1INCH-USDT"), Some(("1INCH", "USDT")));
+        for bad in ["BTCUSDT", "BTC-", "-USDT", "btc-USDT", "BTC-USDT-SWAP"] {
+            assert_eq!(split_name(bad), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_decode_compat_migrates_v1() {
+        #[derive(Serialize)]
+        struct V1<'a> {
+            symbol_id: u16,
+            name: &'a str,
+            source_names: [Option<&'a str>; 8],
+            min_qty: [Option<f64>; 8],
+            tick_size: [Option<f64>; 8],
+        }
+        let old = vec![V1 {
+            symbol_id: 0,
+            name: "PEPE-USDT",
+            source_names: [
+                None,
+                Some("1000PEPEUSDT"),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            ],
+            min_qty: [None; 8],
+            tick_size: [None; 8],
+        }];
+        let payload = bincode::serialize(&old).unwrap();
+
+        for data in [
+            payload.clone(),
+            artifact::encode(SYMBOLS_MAGIC, 1, &payload),
+        ] {
+            let (records, migrated) = SymbolTable::decode_compat(&data).unwrap();
+            assert!(migrated);
+            assert_eq!(records[0].name, "PEPE-USDT");
+            assert_eq!(records[0].price_multiplier, [1.0; 8]);
+            // Feeds refuse both
+            assert!(SymbolTable::decode(&data).is_err());
+        }
+
+        // v2: price_multiplier, no specs
+        let v2 = vec![(&old[0], [1.0, 1000.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0f64])];
+        let data = artifact::encode(SYMBOLS_MAGIC, 2, &bincode::serialize(&v2).unwrap());
+        let (records, migrated) = SymbolTable::decode_compat(&data).unwrap();
+        assert!(migrated);
+        assert_eq!(records[0].price_multiplier[1], 1000.0);
+        assert_eq!(records[0].specs[1], InstrumentSpec::default());
+
+        // v3: specs without delist_at
+        type SpecV3 = (
+            Option<f64>,
+            Option<f64>,
+            Option<f64>,
+            Option<ContractType>,
+            Option<String>,
+            Option<u64>,
+        );
+        let specs: [SpecV3; 8] = std::array::from_fn(|i| {
+            let listed_at = (i == 1).then_some(1_700_000_000_000);
+            (None, None, Some(1.0), None, None, listed_at)
+        });
+        let v3 = vec![(&old[0], [1.0; 8], specs)];
+        let data = artifact::encode(SYMBOLS_MAGIC, 3, &bincode::serialize(&v3).unwrap());
+        let (records, migrated) = SymbolTable::decode_compat(&data).unwrap();
+        assert!(migrated);
+        assert_eq!(records[0].specs[1].listed_at, Some(1_700_000_000_000));
+        assert_eq!(records[0].specs[1].lot_size, Some(1.0));
+        assert_eq!(records[0].specs[1].delist_at, None);
+
+        let current =
+            SymbolTable::encode(&SymbolTable::decode_compat(&payload).unwrap().0).unwrap();
+        let (records, migrated) = SymbolTable::decode_compat(&current).unwrap();
+        assert!(!migrated);
+        assert_eq!(records.len(), 1);
     }
 }
@@ -22,6 +22,18 @@ pub enum SourceId {
 }
 
 impl SourceId {
+    /// All sources in source_id order.
+    pub const ALL: [SourceId; NUM_SOURCES as usize] = [
+        SourceId::BinanceSpot,
+        SourceId::BinanceFutures,
+        SourceId::BybitSpot,
+        SourceId::BybitFutures,
+        SourceId::MexcSpot,
+        SourceId::MexcFutures,
+        SourceId::OkxSpot,
+        SourceId::OkxFutures,
+    ];
+
     pub fn index(self) -> usize {
         self as usize
     }
@@ -53,6 +65,21 @@ impl SourceId {
         }
     }
 
+    /// Exchange name as used in config/exchanges.toml.
+    pub fn exchange(self) -> &'static str {
+        match self {
+            SourceId::BinanceSpot | SourceId::BinanceFutures => "binance",
+            SourceId::BybitSpot | SourceId::BybitFutures => "bybit",
+            SourceId::MexcSpot | SourceId::MexcFutures => "mexc",
+            SourceId::OkxSpot | SourceId::OkxFutures => "okx",
+        }
+    }
+
+    /// Inverse of `name()`: "binance_spot" → BinanceSpot.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.name() == name)
+    }
+
     pub fn from_u8(v: u8) -> Option<Self> {
         match v {
             0 => Some(SourceId::BinanceSpot),
@@ -108,6 +135,33 @@ impl PriceSnapshot {
     pub fn is_valid(&self) -> bool {
         self.best_bid > 0.0 && self.best_ask > 0.0 && self.best_bid <= self.best_ask
     }
+
+    pub fn mid(&self) -> f64 {
+        (self.best_bid + self.best_ask) / 2.0
+    }
+
+    /// Prices for one base unit, given the source's price multiplier
+    /// (see `SymbolRecord::price_multiplier`).
+    pub fn per_unit(self, price_multiplier: f64) -> Self {
+        if price_multiplier == 1.0 {
+            return self;
+        }
+        Self {
+            best_bid: self.best_bid / price_multiplier,
+            best_ask: self.best_ask / price_multiplier,
+            ..self
+        }
+    }
+}
+
+/// Ratio of the larger to the smaller of two mid prices (≥ 1). Far above 1
+/// means the two venues almost certainly list different tokens under the
+/// same ticker. Infinite if either price is not positive.
+pub fn price_ratio(a: f64, b: f64) -> f64 {
+    if !(a > 0.0 && b > 0.0) {
+        return f64::INFINITY;
+    }
+    a.max(b) / a.min(b)
 }
 
 // === Events ===
@@ -117,6 +171,8 @@ impl PriceSnapshot {
 pub enum EventType {
     SpreadSignal = 1,
     TrackingSnapshot = 2,
+    /// A pair just became tradable on both sources of a direction.
+    NewListing = 3,
     // Reserved: 10..15 orders, 20..22 positions, 90..99 control, 100..103 health
 }
 
@@ -125,6 +181,7 @@ impl EventType {
         match v {
             1 => Some(EventType::SpreadSignal),
             2 => Some(EventType::TrackingSnapshot),
+            3 => Some(EventType::NewListing),
             _ => None,
         }
     }
@@ -200,6 +257,46 @@ impl SignalPayload {
     }
 }
 
+/// Payload for NewListing events.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ListingPayload {
+    pub symbol_id: u16,
+    pub direction_id: u8,
+    pub spot_source: u8,
+    pub futures_source: u8,
+    pub _pad: [u8; 3],
+    /// When discovery saw the pair on both sources, unix ms.
+    pub detected_at: u64,
+    /// Listing time of the newer leg, unix ms; 0 if unknown.
+    pub listed_at: u64,
+}
+
+impl ListingPayload {
+    pub const SIZE: usize = std::mem::size_of::<Self>();
+
+    pub fn from_event(event: &Event) -> Option<Self> {
+        if event.header.event_type != EventType::NewListing as u16 {
+            return None;
+        }
+        if (event.header.payload_len as usize) < Self::SIZE {
+            return None;
+        }
+        // Safety: ListingPayload is repr(C) and fits within 40 bytes
+        let ptr = event.payload.as_ptr() as *const ListingPayload;
+        Some(unsafe { ptr.read_unaligned() })
+    }
+
+    pub fn write_to_event(&self, event: &mut Event) {
+        let src = self as *const ListingPayload as *const u8;
+        let dst = event.payload.as_mut_ptr();
+        unsafe {
+            std::ptr::copy_nonoverlapping(src, dst, Self::SIZE);
+        }
+        event.header.payload_len = Self::SIZE as u16;
+    }
+}
+
 /// Direction entry — maps (source, symbol) to a direction and counterpart.
 #[derive(Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
 pub struct DirectionEntry {
@@ -216,6 +313,7 @@ const _: () = {
     assert!(std::mem::align_of::<PriceDataEntry>() == 64);
     assert!(std::mem::size_of::<Event>() == 64);
     assert!(SignalPayload::SIZE <= 40);
+    assert!(ListingPayload::SIZE <= 40);
 };
 
 #[cfg(test)]
@@ -230,6 +328,15 @@ mod tests {
         assert!(SourceId::BinanceFutures.is_futures());
         assert_eq!(SourceId::from_u8(0), Some(SourceId::BinanceSpot));
         assert_eq!(SourceId::from_u8(8), None);
+        assert_eq!(
+            SourceId::from_name("okx_futures"),
+            Some(SourceId::OkxFutures)
+        );
+        assert_eq!(SourceId::from_name("okx_swap"), None);
+        for (i, source) in SourceId::ALL.iter().enumerate() {
+            assert_eq!(source.index(), i);
+        }
+        assert_eq!(SourceId::OkxFutures.exchange(), "okx");
     }
 
     #[test]
@@ -298,5 +405,16 @@ mod tests {
             updated_at: 1,
         };
         assert!(!crossed.is_valid());
+
+        // 1000PEPE quote → per-PEPE price
+        let unit = snap.per_unit(1000.0);
+        assert_eq!(unit.best_bid, 0.1);
+        assert_eq!(unit.best_ask, 0.101);
+        assert_eq!(unit.updated_at, 1);
+        assert_eq!(unit.mid(), 0.1005);
+
+        assert_eq!(price_ratio(2.0, 1.0), 2.0);
+        assert_eq!(price_ratio(1.0, 2.0), 2.0);
+        assert_eq!(price_ratio(0.0, 2.0), f64::INFINITY);
     }
 }
@@ -0,0 +1,374 @@
+//! Offline capture — `pair-discovery --record <dir>` / `--replay <dir>`.
+//!
+//! A recording run saves every REST body and every validation WS frame it
+//! receives; a replay run reads them back instead of touching the network,
+//! so the whole pipeline runs again on exactly the same inputs. Layout:
+//!
+//!   capture.json                       `CaptureManifest` (clock of the run)
+//!   config/                            config.toml, exchanges.toml,
+//!                                      directions.toml, overrides.toml
+//!   previous/                          the generation state the run started
+//!                                      from (symbols.bin, tombstones, ...)
+//!   rest/<source>_instruments_<n>.json page n of the instrument list
+//!   rest/<source>_tickers.json         24h tickers
+//!   ws/<source>_<batch>.json           `WsCapture` of one validation batch
+//!
+//! Both modes freeze the clock at `captured_at`, so the recording run and
+//! its replays stamp tombstones, quarantine and metadata identically. A
+//! request that failed while recording has no file and fails again on
+//! replay; the source is unavailable both times.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use common::types::SourceId;
+use feeds::parser::Frame;
+
+use crate::quarantine::QUARANTINE_FILE;
+use crate::registry::TOMBSTONES_FILE;
+
+pub const CAPTURE_FILE: &str = "capture.json";
+
+/// Config files copied next to a recording, read from the config directory.
+pub const CONFIG_FILES: [&str; 4] = [
+    "config.toml",
+    "exchanges.toml",
+    "directions.toml",
+    "overrides.toml",
+];
+
+/// Generation state a run reads from the output directory.
+pub const STATE_FILES: [&str; 4] = [
+    "symbols.bin",
+    "directions.bin",
+    TOMBSTONES_FILE,
+    QUARANTINE_FILE,
+];
+
+/// capture.json
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CaptureManifest {
+    /// Unix seconds; the clock of the recording run and of every replay.
+    pub captured_at: u64,
+}
+
+/// One validation batch: the exchange symbols it subscribed to, the frames
+/// that arrived, and the error that ended it, if any.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WsCapture {
+    pub symbols: Vec<String>,
+    pub frames: Vec<WsFrame>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WsFrame {
+    /// Unix microseconds, as handed to the parser.
+    pub received_at: u64,
+    pub data: FrameData,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameData {
+    Text(String),
+    /// Hex in the file (MEXC spot streams protobuf).
+    Binary(#[serde(with = "hex")] Vec<u8>),
+}
+
+impl WsFrame {
+    pub fn frame(&self) -> Frame<'_> {
+        match &self.data {
+            FrameData::Text(text) => Frame::Text(text),
+            FrameData::Binary(data) => Frame::Binary(data),
+        }
+    }
+}
+
+/// Where a run gets its exchange data from.
+#[derive(Debug, Clone, Default)]
+pub enum Capture {
+    /// Network only.
+    #[default]
+    Live,
+    /// Network, saving every response under `dir`.
+    Record { dir: PathBuf, captured_at: u64 },
+    /// Files under `dir` only.
+    Replay { dir: PathBuf, captured_at: u64 },
+}
+
+impl Capture {
+    /// Start a recording in `dir`, which must not hold one already.
+    pub fn record(dir: &Path) -> Result<Self> {
+        let manifest_path = dir.join(CAPTURE_FILE);
+        if manifest_path.exists() {
+            anyhow::bail!("{} already holds a capture", dir.display());
+        }
+        for sub in ["config", "previous", "rest", "ws"] {
+            std::fs::create_dir_all(dir.join(sub))
+                .with_context(|| format!("failed to create {}", dir.join(sub).display()))?;
+        }
+        let captured_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let manifest = CaptureManifest { captured_at };
+        std::fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)
+            .with_context(|| format!("failed to write {}", manifest_path.display()))?;
+        Ok(Capture::Record {
+            dir: dir.to_path_buf(),
+            captured_at,
+        })
+    }
+
+    /// Open the recording in `dir` for replay.
+    pub fn replay(dir: &Path) -> Result<Self> {
+        let path = dir.join(CAPTURE_FILE);
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let manifest: CaptureManifest = serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        Ok(Capture::Replay {
+            dir: dir.to_path_buf(),
+            captured_at: manifest.captured_at,
+        })
+    }
+
+    pub fn is_replay(&self) -> bool {
+        matches!(self, Capture::Replay { .. })
+    }
+
+    pub fn is_recording(&self) -> bool {
+        matches!(self, Capture::Record { .. })
+    }
+
+    /// Unix seconds: the wall clock when live, `captured_at` otherwise.
+    pub fn unix_now(&self) -> u64 {
+        match self {
+            Capture::Live => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            Capture::Record { captured_at, .. } | Capture::Replay { captured_at, .. } => {
+                *captured_at
+            }
+        }
+    }
+
+    /// Copy the config files and the generation state of `output_dir` into
+    /// the recording. Missing files are skipped; a replay misses them too.
+    pub fn save_inputs(&self, config_dir: &Path, output_dir: &Path) -> Result<()> {
+        let Capture::Record { dir, .. } = self else {
+            return Ok(());
+        };
+        copy_existing(config_dir, &dir.join("config"), &CONFIG_FILES)?;
+        copy_existing(output_dir, &dir.join("previous"), &STATE_FILES)
+    }
+
+    /// Reset the generation state of `output_dir` to the one the recording
+    /// started from, so every replay runs against the same previous
+    /// generation. Refuses to touch `live_dir`, the generation the running
+    /// processes read (general.generated_dir).
+    pub fn restore_previous(&self, output_dir: &Path, live_dir: &Path) -> Result<()> {
+        let Capture::Replay { dir, .. } = self else {
+            return Ok(());
+        };
+        if same_dir(output_dir, live_dir) {
+            anyhow::bail!(
+                "refusing to replay into the live generation {}; choose another --output",
+                live_dir.display()
+            );
+        }
+        std::fs::create_dir_all(output_dir)
+            .with_context(|| format!("failed to create {}", output_dir.display()))?;
+        for name in STATE_FILES {
+            let path = output_dir.join(name);
+            if path.exists() {
+                std::fs::remove_file(&path)
+                    .with_context(|| format!("failed to remove {}", path.display()))?;
+            }
+        }
+        copy_existing(&dir.join("previous"), output_dir, &STATE_FILES)
+    }
+
+    /// The recorded body for `key` (see `rest_key`).
+    pub fn load_rest(&self, key: &str) -> Result<String> {
+        let path = self.path("rest", key)?;
+        std::fs::read_to_string(&path).with_context(|| format!("not captured: {}", path.display()))
+    }
+
+    /// Save `body` under `key` when recording.
+    pub fn save_rest(&self, key: &str, body: &str) -> Result<()> {
+        if !self.is_recording() {
+            return Ok(());
+        }
+        let path = self.path("rest", key)?;
+        std::fs::write(&path, body).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// The recorded validation batch `batch` of `source`.
+    pub fn load_ws(&self, source: SourceId, batch: usize) -> Result<WsCapture> {
+        let path = self.path("ws", &ws_key(source, batch))?;
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("not captured: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// Save validation batch `batch` of `source` when recording.
+    pub fn save_ws(&self, source: SourceId, batch: usize, capture: &WsCapture) -> Result<()> {
+        if !self.is_recording() {
+            return Ok(());
+        }
+        let path = self.path("ws", &ws_key(source, batch))?;
+        std::fs::write(&path, serde_json::to_string(capture)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    fn path(&self, sub: &str, key: &str) -> Result<PathBuf> {
+        match self {
+            Capture::Live => anyhow::bail!("no capture directory"),
+            Capture::Record { dir, .. } | Capture::Replay { dir, .. } => {
+                Ok(dir.join(sub).join(format!("{}.json", key)))
+            }
+        }
+    }
+}
+
+/// File key of a REST response: page `page` of the instrument list, or the
+/// tickers when `page` is `None`.
+pub fn rest_key(source: SourceId, page: Option<usize>) -> String {
+    match page {
+        Some(page) => format!("{}_instruments_{}", source.name(), page),
+        None => format!("{}_tickers", source.name()),
+    }
+}
+
+fn ws_key(source: SourceId, batch: usize) -> String {
+    format!("{}_{:03}", source.name(), batch)
+}
+
+/// Whether `a` and `b` name the same directory, however they are spelled.
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn copy_existing(from: &Path, to: &Path, names: &[&str]) -> Result<()> {
+    for name in names {
+        let src = from.join(name);
+        if !src.exists() {
+            continue;
+        }
+        let dst = to.join(name);
+        std::fs::copy(&src, &dst)
+            .with_context(|| format!("failed to copy {} to {}", src.display(), dst.display()))?;
+    }
+    Ok(())
+}
+
+mod hex {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], s: S) -> Result<S::Ok, S::Error> {
+        let text: String = data.iter().map(|b| format!("{:02x}", b)).collect();
+        s.serialize_str(&text)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(d)?;
+        if text.len() % 2 != 0 {
+            return Err(serde::de::Error::custom("odd hex length"));
+        }
+        (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&text[i..i + 2], 16).map_err(serde::de::Error::custom))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("discovery-capture-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_record_and_replay_files() {
+        let dir = temp_dir("files");
+        let source = SourceId::MexcSpot;
+        let recording = Capture::record(&dir).unwrap();
+        assert!(Capture::record(&dir).is_err());
+
+        recording
+            .save_rest(&rest_key(source, Some(0)), r#"{"symbols":[]}"#)
+            .unwrap();
+        let batch = WsCapture {
+            symbols: vec!["BTCUSDT".to_string()],
+            frames: vec![
+                WsFrame {
+                    received_at: 1,
+                    data: FrameData::Text("{}".to_string()),
+                },
+                WsFrame {
+                    received_at: 2,
+                    data: FrameData::Binary(vec![0x0a, 0xff, 0x00]),
+                },
+            ],
+            error: Some("closed by server".to_string()),
+        };
+        recording.save_ws(source, 0, &batch).unwrap();
+
+        // Inputs of the run
+        let config_dir = dir.join("in");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        std::fs::write(config_dir.join("config.toml"), "x").unwrap();
+        std::fs::write(config_dir.join(QUARANTINE_FILE), "{}").unwrap();
+        recording.save_inputs(&config_dir, &config_dir).unwrap();
+
+        let replay = Capture::replay(&dir).unwrap();
+        assert_eq!(replay.unix_now(), recording.unix_now());
+        assert_eq!(
+            replay.load_rest("mexc_spot_instruments_0").unwrap(),
+            r#"{"symbols":[]}"#
+        );
+        assert!(replay.load_rest(&rest_key(source, None)).is_err());
+        let loaded = replay.load_ws(source, 0).unwrap();
+        assert_eq!(loaded, batch);
+        assert!(matches!(
+            loaded.frames[1].frame(),
+            Frame::Binary(&[0x0a, 0xff, 0x00])
+        ));
+        assert!(replay.load_ws(source, 1).is_err());
+
+        // A replay starts from the recorded state, not from what is there
+        let output = dir.join("out");
+        std::fs::create_dir_all(&output).unwrap();
+        std::fs::write(output.join("symbols.bin"), "stale").unwrap();
+        replay.restore_previous(&output, &config_dir).unwrap();
+        assert!(!output.join("symbols.bin").exists());
+        assert!(output.join(QUARANTINE_FILE).exists());
+        assert!(dir.join("config/config.toml").exists());
+
+        // ... and never in the live generation
+        std::fs::write(config_dir.join("symbols.bin"), "live").unwrap();
+        let live = config_dir.join("..").join("in");
+        assert!(replay.restore_previous(&live, &config_dir).is_err());
+        assert_eq!(
+            std::fs::read_to_string(config_dir.join("symbols.bin")).unwrap(),
+            "live"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,563 @@
+//! Generation diff — what changed between the previous generated/ and this run.
+//!
+//! Written as generated/diff.json (machine-readable) and generated/diff.txt
+//! (summary), atomically like the rest of generated/. Symbols are compared by name; IDs are stable so a name keeps
+//! its ID across generations. A symbol that keeps its name but changes its
+//! exchange symbol on a source (a relisted instId, a new forced mapping) or
+//! any spec field is a change too: the feeds must resubscribe. If removals
+//! exceed the configured limits the new generation is not published (see
+//! `DiscoveryError::DiffLimitsExceeded`) and its diff goes to
+//! diff.rejected.json / diff.rejected.txt instead, so diff.json keeps
+//! describing the generation that is actually live.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use common::config::DiscoveryConfig;
+use common::directions::DirectionRecord;
+use common::symbols::SymbolRecord;
+use common::types::SourceId;
+
+use crate::generator::write_atomic;
+use crate::registry::is_active;
+
+/// File stem of the published generation's diff.
+pub const DIFF_STEM: &str = "diff";
+/// File stem of the diff of a run held back by the diff limits.
+pub const REJECTED_DIFF_STEM: &str = "diff.rejected";
+
+#[derive(Debug, Default, Serialize)]
+pub struct SourceDiff {
+    /// Active symbols listed on this source in the previous generation.
+    pub previous: usize,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DirectionDiff {
+    pub direction_id: u8,
+    pub name: String,
+    pub previous: usize,
+    pub current: usize,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Exchange symbol of a listing that kept its canonical name.
+#[derive(Debug, Serialize)]
+pub struct SymbolRename {
+    pub symbol: String,
+    pub source: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpecChange {
+    pub symbol: String,
+    pub source: &'static str,
+    /// "tick_size", "min_qty", "max_qty", "min_notional", "lot_size",
+    /// "price_multiplier", "contract_type", "settle_asset", "listed_at" or
+    /// "delist_at" (both unix ms).
+    pub field: &'static str,
+    pub old: Option<SpecValue>,
+    pub new: Option<SpecValue>,
+}
+
+/// One spec field's value; numbers stay numbers in diff.json.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum SpecValue {
+    Number(f64),
+    Text(String),
+}
+
+impl std::fmt::Display for SpecValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpecValue::Number(n) => write!(f, "{}", n),
+            SpecValue::Text(t) => f.write_str(t),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct GenerationDiff {
+    pub previous_symbols: usize,
+    pub current_symbols: usize,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub per_source: BTreeMap<&'static str, SourceDiff>,
+    pub directions: Vec<DirectionDiff>,
+    pub renamed: Vec<SymbolRename>,
+    pub spec_changes: Vec<SpecChange>,
+}
+
+/// Removal limits above which a generation is held back.
+#[derive(Debug, Clone, Copy)]
+pub struct DiffLimits {
+    /// Max % of previously active symbols that may disappear.
+    pub max_removed_pct: f64,
+    /// Max % of one source's previous symbols that may disappear from it.
+    pub max_source_removed_pct: f64,
+}
+
+impl DiffLimits {
+    pub fn from_config(config: &DiscoveryConfig) -> Self {
+        Self {
+            max_removed_pct: config.diff_max_removed_pct,
+            max_source_removed_pct: config.diff_max_source_removed_pct,
+        }
+    }
+}
+
+fn active_names(records: &[SymbolRecord]) -> BTreeMap<&str, &SymbolRecord> {
+    records
+        .iter()
+        .filter(|r| is_active(r))
+        .map(|r| (r.name.as_str(), r))
+        .collect()
+}
+
+fn direction_names<'a>(dir: &DirectionRecord, records: &'a [SymbolRecord]) -> BTreeSet<&'a str> {
+    dir.symbols
+        .iter()
+        .filter_map(|&id| records.get(id as usize))
+        .map(|r| r.name.as_str())
+        .collect()
+}
+
+fn pct(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / whole as f64
+    }
+}
+
+fn same(a: &Option<SpecValue>, b: &Option<SpecValue>) -> bool {
+    match (a, b) {
+        (Some(SpecValue::Number(a)), Some(SpecValue::Number(b))) => {
+            (a - b).abs() <= f64::EPSILON * a.abs().max(b.abs())
+        }
+        _ => a == b,
+    }
+}
+
+/// Every compared field of one (symbol, source) listing, by name.
+fn spec_fields(r: &SymbolRecord, i: usize) -> [(&'static str, Option<SpecValue>); 10] {
+    let spec = &r.specs[i];
+    let number = |v: Option<f64>| v.map(SpecValue::Number);
+    let ms = |v: Option<u64>| v.map(|t| SpecValue::Number(t as f64));
+    [
+        ("tick_size", number(r.tick_size[i])),
+        ("min_qty", number(r.min_qty[i])),
+        ("max_qty", number(spec.max_qty)),
+        ("min_notional", number(spec.min_notional)),
+        ("lot_size", number(spec.lot_size)),
+        ("price_multiplier", number(Some(r.price_multiplier[i]))),
+        (
+            "contract_type",
+            spec.contract_type
+                .map(|c| SpecValue::Text(format!("{:?}", c))),
+        ),
+        (
+            "settle_asset",
+            spec.settle_asset.clone().map(SpecValue::Text),
+        ),
+        ("listed_at", ms(spec.listed_at)),
+        ("delist_at", ms(spec.delist_at)),
+    ]
+}
+
+fn show(v: &Option<SpecValue>) -> String {
+    v.as_ref()
+        .map_or_else(|| "-".to_string(), |v| v.to_string())
+}
+
+impl GenerationDiff {
+    pub fn compute(
+        prev_symbols: &[SymbolRecord],
+        prev_directions: &[DirectionRecord],
+        symbols: &[SymbolRecord],
+        directions: &[DirectionRecord],
+    ) -> Self {
+        let prev = active_names(prev_symbols);
+        let cur = active_names(symbols);
+
+        let mut diff = GenerationDiff {
+            previous_symbols: prev.len(),
+            current_symbols: cur.len(),
+            added: cur
+                .keys()
+                .filter(|n| !prev.contains_key(*n))
+                .map(|n| n.to_string())
+                .collect(),
+            removed: prev
+                .keys()
+                .filter(|n| !cur.contains_key(*n))
+                .map(|n| n.to_string())
+                .collect(),
+            ..Default::default()
+        };
+
+        for source in SourceId::ALL {
+            let idx = source.index();
+            let listed = |map: &BTreeMap<&str, &SymbolRecord>| -> BTreeSet<String> {
+                map.iter()
+                    .filter(|(_, r)| r.source_names[idx].is_some())
+                    .map(|(n, _)| n.to_string())
+                    .collect()
+            };
+            let before = listed(&prev);
+            let after = listed(&cur);
+            diff.per_source.insert(
+                source.name(),
+                SourceDiff {
+                    previous: before.len(),
+                    added: after.difference(&before).cloned().collect(),
+                    removed: before.difference(&after).cloned().collect(),
+                },
+            );
+        }
+
+        for dir in directions {
+            let before = prev_directions
+                .iter()
+                .find(|d| d.direction_id == dir.direction_id)
+                .map(|d| direction_names(d, prev_symbols))
+                .unwrap_or_default();
+            let after = direction_names(dir, symbols);
+            diff.directions.push(DirectionDiff {
+                direction_id: dir.direction_id,
+                name: dir.name.clone(),
+                previous: before.len(),
+                current: after.len(),
+                added: after.difference(&before).map(|n| n.to_string()).collect(),
+                removed: before.difference(&after).map(|n| n.to_string()).collect(),
+            });
+        }
+
+        for (name, new) in &cur {
+            let Some(old) = prev.get(name) else {
+                continue;
+            };
+            for source in SourceId::ALL {
+                let i = source.index();
+                let (Some(old_symbol), Some(new_symbol)) =
+                    (&old.source_names[i], &new.source_names[i])
+                else {
+                    continue;
+                };
+                if old_symbol != new_symbol {
+                    diff.renamed.push(SymbolRename {
+                        symbol: name.to_string(),
+                        source: source.name(),
+                        old: old_symbol.clone(),
+                        new: new_symbol.clone(),
+                    });
+                }
+                let fields = spec_fields(old, i).into_iter().zip(spec_fields(new, i));
+                for ((field, old), (_, new)) in fields {
+                    if !same(&old, &new) {
+                        diff.spec_changes.push(SpecChange {
+                            symbol: name.to_string(),
+                            source: source.name(),
+                            field,
+                            old,
+                            new,
+                        });
+                    }
+                }
+            }
+        }
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self
+                .per_source
+                .values()
+                .all(|s| s.added.is_empty() && s.removed.is_empty())
+            && self
+                .directions
+                .iter()
+                .all(|d| d.added.is_empty() && d.removed.is_empty())
+            && self.renamed.is_empty()
+            && self.spec_changes.is_empty()
+    }
+
+    /// Limit violations, one line each. A first generation has nothing to lose.
+    pub fn check_limits(&self, limits: &DiffLimits) -> Vec<String> {
+        let mut violations = Vec::new();
+        let removed = pct(self.removed.len(), self.previous_symbols);
+        if removed > limits.max_removed_pct {
+            violations.push(format!(
+                "{} of {} symbols removed ({:.1}% > {:.1}%)",
+                self.removed.len(),
+                self.previous_symbols,
+                removed,
+                limits.max_removed_pct
+            ));
+        }
+        for (source, s) in &self.per_source {
+            let removed = pct(s.removed.len(), s.previous);
+            if removed > limits.max_source_removed_pct {
+                violations.push(format!(
+                    "{}: {} of {} symbols removed ({:.1}% > {:.1}%)",
+                    source,
+                    s.removed.len(),
+                    s.previous,
+                    removed,
+                    limits.max_source_removed_pct
+                ));
+            }
+        }
+        violations
+    }
+
+    /// Human-readable summary (diff.txt).
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "=== Generation Diff ===");
+        let _ = writeln!(
+            out,
+            "symbols: {} -> {} (+{} -{})",
+            self.previous_symbols,
+            self.current_symbols,
+            self.added.len(),
+            self.removed.len()
+        );
+        if !self.added.is_empty() {
+            let _ = writeln!(out, "added: {}", self.added.join(", "));
+        }
+        if !self.removed.is_empty() {
+            let _ = writeln!(out, "removed: {}", self.removed.join(", "));
+        }
+
+        let _ = writeln!(out, "\n--- per source ---");
+        for (source, s) in &self.per_source {
+            let _ = writeln!(
+                out,
+                "{:<16} +{:<4} -{:<4}{}",
+                source,
+                s.added.len(),
+                s.removed.len(),
+                if s.removed.is_empty() {
+                    String::new()
+                } else {
+                    format!(" removed: {}", s.removed.join(", "))
+                }
+            );
+        }
+
+        let _ = writeln!(out, "\n--- per direction ---");
+        for d in &self.directions {
+            let _ = writeln!(
+                out,
+                "{:>2} {:<28} {} -> {} (+{} -{})",
+                d.direction_id,
+                d.name,
+                d.previous,
+                d.current,
+                d.added.len(),
+                d.removed.len()
+            );
+        }
+
+        if !self.renamed.is_empty() {
+            let _ = writeln!(out, "\n--- exchange symbol changes ---");
+            for r in &self.renamed {
+                let _ = writeln!(out, "{} {}: {} -> {}", r.symbol, r.source, r.old, r.new);
+            }
+        }
+
+        if !self.spec_changes.is_empty() {
+            let _ = writeln!(out, "\n--- spec changes ---");
+            for c in &self.spec_changes {
+                let _ = writeln!(
+                    out,
+                    "{} {} {}: {} -> {}",
+                    c.symbol,
+                    c.source,
+                    c.field,
+                    show(&c.old),
+                    show(&c.new)
+                );
+            }
+        }
+        out
+    }
+
+    /// Write `<stem>.json` and `<stem>.txt` into `dir`.
+    pub fn write(&self, dir: &Path, stem: &str) -> Result<()> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+        let json = serde_json::to_string_pretty(self)?;
+        write_atomic(&dir.join(format!("{}.json", stem)), json.as_bytes())?;
+        write_atomic(
+            &dir.join(format!("{}.txt", stem)),
+            self.summary().as_bytes(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::types::NUM_SOURCES;
+
+    fn record(id: u16, name: &str, sources: &[SourceId], tick: f64) -> SymbolRecord {
+        let mut r = SymbolRecord::new(id, name);
+        for s in sources {
+            r.source_names[s.index()] = Some(name.replace('-', ""));
+            r.tick_size[s.index()] = Some(tick);
+        }
+        r
+    }
+
+    fn direction(symbols: Vec<u16>) -> DirectionRecord {
+        DirectionRecord {
+            direction_id: 0,
+            spot_source: SourceId::OkxSpot as u8,
+            futures_source: SourceId::BinanceFutures as u8,
+            name: "okx_spot_binance_futures".to_string(),
+            symbols,
+        }
+    }
+
+    const BOTH: &[SourceId] = &[SourceId::OkxSpot, SourceId::BinanceFutures];
+
+    fn limits() -> DiffLimits {
+        DiffLimits {
+            max_removed_pct: 10.0,
+            max_source_removed_pct: 25.0,
+        }
+    }
+
+    #[test]
+    fn test_diff_added_removed_and_specs() {
+        let prev = vec![
+            record(0, "BTC-USDT", BOTH, 0.1),
+            record(1, "ETH-USDT", BOTH, 0.01),
+            record(2, "LUNA-USDT", BOTH, 0.001),
+        ];
+        let mut cur = vec![
+            record(0, "BTC-USDT", BOTH, 0.5),
+            record(1, "ETH-USDT", &[SourceId::OkxSpot], 0.01),
+            record(2, "LUNA-USDT", &[], 0.0),
+            record(3, "SOL-USDT", BOTH, 0.01),
+        ];
+        cur[2].tick_size = [None; NUM_SOURCES as usize];
+        // ETH redenominated on OKX spot
+        cur[1].price_multiplier[SourceId::OkxSpot.index()] = 1000.0;
+
+        let diff = GenerationDiff::compute(
+            &prev,
+            &[direction(vec![0, 1, 2])],
+            &cur,
+            &[direction(vec![0, 3])],
+        );
+        assert_eq!(diff.added, vec!["SOL-USDT"]);
+        assert_eq!(diff.removed, vec!["LUNA-USDT"]);
+        assert_eq!(
+            diff.per_source["binance_futures"].removed,
+            vec!["ETH-USDT", "LUNA-USDT"]
+        );
+        assert_eq!(diff.per_source["okx_spot"].added, vec!["SOL-USDT"]);
+        assert_eq!(diff.directions[0].added, vec!["SOL-USDT"]);
+        assert_eq!(diff.directions[0].removed, vec!["ETH-USDT", "LUNA-USDT"]);
+        // BTC tick changed on both sources, ETH multiplier on one
+        assert_eq!(diff.spec_changes.len(), 3);
+        assert!(diff.spec_changes[..2].iter().all(|c| c.symbol == "BTC-USDT"
+            && c.field == "tick_size"
+            && c.new == Some(SpecValue::Number(0.5))));
+        assert_eq!(diff.spec_changes[2].symbol, "ETH-USDT");
+        assert_eq!(diff.spec_changes[2].field, "price_multiplier");
+        assert!(!diff.is_empty());
+
+        let json: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&diff).unwrap()).unwrap();
+        assert_eq!(json["removed"][0], "LUNA-USDT");
+        assert_eq!(json["spec_changes"][0]["new"], 0.5);
+    }
+
+    #[test]
+    fn test_exchange_symbol_change_is_a_change() {
+        let prev = vec![record(0, "BTC-USDT", BOTH, 0.1)];
+        assert!(GenerationDiff::compute(&prev, &[], &prev, &[]).is_empty());
+
+        // OKX relisted the pair under a new instId: same name, same sources
+        let mut cur = prev.clone();
+        cur[0].source_names[SourceId::OkxSpot.index()] = Some("BTC-USDT-V2".to_string());
+        let diff = GenerationDiff::compute(&prev, &[], &cur, &[]);
+        assert!(!diff.is_empty());
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.renamed.len(), 1);
+        assert_eq!(diff.renamed[0].source, "okx_spot");
+        assert_eq!(
+            (diff.renamed[0].old.as_str(), diff.renamed[0].new.as_str()),
+            ("BTCUSDT", "BTC-USDT-V2")
+        );
+        assert!(diff.summary().contains("okx_spot: BTCUSDT -> BTC-USDT-V2"));
+
+        // Non-numeric spec fields count as well
+        let mut cur = prev.clone();
+        let spec = &mut cur[0].specs[SourceId::BinanceFutures.index()];
+        spec.settle_asset = Some("USDC".to_string());
+        spec.listed_at = Some(1_600_000_000_000);
+        let diff = GenerationDiff::compute(&prev, &[], &cur, &[]);
+        let fields: Vec<_> = diff.spec_changes.iter().map(|c| c.field).collect();
+        assert_eq!(fields, vec!["settle_asset", "listed_at"]);
+        assert_eq!(
+            diff.spec_changes[0].new,
+            Some(SpecValue::Text("USDC".to_string()))
+        );
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn test_limits() {
+        let prev: Vec<SymbolRecord> = (0..20)
+            .map(|i| record(i, &format!("S{}-USDT", i), BOTH, 0.1))
+            .collect();
+
+        // 2 of 20 gone = 10%: within the limit
+        let mut cur = prev.clone();
+        for r in &mut cur[..2] {
+            r.source_names = Default::default();
+        }
+        let diff = GenerationDiff::compute(&prev, &[], &cur, &[]);
+        assert!(diff.check_limits(&limits()).is_empty());
+
+        // 3 of 20 = 15%
+        cur[2].source_names = Default::default();
+        let diff = GenerationDiff::compute(&prev, &[], &cur, &[]);
+        let violations = diff.check_limits(&limits());
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].contains("3 of 20"));
+
+        // One source losing most of its listings trips the per-source limit
+        let mut cur = prev.clone();
+        for r in &mut cur[..6] {
+            r.source_names[SourceId::OkxSpot.index()] = None;
+        }
+        let diff = GenerationDiff::compute(&prev, &[], &cur, &[]);
+        let violations = diff.check_limits(&limits());
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].starts_with("okx_spot"));
+
+        // First generation never trips
+        let diff = GenerationDiff::compute(&[], &[], &prev, &[]);
+        assert!(diff.check_limits(&limits()).is_empty());
+        assert_eq!(diff.added.len(), 20);
+    }
+}
@@ -0,0 +1,32 @@
+//! Direction lists — for each configured direction, the symbols listed on
+//! both its spot and its futures source.
+
+use common::config::DirectionsConfig;
+use common::directions::DirectionRecord;
+use common::symbols::SymbolRecord;
+
+/// Build one DirectionRecord per configured direction. Symbol lists are in
+/// symbol_id order; tombstoned records have no source names and never match.
+pub fn build_directions(
+    records: &[SymbolRecord],
+    config: &DirectionsConfig,
+) -> Vec<DirectionRecord> {
+    config
+        .direction
+        .iter()
+        .map(|d| DirectionRecord {
+            direction_id: d.id,
+            spot_source: d.spot_source,
+            futures_source: d.futures_source,
+            name: d.name.clone(),
+            symbols: records
+                .iter()
+                .filter(|r| {
+                    r.source_names[d.spot_source as usize].is_some()
+                        && r.source_names[d.futures_source as usize].is_some()
+                })
+                .map(|r| r.symbol_id)
+                .collect(),
+        })
+        .collect()
+}
@@ -0,0 +1,62 @@
+//! Discovery errors that callers need to branch on.
+//!
+//! Everything else is reported through `anyhow` with context. These variants
+//! travel inside `anyhow::Error` and are recovered with `downcast_ref` by
+//! pair-discovery to pick its exit code.
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum DiscoveryError {
+    /// Fewer sources answered the REST stage than required.
+    InsufficientSources { successful: usize, required: usize },
+    /// The new generation removes more than the configured diff limits allow.
+    DiffLimitsExceeded { violations: Vec<String> },
+    /// Fewer sources got through WS validation than required.
+    InsufficientValidation { validated: usize, required: usize },
+}
+
+impl DiscoveryError {
+    /// Process exit code for pair-discovery.
+    ///   1 — generic fatal error (config, I/O)
+    ///   2 — not enough REST sources
+    ///   3 — diff limits exceeded, generation not published
+    ///   4 — not enough sources passed WS validation
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            DiscoveryError::InsufficientSources { .. } => 2,
+            DiscoveryError::DiffLimitsExceeded { .. } => 3,
+            DiscoveryError::InsufficientValidation { .. } => 4,
+        }
+    }
+}
+
+impl fmt::Display for DiscoveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiscoveryError::InsufficientSources {
+                successful,
+                required,
+            } => write!(
+                f,
+                "insufficient sources: {} successful, {} required",
+                successful, required
+            ),
+            DiscoveryError::DiffLimitsExceeded { violations } => write!(
+                f,
+                "diff limits exceeded, generation not published: {}",
+                violations.join("; ")
+            ),
+            DiscoveryError::InsufficientValidation {
+                validated,
+                required,
+            } => write!(
+                f,
+                "insufficient validation: {} sources validated, {} required",
+                validated, required
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DiscoveryError {}
@@ -0,0 +1,650 @@
+//! Generator — publishes a discovery run into generated/.
+//!
+//! Files written:
+//!   symbols.bin / directions.bin   headered bincode (see `common::artifact`)
+//!   metadata.json                  counts, checksums, format version,
+//!                                  24h volume per symbol and source,
+//!                                  scheduled delistings
+//!   symbols.txt / directions.txt   tab-separated mirrors of the binaries
+//!   validation_report.txt          per-source WS validation outcome,
+//!                                  exchange status exclusions, liquidity
+//!                                  rejections and the overrides applied
+//!   quarantine.txt                 pairs held out as ticker collisions
+//!   listings.json                  pairs newly listed on both sources of a
+//!                                  direction (see `common::listings`)
+//!
+//! Every file goes through `write_atomic` (temp file, fsync, rename), so a
+//! reader sees either the previous or the new version, never a partial one.
+//! The binaries are written first and metadata.json last.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::Write as _;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use tracing::warn;
+
+use common::artifact::{self, DIRECTIONS_VERSION, SYMBOLS_VERSION};
+use common::directions::{DirectionRecord, DirectionTable};
+use common::listings::{ListingRecord, Listings, LISTINGS_FILE};
+use common::symbols::{SymbolRecord, SymbolTable};
+use common::types::SourceId;
+
+use crate::liquidity::LiquidityReport;
+use crate::overrides::AppliedOverride;
+use crate::quarantine::Quarantine;
+use crate::registry::is_active;
+use crate::status::StatusReport;
+use crate::validator::ValidationReport;
+
+/// Write `data` to `path` via a temp file in the same directory: write,
+/// fsync, rename, then fsync the directory so the rename itself is durable.
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("not a file path: {}", path.display()))?;
+    let tmp = dir.join(format!(".{}.tmp", file_name.to_string_lossy()));
+
+    let mut file =
+        File::create(&tmp).with_context(|| format!("failed to create {}", tmp.display()))?;
+    file.write_all(data)
+        .and_then(|_| file.sync_all())
+        .with_context(|| format!("failed to write {}", tmp.display()))?;
+    drop(file);
+
+    std::fs::rename(&tmp, path)
+        .with_context(|| format!("failed to rename {} to {}", tmp.display(), path.display()))?;
+    File::open(dir)
+        .and_then(|d| d.sync_all())
+        .with_context(|| format!("failed to sync {}", dir.display()))?;
+    Ok(())
+}
+
+/// The previous generation, empty if there is none.
+#[derive(Debug, Default)]
+pub struct Previous {
+    pub symbols: Vec<SymbolRecord>,
+    pub directions: Vec<DirectionRecord>,
+    /// Read from an older format; must be republished even if unchanged.
+    pub legacy: bool,
+}
+
+impl Previous {
+    /// Load symbols.bin / directions.bin from `dir`. Older formats (no header,
+    /// earlier versions) are migrated, so upgrading keeps symbol_ids stable;
+    /// any other format error is fatal.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let symbols_path = dir.join("symbols.bin");
+        let directions_path = dir.join("directions.bin");
+        let mut previous = Self::default();
+        if symbols_path.exists() {
+            let (records, legacy) = load_records(&symbols_path, SymbolTable::decode_compat)?;
+            previous.symbols = records;
+            previous.legacy |= legacy;
+        }
+        if directions_path.exists() {
+            let (records, legacy) = load_records(&directions_path, DirectionTable::decode_compat)?;
+            previous.directions = records;
+            previous.legacy |= legacy;
+        }
+        Ok(previous)
+    }
+}
+
+/// Records from `path`, and whether they were in an older format.
+fn load_records<T, F>(path: &Path, decode_compat: F) -> Result<(Vec<T>, bool)>
+where
+    F: Fn(&[u8]) -> Result<(Vec<T>, bool)>,
+{
+    let data = std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let (records, legacy) =
+        decode_compat(&data).with_context(|| format!("failed to load {}", path.display()))?;
+    if legacy {
+        warn!("{} is in an older format, migrating it", path.display());
+    }
+    Ok((records, legacy))
+}
+
+#[derive(Debug, Serialize)]
+pub struct DirectionMeta {
+    pub direction_id: u8,
+    pub pairs: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SourceValidationMeta {
+    pub total: usize,
+    pub valid: usize,
+    pub invalid: usize,
+}
+
+/// metadata.json
+#[derive(Debug, Serialize)]
+pub struct Metadata {
+    pub symbols_version: u16,
+    pub directions_version: u16,
+    /// Unix seconds.
+    pub generated_at: u64,
+    /// Records in symbols.bin, tombstones included.
+    pub num_records: usize,
+    pub num_symbols: usize,
+    pub per_source_counts: BTreeMap<&'static str, usize>,
+    pub directions: BTreeMap<String, DirectionMeta>,
+    pub validation: BTreeMap<&'static str, SourceValidationMeta>,
+    /// Pairs removed from directions as ticker collisions.
+    pub quarantined: usize,
+    /// Pairs newly listed on both sources of a direction.
+    pub new_listings: usize,
+    /// 24h quote volume of each published listing: name -> source -> volume.
+    pub quote_volume_24h: BTreeMap<String, BTreeMap<&'static str, f64>>,
+    /// Announced delist time of each published listing that has one:
+    /// name -> source -> unix ms.
+    pub scheduled_delistings: BTreeMap<String, BTreeMap<&'static str, u64>>,
+    /// FNV-1a 64 of each binary payload, as in its header.
+    pub checksums: BTreeMap<&'static str, String>,
+}
+
+/// One generation, ready to be written.
+pub struct Generation<'a> {
+    pub symbols: &'a [SymbolRecord],
+    pub directions: &'a [DirectionRecord],
+    pub validation: &'a ValidationReport,
+    pub status: &'a StatusReport,
+    pub liquidity: &'a LiquidityReport,
+    /// Manual overrides that took effect (config/overrides.toml).
+    pub overrides: &'a [AppliedOverride],
+    /// Ticker collisions, already removed from `directions`.
+    pub quarantine: &'a Quarantine,
+    pub listings: &'a [ListingRecord],
+    /// Unix seconds.
+    pub generated_at: u64,
+}
+
+impl Generation<'_> {
+    /// Write every artifact into `dir`.
+    pub fn write(&self, dir: &Path) -> Result<Metadata> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+
+        let symbols_bin = SymbolTable::encode(self.symbols)?;
+        let directions_bin = DirectionTable::encode(self.directions)?;
+        let metadata = self.metadata(&symbols_bin, &directions_bin);
+
+        write_atomic(&dir.join("directions.bin"), &directions_bin)?;
+        write_atomic(&dir.join("symbols.bin"), &symbols_bin)?;
+        write_atomic(&dir.join("symbols.txt"), self.symbols_txt().as_bytes())?;
+        write_atomic(
+            &dir.join("directions.txt"),
+            self.directions_txt().as_bytes(),
+        )?;
+        write_atomic(
+            &dir.join("validation_report.txt"),
+            self.validation_report_txt().as_bytes(),
+        )?;
+        write_atomic(
+            &dir.join("quarantine.txt"),
+            self.quarantine_txt().as_bytes(),
+        )?;
+        let listings = Listings {
+            listings: self.listings.to_vec(),
+        };
+        write_atomic(
+            &dir.join(LISTINGS_FILE),
+            serde_json::to_string_pretty(&listings)?.as_bytes(),
+        )?;
+        write_atomic(
+            &dir.join("metadata.json"),
+            serde_json::to_string_pretty(&metadata)?.as_bytes(),
+        )?;
+        Ok(metadata)
+    }
+
+    fn metadata(&self, symbols_bin: &[u8], directions_bin: &[u8]) -> Metadata {
+        let payload_checksum =
+            |data: &[u8]| format!("{:016x}", artifact::checksum(&data[artifact::HEADER_LEN..]));
+        Metadata {
+            symbols_version: SYMBOLS_VERSION,
+            directions_version: DIRECTIONS_VERSION,
+            generated_at: self.generated_at,
+            num_records: self.symbols.len(),
+            num_symbols: self.symbols.iter().filter(|r| is_active(r)).count(),
+            per_source_counts: SourceId::ALL
+                .iter()
+                .map(|s| {
+                    let count = self
+                        .symbols
+                        .iter()
+                        .filter(|r| r.source_names[s.index()].is_some())
+                        .count();
+                    (s.name(), count)
+                })
+                .collect(),
+            directions: self
+                .directions
+                .iter()
+                .map(|d| {
+                    (
+                        d.name.clone(),
+                        DirectionMeta {
+                            direction_id: d.direction_id,
+                            pairs: d.symbols.len(),
+                        },
+                    )
+                })
+                .collect(),
+            validation: self
+                .validation
+                .sources
+                .iter()
+                .map(|sv| {
+                    (
+                        sv.source.name(),
+                        SourceValidationMeta {
+                            total: sv.total,
+                            valid: sv.valid,
+                            invalid: sv.invalid.len(),
+                        },
+                    )
+                })
+                .collect(),
+            quarantined: self.quarantine.entries.len(),
+            new_listings: self.listings.len(),
+            quote_volume_24h: self.quote_volumes(),
+            scheduled_delistings: self.scheduled_delistings(),
+            checksums: BTreeMap::from([
+                ("symbols.bin", payload_checksum(symbols_bin)),
+                ("directions.bin", payload_checksum(directions_bin)),
+            ]),
+        }
+    }
+
+    /// Volumes of the listings that made it into this generation.
+    fn quote_volumes(&self) -> BTreeMap<String, BTreeMap<&'static str, f64>> {
+        let mut out = BTreeMap::new();
+        for r in self.symbols {
+            let Some(volumes) = self.liquidity.volumes.get(&r.name) else {
+                continue;
+            };
+            let listed: BTreeMap<&'static str, f64> = volumes
+                .iter()
+                .filter(|(source, _)| {
+                    SourceId::from_name(source).is_some_and(|s| r.source_names[s.index()].is_some())
+                })
+                .map(|(source, v)| (*source, *v))
+                .collect();
+            if !listed.is_empty() {
+                out.insert(r.name.clone(), listed);
+            }
+        }
+        out
+    }
+
+    fn scheduled_delistings(&self) -> BTreeMap<String, BTreeMap<&'static str, u64>> {
+        let mut out = BTreeMap::new();
+        for r in self.symbols {
+            let listed: BTreeMap<&'static str, u64> = SourceId::ALL
+                .iter()
+                .filter(|s| r.source_names[s.index()].is_some())
+                .filter_map(|s| r.specs[s.index()].delist_at.map(|at| (s.name(), at)))
+                .collect();
+            if !listed.is_empty() {
+                out.insert(r.name.clone(), listed);
+            }
+        }
+        out
+    }
+
+    /// symbols.txt: one line per record, "-" where a source does not list it.
+    pub fn symbols_txt(&self) -> String {
+        let mut out = String::from("symbol_id\tname");
+        for source in SourceId::ALL {
+            out.push('\t');
+            out.push_str(source.name());
+        }
+        out.push('\n');
+        for r in self.symbols {
+            let _ = write!(out, "{}\t{}", r.symbol_id, r.name);
+            for name in &r.source_names {
+                out.push('\t');
+                out.push_str(name.as_deref().unwrap_or("-"));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// directions.txt
+    pub fn directions_txt(&self) -> String {
+        let mut out = String::from("direction_id\tname\tnum_pairs\n");
+        for d in self.directions {
+            let _ = writeln!(out, "{}\t{}\t{}", d.direction_id, d.name, d.symbols.len());
+        }
+        out
+    }
+
+    /// validation_report.txt
+    pub fn validation_report_txt(&self) -> String {
+        let mut out = String::from("=== Validation Report ===\n");
+        let (mut total, mut invalid) = (0, 0);
+        for sv in &self.validation.sources {
+            total += sv.total;
+            invalid += sv.invalid.len();
+            let _ = writeln!(
+                out,
+                "\n{}: {} total, {} valid, {} invalid{}",
+                sv.source.name(),
+                sv.total,
+                sv.valid,
+                sv.invalid.len(),
+                if sv.total > 0 && sv.batches == 0 {
+                    " (not validated)"
+                } else {
+                    ""
+                }
+            );
+            if sv.failed_batches > 0 {
+                let _ = writeln!(out, "failed batches: {}/{}", sv.failed_batches, sv.batches);
+            }
+            for pair in &sv.invalid {
+                let _ = writeln!(
+                    out,
+                    "  {} ({}): {}",
+                    pair.exchange_symbol, pair.name, pair.reason
+                );
+            }
+        }
+        let pct = if total == 0 {
+            0.0
+        } else {
+            invalid as f64 * 100.0 / total as f64
+        };
+        let _ = writeln!(
+            out,
+            "\nTotal: {} pairs validated, {} invalid ({:.1}%)",
+            total, invalid, pct
+        );
+
+        if !self.status.excluded.is_empty() {
+            let _ = writeln!(
+                out,
+                "\n=== Exchange status ({} excluded) ===",
+                self.status.excluded.len()
+            );
+            for u in &self.status.excluded {
+                let _ = writeln!(
+                    out,
+                    "  {} {} ({}): {}",
+                    u.source.name(),
+                    u.exchange_symbol,
+                    u.name,
+                    u.reason
+                );
+            }
+        }
+        if !self.status.scheduled.is_empty() {
+            let _ = writeln!(
+                out,
+                "\n=== Scheduled delistings ({}) ===",
+                self.status.scheduled.len()
+            );
+            for d in &self.status.scheduled {
+                let _ = writeln!(
+                    out,
+                    "  {} {} ({}): at {}",
+                    d.source.name(),
+                    d.exchange_symbol,
+                    d.name,
+                    d.delist_at
+                );
+            }
+        }
+
+        if !self.liquidity.rejected.is_empty() {
+            let _ = writeln!(
+                out,
+                "\n=== Liquidity ({} rejected) ===",
+                self.liquidity.rejected.len()
+            );
+            for r in &self.liquidity.rejected {
+                let _ = writeln!(
+                    out,
+                    "  {} {} ({}): {}",
+                    r.source.name(),
+                    r.exchange_symbol,
+                    r.name,
+                    r.reason
+                );
+            }
+        }
+
+        if !self.overrides.is_empty() {
+            let _ = writeln!(out, "\n=== Overrides ({}) ===", self.overrides.len());
+            for o in self.overrides {
+                let _ = writeln!(out, "{}", o);
+            }
+        }
+        out
+    }
+
+    /// quarantine.txt
+    pub fn quarantine_txt(&self) -> String {
+        let mut out = String::from("direction\tsymbol\tprice_ratio\tsince\tclean_runs\n");
+        for e in &self.quarantine.entries {
+            let _ = writeln!(
+                out,
+                "{}\t{}\t{:.4}\t{}\t{}",
+                e.direction, e.symbol, e.ratio, e.since, e.clean_runs
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::artifact::ArtifactError;
+
+    use crate::liquidity::{Illiquid, IlliquidReason};
+    use crate::quarantine::QuarantineEntry;
+    use crate::status::{InstrumentState, Unavailable, UnavailableReason};
+    use crate::validator::{InvalidPair, InvalidReason, SourceValidation};
+
+    fn record(id: u16, name: &str, sources: &[SourceId]) -> SymbolRecord {
+        sources.iter().fold(SymbolRecord::new(id, name), |r, &s| {
+            r.with_source(s, name.replace('-', ""))
+        })
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "discovery-generator-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_generation_write_and_reload() {
+        let both = [SourceId::OkxSpot, SourceId::BinanceFutures];
+        let mut symbols = vec![
+            record(0, "BTC-USDT", &both),
+            record(1, "LUNA-USDT", &[]),
+            record(2, "ETH-USDT", &both),
+        ];
+        symbols[2].specs[SourceId::BinanceFutures.index()].delist_at = Some(1_800_000_000_000);
+        // Not listed there: not published
+        symbols[1].specs[SourceId::OkxSpot.index()].delist_at = Some(1_700_000_000_000);
+        let directions = vec![DirectionRecord {
+            direction_id: 0,
+            spot_source: SourceId::OkxSpot as u8,
+            futures_source: SourceId::BinanceFutures as u8,
+            name: "okx_spot_binance_futures".to_string(),
+            symbols: vec![0, 2],
+        }];
+        let validation = ValidationReport {
+            sources: vec![SourceValidation {
+                source: SourceId::OkxSpot,
+                total: 3,
+                valid: 2,
+                invalid: vec![InvalidPair {
+                    name: "XYZ-USDT".to_string(),
+                    exchange_symbol: "XYZ-USDT".to_string(),
+                    reason: InvalidReason::ZeroBid,
+                }],
+                batches: 1,
+                failed_batches: 0,
+                mids: BTreeMap::new(),
+            }],
+        };
+        let generation = Generation {
+            symbols: &symbols,
+            directions: &directions,
+            validation: &validation,
+            status: &StatusReport {
+                excluded: vec![Unavailable {
+                    source: SourceId::BinanceFutures,
+                    exchange_symbol: "XEMUSDT".to_string(),
+                    name: "XEM-USDT".to_string(),
+                    reason: UnavailableReason::State {
+                        state: InstrumentState::Delivering,
+                        status: "SETTLING".to_string(),
+                    },
+                }],
+                scheduled: vec![],
+            },
+            liquidity: &LiquidityReport {
+                rejected: vec![Illiquid {
+                    source: SourceId::OkxSpot,
+                    exchange_symbol: "DEAD-USDT".to_string(),
+                    name: "DEAD-USDT".to_string(),
+                    reason: IlliquidReason::LowVolume { quote_volume: 12.0 },
+                }],
+                volumes: BTreeMap::from([(
+                    "BTC-USDT".to_string(),
+                    BTreeMap::from([("okx_spot", 5e8), ("bybit_spot", 1e8)]),
+                )]),
+            },
+            overrides: &[AppliedOverride::PinnedMissing {
+                name: "SOL-USDT".to_string(),
+            }],
+            quarantine: &Quarantine {
+                entries: vec![QuarantineEntry {
+                    direction_id: 0,
+                    direction: "okx_spot_binance_futures".to_string(),
+                    symbol: "LUNA-USDT".to_string(),
+                    ratio: 12.5,
+                    since: 1_699_999_000,
+                    clean_runs: 1,
+                }],
+            },
+            listings: &[ListingRecord {
+                direction_id: 0,
+                symbol_id: 2,
+                name: "ETH-USDT".to_string(),
+                spot_source: SourceId::OkxSpot as u8,
+                futures_source: SourceId::BinanceFutures as u8,
+                detected_at: 1_700_000_000_000,
+                listed_at: None,
+            }],
+            generated_at: 1_700_000_000,
+        };
+
+        let dir = temp_dir("write");
+        let metadata = generation.write(&dir).unwrap();
+        assert_eq!(metadata.num_records, 3);
+        assert_eq!(metadata.num_symbols, 2);
+        assert_eq!(metadata.per_source_counts["okx_spot"], 2);
+        assert_eq!(metadata.directions["okx_spot_binance_futures"].pairs, 2);
+        assert_eq!(metadata.validation["okx_spot"].invalid, 1);
+
+        let table = SymbolTable::load(&dir).unwrap();
+        assert_eq!(table.num_symbols(), 3);
+        assert_eq!(table.resolve(SourceId::OkxSpot, "ETHUSDT"), Some(2));
+        assert_eq!(
+            DirectionTable::load(&dir).unwrap().records[0].symbols,
+            vec![0, 2]
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("metadata.json")).unwrap())
+                .unwrap();
+        assert_eq!(json["symbols_version"], SYMBOLS_VERSION);
+        let txt = std::fs::read_to_string(dir.join("symbols.txt")).unwrap();
+        assert_eq!(txt.lines().count(), 4);
+        assert!(txt.lines().nth(2).unwrap().starts_with("1\tLUNA-USDT\t-\t"));
+        let report = std::fs::read_to_string(dir.join("validation_report.txt")).unwrap();
+        assert!(report.contains("XYZ-USDT (XYZ-USDT): zero bid/ask"));
+        assert!(report.contains("=== Overrides (1) ===\npin     SOL-USDT missing"));
+        let quarantine = std::fs::read_to_string(dir.join("quarantine.txt")).unwrap();
+        assert_eq!(
+            quarantine.lines().nth(1),
+            Some("okx_spot_binance_futures\tLUNA-USDT\t12.5000\t1699999000\t1")
+        );
+        assert_eq!(metadata.quarantined, 1);
+        assert_eq!(metadata.new_listings, 1);
+        assert_eq!(Listings::load(&dir).unwrap().listings[0].symbol_id, 2);
+        // Only sources the published record lists
+        assert_eq!(
+            metadata.quote_volume_24h["BTC-USDT"],
+            BTreeMap::from([("okx_spot", 5e8)])
+        );
+        assert_eq!(
+            metadata.scheduled_delistings,
+            BTreeMap::from([(
+                "ETH-USDT".to_string(),
+                BTreeMap::from([("binance_futures", 1_800_000_000_000)])
+            )])
+        );
+        assert!(report.contains(
+            "=== Exchange status (1 excluded) ===\n  binance_futures XEMUSDT (XEM-USDT): delivering (SETTLING)\n"
+        ));
+        assert!(report.contains(
+            "=== Liquidity (1 rejected) ===\n  okx_spot DEAD-USDT (DEAD-USDT): 24h volume 12\n"
+        ));
+        // No temp files left behind
+        assert!(std::fs::read_dir(&dir).unwrap().all(|e| !e
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .ends_with(".tmp")));
+
+        // A truncated symbols.bin is an error, not a panic
+        let data = std::fs::read(dir.join("symbols.bin")).unwrap();
+        std::fs::write(dir.join("symbols.bin"), &data[..data.len() / 2]).unwrap();
+        let err = SymbolTable::load(&dir).err().unwrap();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(ArtifactError::Truncated { .. })
+        ));
+        assert!(Previous::load(&dir).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_previous_reads_legacy_bincode() {
+        let dir = temp_dir("legacy");
+        std::fs::create_dir_all(&dir).unwrap();
+        // Unheadered, pre-price_multiplier layout
+        let r = record(0, "BTC-USDT", &[SourceId::OkxSpot]);
+        let v1 = vec![(r.symbol_id, r.name, r.source_names, r.min_qty, r.tick_size)];
+        std::fs::write(dir.join("symbols.bin"), bincode::serialize(&v1).unwrap()).unwrap();
+
+        let previous = Previous::load(&dir).unwrap();
+        assert_eq!(previous.symbols[0].name, "BTC-USDT");
+        assert_eq!(previous.symbols[0].price_multiplier[0], 1.0);
+        assert!(previous.directions.is_empty());
+        assert!(previous.legacy);
+
+        // Feeds still refuse it
+        assert!(SymbolTable::load(&dir).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1 +1,20 @@
+pub mod capture;
+pub mod diff;
+pub mod direction_builder;
+pub mod error;
+pub mod generator;
+pub mod liquidity;
+pub mod listings;
+pub mod normalizer;
+pub mod overrides;
+pub mod quarantine;
+pub mod registry;
+pub mod rest_client;
+pub mod status;
+pub mod validator;
 
+// Exchange stand-ins, shared with the pair-discovery tests
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_http;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_ws;
@@ -0,0 +1,239 @@
+//! Liquidity filter — drops listings that trade too little to matter.
+//!
+//! Each source's listing is judged on that source's own 24h ticker, against
+//! `discovery.min_quote_volume_24h` (quote currency) and
+//! `discovery.max_book_width_pct` (bid/ask width in % of mid). A dead listing
+//! on one venue does not affect the same symbol elsewhere; if that leaves
+//! the symbol on one side of a direction only, `build_candidates` drops it
+//! as usual.
+//!
+//! Missing data never rejects: a source without tickers (fetch failed), an
+//! instrument absent from its source's tickers, or a ticker without a book
+//! (Binance futures) skips the corresponding check. Pinned symbols are
+//! exempt.
+//!
+//! Runs after overrides, before candidates are built. The volumes of kept
+//! listings end up in metadata.json.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use common::config::DiscoveryConfig;
+use common::types::SourceId;
+
+use crate::normalizer::NormalizedInstrument;
+use crate::rest_client::TickerResults;
+
+#[derive(Debug, Clone, Copy)]
+pub struct LiquidityFilter {
+    pub min_quote_volume_24h: f64,
+    pub max_book_width_pct: f64,
+}
+
+impl LiquidityFilter {
+    pub fn from_config(config: &DiscoveryConfig) -> Self {
+        Self {
+            min_quote_volume_24h: config.min_quote_volume_24h,
+            max_book_width_pct: config.max_book_width_pct,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IlliquidReason {
+    LowVolume { quote_volume: f64 },
+    WideBook { width_pct: f64 },
+}
+
+impl IlliquidReason {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            IlliquidReason::LowVolume { .. } => "low_volume",
+            IlliquidReason::WideBook { .. } => "wide_book",
+        }
+    }
+}
+
+impl fmt::Display for IlliquidReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IlliquidReason::LowVolume { quote_volume } => {
+                write!(f, "24h volume {:.0}", quote_volume)
+            }
+            IlliquidReason::WideBook { width_pct } => write!(f, "book width {:.2}%", width_pct),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Illiquid {
+    pub source: SourceId,
+    pub exchange_symbol: String,
+    /// Canonical name, e.g. "BTC-USDT".
+    pub name: String,
+    pub reason: IlliquidReason,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LiquidityReport {
+    pub rejected: Vec<Illiquid>,
+    /// 24h quote volume of every kept listing: name -> source name -> volume.
+    pub volumes: BTreeMap<String, BTreeMap<&'static str, f64>>,
+}
+
+impl LiquidityReport {
+    /// Rejection counts keyed by (source name, reason kind).
+    pub fn counts(&self) -> BTreeMap<(&'static str, &'static str), usize> {
+        let mut counts = BTreeMap::new();
+        for r in &self.rejected {
+            *counts
+                .entry((r.source.name(), r.reason.kind()))
+                .or_insert(0) += 1;
+        }
+        counts
+    }
+}
+
+impl LiquidityFilter {
+    /// Remove illiquid listings from `normalized`. Names in `exempt` (the
+    /// pinned symbols) are kept regardless.
+    pub fn apply(
+        &self,
+        tickers: &TickerResults,
+        normalized: &mut Vec<NormalizedInstrument>,
+        exempt: &[String],
+    ) -> LiquidityReport {
+        let mut report = LiquidityReport::default();
+        normalized.retain(|inst| {
+            let Some(ticker) = tickers
+                .get(inst.source)
+                .and_then(|t| t.get(&inst.raw.exchange_symbol))
+            else {
+                return true;
+            };
+            let reason = match (ticker.quote_volume, ticker.book_width_pct()) {
+                (Some(v), _) if v < self.min_quote_volume_24h => {
+                    Some(IlliquidReason::LowVolume { quote_volume: v })
+                }
+                (_, Some(w)) if w > self.max_book_width_pct => {
+                    Some(IlliquidReason::WideBook { width_pct: w })
+                }
+                _ => None,
+            };
+            match reason {
+                Some(reason) if !exempt.contains(&inst.name) => {
+                    report.rejected.push(Illiquid {
+                        source: inst.source,
+                        exchange_symbol: inst.raw.exchange_symbol.clone(),
+                        name: inst.name.clone(),
+                        reason,
+                    });
+                    false
+                }
+                _ => {
+                    if let Some(v) = ticker.quote_volume {
+                        report
+                            .volumes
+                            .entry(inst.name.clone())
+                            .or_default()
+                            .insert(inst.source.name(), v);
+                    }
+                    true
+                }
+            }
+        });
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::types::NUM_SOURCES;
+
+    use crate::rest_client::{RawInstrument, Ticker24h, Tickers};
+    use crate::status::InstrumentState;
+
+    fn inst(source: SourceId, symbol: &str, name: &str) -> NormalizedInstrument {
+        let (base, quote) = name.split_once('-').unwrap();
+        NormalizedInstrument {
+            source,
+            name: name.to_string(),
+            base: base.to_string(),
+            quote: quote.to_string(),
+            price_multiplier: 1.0,
+            raw: RawInstrument {
+                exchange_symbol: symbol.to_string(),
+                base_asset: base.to_string(),
+                quote_asset: quote.to_string(),
+                status: "TRADING".to_string(),
+                state: InstrumentState::Trading,
+                min_qty: None,
+                tick_size: None,
+                spec: Default::default(),
+            },
+        }
+    }
+
+    fn ticker(volume: f64, bid: f64, ask: f64) -> Ticker24h {
+        Ticker24h {
+            quote_volume: Some(volume),
+            best_bid: Some(bid),
+            best_ask: Some(ask),
+        }
+    }
+
+    #[test]
+    fn test_liquidity_filter() {
+        let spot = SourceId::BinanceSpot;
+        let fut = SourceId::BybitFutures;
+        let mut tickers: [Option<Tickers>; NUM_SOURCES as usize] = Default::default();
+        tickers[spot.index()] = Some(Tickers::from([
+            ("BTCUSDT".to_string(), ticker(1e9, 100.0, 100.01)),
+            ("DEADUSDT".to_string(), ticker(500.0, 1.0, 1.0)),
+            ("WIDEUSDT".to_string(), ticker(1e6, 1.0, 1.05)),
+            ("PINUSDT".to_string(), ticker(10.0, 1.0, 1.0)),
+        ]));
+        // Bybit tickers failed: nothing filtered there
+        let tickers = TickerResults { tickers };
+
+        let mut normalized = vec![
+            inst(spot, "BTCUSDT", "BTC-USDT"),
+            inst(spot, "DEADUSDT", "DEAD-USDT"),
+            inst(spot, "WIDEUSDT", "WIDE-USDT"),
+            inst(spot, "PINUSDT", "PIN-USDT"),
+            inst(spot, "FRESHUSDT", "FRESH-USDT"),
+            inst(fut, "DEADUSDT", "DEAD-USDT"),
+        ];
+        let filter = LiquidityFilter {
+            min_quote_volume_24h: 100_000.0,
+            max_book_width_pct: 1.0,
+        };
+        let report = filter.apply(&tickers, &mut normalized, &["PIN-USDT".to_string()]);
+
+        let kept: Vec<_> = normalized
+            .iter()
+            .map(|i| (i.source, i.name.as_str()))
+            .collect();
+        assert_eq!(
+            kept,
+            vec![
+                (spot, "BTC-USDT"),
+                (spot, "PIN-USDT"),
+                (spot, "FRESH-USDT"),
+                (fut, "DEAD-USDT"),
+            ]
+        );
+        assert_eq!(
+            report.rejected[0].reason,
+            IlliquidReason::LowVolume {
+                quote_volume: 500.0
+            }
+        );
+        assert_eq!(report.rejected[1].reason.kind(), "wide_book");
+        assert_eq!(report.counts()[&("binance_spot", "low_volume")], 1);
+        assert_eq!(report.volumes["BTC-USDT"]["binance_spot"], 1e9);
+        assert_eq!(report.volumes["PIN-USDT"]["binance_spot"], 10.0);
+        assert!(!report.volumes.contains_key("FRESH-USDT"));
+    }
+}
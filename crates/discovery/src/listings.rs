@@ -0,0 +1,248 @@
+//! New-listing detector.
+//!
+//! Cross-venue spreads are widest right after a token lists on its second
+//! venue, so the daemon polls the instrument lists every
+//! `discovery.listing_poll_interval_sec` between full runs (REST only: no
+//! tickers, no WS validation) and runs the pipeline at once when a pair
+//! newly appears on both sources of a direction.
+//!
+//! A pair is new when both sources list it now, the previous generation's
+//! direction does not hold it, and at least one of the two sources did not
+//! list it before. The last condition keeps quarantined pairs from counting
+//! as listings. A leg dropped by validation or the liquidity filter is not
+//! in the previous generation either, so its return does count; the daemon
+//! fast-tracks each pair only once. Without a previous generation there is
+//! no baseline and nothing is new.
+//!
+//! New pairs are exempt from the liquidity filter (a fresh listing has no
+//! 24h volume yet). Those that make it into the published directions are
+//! written to generated/listings.json for the engine's NewListing events.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+use common::config::DirectionsConfig;
+use common::directions::DirectionRecord;
+use common::listings::ListingRecord;
+use common::symbols::SymbolRecord;
+use common::types::NUM_SOURCES;
+
+use crate::normalizer::NormalizedInstrument;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewListing {
+    pub direction_id: u8,
+    pub direction: String,
+    /// Canonical name, e.g. "XYZ-USDT".
+    pub name: String,
+    /// Listing time of the newer leg, unix ms.
+    pub listed_at: Option<u64>,
+}
+
+impl fmt::Display for NewListing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "new listing {} in {}", self.name, self.direction)
+    }
+}
+
+/// Pairs of `directions` that `normalized` lists on both sources and the
+/// previous generation did not, in (direction_id, name) order.
+pub fn detect(
+    previous_symbols: &[SymbolRecord],
+    previous_directions: &[DirectionRecord],
+    normalized: &[NormalizedInstrument],
+    directions: &DirectionsConfig,
+) -> Vec<NewListing> {
+    if previous_symbols.is_empty() {
+        return Vec::new();
+    }
+    let previous: HashMap<&str, &SymbolRecord> = previous_symbols
+        .iter()
+        .map(|r| (r.name.as_str(), r))
+        .collect();
+    let previous_pairs: HashSet<(u8, &str)> = previous_directions
+        .iter()
+        .flat_map(|d| {
+            d.symbols.iter().filter_map(move |&id| {
+                previous_symbols
+                    .get(id as usize)
+                    .map(|r| (d.direction_id, r.name.as_str()))
+            })
+        })
+        .collect();
+
+    let mut listed: BTreeMap<&str, [Option<&NormalizedInstrument>; NUM_SOURCES as usize]> =
+        BTreeMap::new();
+    for n in normalized {
+        listed.entry(n.name.as_str()).or_default()[n.source.index()] = Some(n);
+    }
+
+    let mut out = Vec::new();
+    for d in &directions.direction {
+        let (spot, futures) = (d.spot_source as usize, d.futures_source as usize);
+        for (&name, sources) in &listed {
+            let (Some(s), Some(f)) = (sources[spot], sources[futures]) else {
+                continue;
+            };
+            if previous_pairs.contains(&(d.id, name)) {
+                continue;
+            }
+            let was_on_both = previous.get(name).is_some_and(|r| {
+                r.source_names[spot].is_some() && r.source_names[futures].is_some()
+            });
+            if was_on_both {
+                continue;
+            }
+            out.push(NewListing {
+                direction_id: d.id,
+                direction: d.name.clone(),
+                name: name.to_string(),
+                listed_at: s.raw.spec.listed_at.max(f.raw.spec.listed_at),
+            });
+        }
+    }
+    out.sort_by(|a, b| (a.direction_id, &a.name).cmp(&(b.direction_id, &b.name)));
+    out
+}
+
+/// The `new` pairs that made it into the published `directions`, as
+/// listings.json records. `now_ms` is unix milliseconds.
+pub fn records(
+    new: &[NewListing],
+    symbols: &[SymbolRecord],
+    directions: &[DirectionRecord],
+    now_ms: u64,
+) -> Vec<ListingRecord> {
+    let mut out = Vec::new();
+    for listing in new {
+        let Some(d) = directions
+            .iter()
+            .find(|d| d.direction_id == listing.direction_id)
+        else {
+            continue;
+        };
+        let Some(&symbol_id) = d
+            .symbols
+            .iter()
+            .find(|&&id| symbols[id as usize].name == listing.name)
+        else {
+            continue;
+        };
+        out.push(ListingRecord {
+            direction_id: d.direction_id,
+            symbol_id,
+            name: listing.name.clone(),
+            spot_source: d.spot_source,
+            futures_source: d.futures_source,
+            detected_at: now_ms,
+            listed_at: listing.listed_at,
+        });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::config::DirectionConfigEntry;
+    use common::symbols::InstrumentSpec;
+    use common::types::SourceId;
+
+    use crate::rest_client::RawInstrument;
+    use crate::status::InstrumentState;
+
+    const SPOT: SourceId = SourceId::BybitSpot;
+    const FUT: SourceId = SourceId::BinanceFutures;
+
+    fn inst(source: SourceId, name: &str, listed_at: Option<u64>) -> NormalizedInstrument {
+        let (base, quote) = name.split_once('-').unwrap();
+        NormalizedInstrument {
+            source,
+            name: name.to_string(),
+            base: base.to_string(),
+            quote: quote.to_string(),
+            price_multiplier: 1.0,
+            raw: RawInstrument {
+                exchange_symbol: name.replace('-', ""),
+                base_asset: base.to_string(),
+                quote_asset: quote.to_string(),
+                status: "Trading".to_string(),
+                state: InstrumentState::Trading,
+                min_qty: None,
+                tick_size: None,
+                spec: InstrumentSpec {
+                    listed_at,
+                    ..Default::default()
+                },
+            },
+        }
+    }
+
+    fn record(id: u16, name: &str, sources: &[SourceId]) -> SymbolRecord {
+        sources.iter().fold(SymbolRecord::new(id, name), |r, &s| {
+            r.with_source(s, name.replace('-', ""))
+        })
+    }
+
+    fn direction(symbols: Vec<u16>) -> DirectionRecord {
+        DirectionRecord {
+            direction_id: 2,
+            spot_source: SPOT as u8,
+            futures_source: FUT as u8,
+            name: "bybit_spot_binance_futures".to_string(),
+            symbols,
+        }
+    }
+
+    #[test]
+    fn test_detect_new_listings() {
+        let defs = DirectionsConfig {
+            direction: vec![DirectionConfigEntry {
+                id: 2,
+                spot_source: SPOT as u8,
+                futures_source: FUT as u8,
+                name: "bybit_spot_binance_futures".to_string(),
+            }],
+        };
+        let previous = vec![
+            record(0, "BTC-USDT", &[SPOT, FUT]),
+            // Listed on both before, quarantined out of the direction
+            record(1, "NEIRO-USDT", &[SPOT, FUT]),
+            record(2, "XYZ-USDT", &[SPOT]),
+        ];
+        let previous_directions = vec![direction(vec![0])];
+        let normalized = vec![
+            inst(SPOT, "BTC-USDT", None),
+            inst(FUT, "BTC-USDT", None),
+            inst(SPOT, "NEIRO-USDT", None),
+            inst(FUT, "NEIRO-USDT", None),
+            inst(SPOT, "XYZ-USDT", Some(1_600_000_000_000)),
+            inst(FUT, "XYZ-USDT", Some(1_700_000_000_000)),
+            inst(SPOT, "NEW-USDT", None),
+            inst(FUT, "NEW-USDT", None),
+            inst(SPOT, "HALF-USDT", None),
+        ];
+
+        let new = detect(&previous, &previous_directions, &normalized, &defs);
+        let names: Vec<_> = new.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["NEW-USDT", "XYZ-USDT"]);
+        assert_eq!(new[1].listed_at, Some(1_700_000_000_000));
+        assert_eq!(
+            new[1].to_string(),
+            "new listing XYZ-USDT in bybit_spot_binance_futures"
+        );
+        // First run: no baseline
+        assert!(detect(&[], &[], &normalized, &defs).is_empty());
+
+        // NEW-USDT did not make it into the published direction
+        let symbols = vec![
+            record(0, "BTC-USDT", &[SPOT, FUT]),
+            record(1, "NEIRO-USDT", &[SPOT, FUT]),
+            record(2, "XYZ-USDT", &[SPOT, FUT]),
+        ];
+        let published = records(&new, &symbols, &[direction(vec![0, 2])], 42);
+        assert_eq!(published.len(), 1);
+        assert_eq!((published[0].symbol_id, published[0].detected_at), (2, 42));
+        assert_eq!(published[0].spot_source, SPOT as u8);
+    }
+}
@@ -0,0 +1,501 @@
+//! Symbol normalizer — exchange symbol → canonical "BASE-QUOTE".
+//!
+//! Matching is strict: the exchange symbol must be exactly what the source's
+//! naming scheme produces from the reported base/quote assets.
+//!   Binance, Bybit, MEXC spot   BASEQUOTE        "BTCUSDT"
+//!   MEXC futures                BASE_QUOTE       "BTC_USDT"
+//!   OKX spot                    BASE-QUOTE       "BTC-USDT"
+//!   OKX swap                    BASE-QUOTE-SWAP  "BTC-USDT-SWAP"
+//! Anything else is rejected with a specific `NormalizationError`, and every
+//! rejection is kept in the `NormalizationReport`.
+//!
+//! Low-priced assets are often listed in a bigger denomination: "1000PEPEUSDT"
+//! on Binance/Bybit quotes 1000 PEPE. The prefix is stripped from the
+//! canonical name ("PEPE-USDT") and kept as the price multiplier, so the pair
+//! lines up with venues quoting one PEPE. OKX `ctVal` and MEXC `contractSize`
+//! only size contracts (prices there are per coin of the base), so they scale
+//! min_qty in rest_client but never the price.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use common::symbols::is_asset;
+use common::types::{SourceId, NUM_SOURCES};
+
+use crate::rest_client::{FetchResults, RawInstrument};
+
+/// Why an instrument was dropped during normalization.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NormalizationError {
+    /// Base or quote asset is empty or contains characters other than A-Z/0-9.
+    InvalidAsset { asset: String },
+    /// Base and quote are the same asset.
+    BaseEqualsQuote { asset: String },
+    /// Quote asset is not in `discovery.quote_filter`.
+    UnsupportedQuote { quote: String },
+    /// The quote spelled in the symbol differs from the reported quote asset
+    /// ("BTCUSD" reported with quote "USDT").
+    QuoteMismatch { in_symbol: String, reported: String },
+    /// base+quote is followed by something the source's scheme does not allow
+    /// ("BTCUSDT_250926", "BTC-USDT-250926").
+    UnknownSuffix { suffix: String },
+    /// The symbol does not decompose into the reported base and quote at all.
+    SymbolMismatch { expected: String },
+}
+
+impl NormalizationError {
+    /// Stable short name, used as the counter key in reports.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NormalizationError::InvalidAsset { .. } => "invalid_asset",
+            NormalizationError::BaseEqualsQuote { .. } => "base_equals_quote",
+            NormalizationError::UnsupportedQuote { .. } => "unsupported_quote",
+            NormalizationError::QuoteMismatch { .. } => "quote_mismatch",
+            NormalizationError::UnknownSuffix { .. } => "unknown_suffix",
+            NormalizationError::SymbolMismatch { .. } => "symbol_mismatch",
+        }
+    }
+}
+
+impl fmt::Display for NormalizationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NormalizationError::InvalidAsset { asset } => write!(f, "invalid asset {:?}", asset),
+            NormalizationError::BaseEqualsQuote { asset } => {
+                write!(f, "base and quote are both {}", asset)
+            }
+            NormalizationError::UnsupportedQuote { quote } => {
+                write!(f, "quote {} not in quote_filter", quote)
+            }
+            NormalizationError::QuoteMismatch {
+                in_symbol,
+                reported,
+            } => write!(
+                f,
+                "symbol quotes in {} but exchange reports {}",
+                in_symbol, reported
+            ),
+            NormalizationError::UnknownSuffix { suffix } => {
+                write!(f, "unknown suffix {:?}", suffix)
+            }
+            NormalizationError::SymbolMismatch { expected } => {
+                write!(
+                    f,
+                    "symbol does not match base/quote (expected {})",
+                    expected
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for NormalizationError {}
+
+/// An instrument that passed normalization.
+#[derive(Debug, Clone)]
+pub struct NormalizedInstrument {
+    pub source: SourceId,
+    /// Canonical "BASE-QUOTE" name, stored in `SymbolRecord::name`.
+    pub name: String,
+    pub base: String,
+    pub quote: String,
+    /// Base units one exchange price covers, see `SymbolRecord::price_multiplier`.
+    pub price_multiplier: f64,
+    pub raw: RawInstrument,
+}
+
+/// A dropped instrument and the reason.
+#[derive(Debug, Clone)]
+pub struct Rejection {
+    pub source: SourceId,
+    pub exchange_symbol: String,
+    pub error: NormalizationError,
+}
+
+/// Outcome of normalizing every fetched source.
+#[derive(Debug, Default)]
+pub struct NormalizationReport {
+    pub accepted: [usize; NUM_SOURCES as usize],
+    pub rejections: Vec<Rejection>,
+}
+
+impl NormalizationReport {
+    /// Rejection counts keyed by (source name, error kind).
+    pub fn counts(&self) -> BTreeMap<(&'static str, &'static str), usize> {
+        let mut counts = BTreeMap::new();
+        for r in &self.rejections {
+            *counts.entry((r.source.name(), r.error.kind())).or_insert(0) += 1;
+        }
+        counts
+    }
+}
+
+/// Separator and mandatory suffix of a source's symbol scheme.
+fn scheme(source: SourceId) -> (Option<char>, Option<&'static str>) {
+    match source {
+        SourceId::MexcFutures => (Some('_'), None),
+        SourceId::OkxSpot => (Some('-'), None),
+        SourceId::OkxFutures => (Some('-'), Some("SWAP")),
+        _ => (None, None),
+    }
+}
+
+/// Exchange symbol the source would use for base/quote.
+pub fn exchange_symbol(source: SourceId, base: &str, quote: &str) -> String {
+    match scheme(source) {
+        (Some(sep), Some(suffix)) => format!("{base}{sep}{quote}{sep}{suffix}"),
+        (Some(sep), None) => format!("{base}{sep}{quote}"),
+        _ => format!("{base}{quote}"),
+    }
+}
+
+/// Denomination prefixes, longest first.
+const MULTIPLIER_PREFIXES: [(&str, f64); 5] = [
+    ("1000000", 1e6),
+    ("100000", 1e5),
+    ("10000", 1e4),
+    ("1000", 1e3),
+    ("1M", 1e6),
+];
+
+/// Split a denomination prefix off `base`: "1000PEPE" → ("PEPE", 1000.0),
+/// "1MBABYDOGE" → ("BABYDOGE", 1e6). The rest must be at least two
+/// characters and start with a letter, so "1INCH" or "1000" stay as they are.
+pub fn split_multiplier(base: &str) -> (&str, f64) {
+    for (prefix, multiplier) in MULTIPLIER_PREFIXES {
+        if let Some(rest) = base.strip_prefix(prefix) {
+            if rest.len() >= 2 && rest.as_bytes()[0].is_ascii_uppercase() {
+                return (rest, multiplier);
+            }
+        }
+    }
+    (base, 1.0)
+}
+
+fn check_asset(asset: &str) -> Result<(), NormalizationError> {
+    if is_asset(asset) {
+        Ok(())
+    } else {
+        Err(NormalizationError::InvalidAsset {
+            asset: asset.to_string(),
+        })
+    }
+}
+
+pub struct Normalizer {
+    quote_filter: Vec<String>,
+}
+
+impl Normalizer {
+    pub fn new(quote_filter: &[String]) -> Self {
+        Self {
+            quote_filter: quote_filter.to_vec(),
+        }
+    }
+
+    /// Normalize one instrument of `source`.
+    pub fn normalize(
+        &self,
+        source: SourceId,
+        raw: &RawInstrument,
+    ) -> Result<NormalizedInstrument, NormalizationError> {
+        let base = raw.base_asset.as_str();
+        let quote = raw.quote_asset.as_str();
+        check_asset(base)?;
+        check_asset(quote)?;
+        if base == quote {
+            return Err(NormalizationError::BaseEqualsQuote {
+                asset: base.to_string(),
+            });
+        }
+        if !self.quote_filter.iter().any(|q| q == quote) {
+            return Err(NormalizationError::UnsupportedQuote {
+                quote: quote.to_string(),
+            });
+        }
+        match_symbol(source, &raw.exchange_symbol, base, quote)?;
+
+        let (unit_base, price_multiplier) = split_multiplier(base);
+        Ok(NormalizedInstrument {
+            source,
+            name: format!("{}-{}", unit_base, quote),
+            base: unit_base.to_string(),
+            quote: quote.to_string(),
+            price_multiplier,
+            raw: raw.clone(),
+        })
+    }
+
+    /// Normalize every fetched source, collecting rejections into the report.
+    pub fn normalize_all(
+        &self,
+        fetched: &FetchResults,
+    ) -> (Vec<NormalizedInstrument>, NormalizationReport) {
+        let mut accepted = Vec::new();
+        let mut report = NormalizationReport::default();
+        for source in SourceId::ALL {
+            let Some(instruments) = fetched.get(source) else {
+                continue;
+            };
+            for raw in instruments {
+                match self.normalize(source, raw) {
+                    Ok(n) => {
+                        report.accepted[source.index()] += 1;
+                        accepted.push(n);
+                    }
+                    Err(error) => report.rejections.push(Rejection {
+                        source,
+                        exchange_symbol: raw.exchange_symbol.clone(),
+                        error,
+                    }),
+                }
+            }
+        }
+        (accepted, report)
+    }
+}
+
+/// Check that `symbol` is exactly base+quote in the source's scheme.
+fn match_symbol(
+    source: SourceId,
+    symbol: &str,
+    base: &str,
+    quote: &str,
+) -> Result<(), NormalizationError> {
+    let expected = exchange_symbol(source, base, quote);
+    if symbol == expected {
+        return Ok(());
+    }
+    let mismatch = || NormalizationError::SymbolMismatch {
+        expected: expected.clone(),
+    };
+
+    match scheme(source) {
+        (None, _) => {
+            // Concatenated: "BTCUSDT". Only the base prefix is unambiguous.
+            let rest = symbol.strip_prefix(base).ok_or_else(mismatch)?;
+            match rest.strip_prefix(quote) {
+                Some(suffix) => Err(NormalizationError::UnknownSuffix {
+                    suffix: suffix.to_string(),
+                }),
+                None => Err(NormalizationError::QuoteMismatch {
+                    in_symbol: rest.to_string(),
+                    reported: quote.to_string(),
+                }),
+            }
+        }
+        (Some(sep), required_suffix) => {
+            let mut parts = symbol.split(sep);
+            if parts.next() != Some(base) {
+                return Err(mismatch());
+            }
+            match parts.next() {
+                Some(q) if q == quote => {}
+                Some(q) => {
+                    return Err(NormalizationError::QuoteMismatch {
+                        in_symbol: q.to_string(),
+                        reported: quote.to_string(),
+                    })
+                }
+                None => return Err(mismatch()),
+            }
+            let suffix: Vec<&str> = parts.collect();
+            match required_suffix {
+                Some(req) if suffix.is_empty() => Err(NormalizationError::UnknownSuffix {
+                    suffix: format!("missing {}", req),
+                }),
+                _ => Err(NormalizationError::UnknownSuffix {
+                    suffix: suffix.join(&sep.to_string()),
+                }),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::InstrumentState;
+
+    fn raw(symbol: &str, base: &str, quote: &str) -> RawInstrument {
+        RawInstrument {
+            exchange_symbol: symbol.to_string(),
+            base_asset: base.to_string(),
+            quote_asset: quote.to_string(),
+            status: "TRADING".to_string(),
+            state: InstrumentState::Trading,
+            min_qty: None,
+            tick_size: None,
+            spec: Default::default(),
+        }
+    }
+
+    fn normalizer() -> Normalizer {
+        Normalizer::new(&["USDT".to_string()])
+    }
+
+    #[test]
+    fn test_accepts_every_scheme() {
+        let n = normalizer();
+        let cases = [
+            (SourceId::BinanceSpot, "BTCUSDT"),
+            (SourceId::BinanceFutures, "BTCUSDT"),
+            (SourceId::BybitSpot, "BTCUSDT"),
+            (SourceId::BybitFutures, "BTCUSDT"),
+            (SourceId::MexcSpot, "BTCUSDT"),
+            (SourceId::MexcFutures, "BTC_USDT"),
+            (SourceId::OkxSpot, "BTC-USDT"),
+            (SourceId::OkxFutures, "BTC-USDT-SWAP"),
+        ];
+        for (source, symbol) in cases {
+            let ok = n.normalize(source, &raw(symbol, "BTC", "USDT")).unwrap();
+            assert_eq!(ok.name, "BTC-USDT", "{:?} {}", source, symbol);
+            assert_eq!(exchange_symbol(source, "BTC", "USDT"), symbol);
+        }
+    }
+
+    #[test]
+    fn test_rejections() {
+        let n = normalizer();
+        let err = |source, symbol, base, quote| {
+            n.normalize(source, &raw(symbol, base, quote)).unwrap_err()
+        };
+
+        // BTCUSD-vs-BTCUSDT: no substring matching
+        assert_eq!(
+            err(SourceId::BinanceSpot, "BTCUSD", "BTC", "USDT"),
+            NormalizationError::QuoteMismatch {
+                in_symbol: "USD".into(),
+                reported: "USDT".into()
+            }
+        );
+        assert_eq!(
+            err(SourceId::OkxSpot, "BTC-USDC", "BTC", "USDT"),
+            NormalizationError::QuoteMismatch {
+                in_symbol: "USDC".into(),
+                reported: "USDT".into()
+            }
+        );
+        assert_eq!(
+            err(SourceId::BinanceFutures, "BTCUSDT_250926", "BTC", "USDT"),
+            NormalizationError::UnknownSuffix {
+                suffix: "_250926".into()
+            }
+        );
+        assert_eq!(
+            err(SourceId::OkxFutures, "BTC-USDT-250926", "BTC", "USDT"),
+            NormalizationError::UnknownSuffix {
+                suffix: "250926".into()
+            }
+        );
+        assert_eq!(
+            err(SourceId::OkxFutures, "BTC-USDT", "BTC", "USDT").kind(),
+            "unknown_suffix"
+        );
+        assert_eq!(
+            err(SourceId::MexcSpot, "USDTUSDT", "USDT", "USDT"),
+            NormalizationError::BaseEqualsQuote {
+                asset: "USDT".into()
+            }
+        );
+        assert_eq!(
+            err(SourceId::BybitSpot, "BTCEUR", "BTC", "EUR"),
+            NormalizationError::UnsupportedQuote {
+                quote: "EUR".into()
+            }
+        );
+        assert_eq!(
+            err(SourceId::MexcFutures, "WBTC_USDT", "BTC", "USDT"),
+            NormalizationError::SymbolMismatch {
+                expected: "BTC_USDT".into()
+            }
+        );
+        assert_eq!(
+            err(SourceId::MexcSpot, "btcUSDT", "btc", "USDT"),
+            NormalizationError::InvalidAsset {
+                asset: "btc".into()
+            }
+        );
+        assert_eq!(
+            err(SourceId::BinanceSpot, "USDT", "", "USDT").kind(),
+            "invalid_asset"
+        );
+    }
+
+    #[test]
+    fn test_multiplier_prefix() {
+        let n = normalizer();
+        let cases = [
+            (
+                SourceId::BinanceFutures,
+                "1000PEPEUSDT",
+                "1000PEPE",
+                "PEPE-USDT",
+                1e3,
+            ),
+            (
+                SourceId::BybitFutures,
+                "10000LADYSUSDT",
+                "10000LADYS",
+                "LADYS-USDT",
+                1e4,
+            ),
+            (
+                SourceId::BinanceFutures,
+                "1000000MOGUSDT",
+                "1000000MOG",
+                "MOG-USDT",
+                1e6,
+            ),
+            (
+                SourceId::BinanceSpot,
+                "1MBABYDOGEUSDT",
+                "1MBABYDOGE",
+                "BABYDOGE-USDT",
+                1e6,
+            ),
+            (
+                SourceId::BinanceSpot,
+                "1INCHUSDT",
+                "1INCH",
+                "1INCH-USDT",
+                1.0,
+            ),
+            (
+                SourceId::OkxFutures,
+                "PEPE-USDT-SWAP",
+                "PEPE",
+                "PEPE-USDT",
+                1.0,
+            ),
+        ];
+        for (source, symbol, base, name, multiplier) in cases {
+            let ok = n.normalize(source, &raw(symbol, base, "USDT")).unwrap();
+            assert_eq!(ok.name, name, "{}", symbol);
+            assert_eq!(ok.price_multiplier, multiplier, "{}", symbol);
+            assert_eq!(ok.raw.exchange_symbol, symbol);
+        }
+        assert_eq!(split_multiplier("1000X"), ("1000X", 1.0));
+        assert_eq!(split_multiplier("1000"), ("1000", 1.0));
+    }
+
+    #[test]
+    fn test_normalize_all_report() {
+        let mut instruments: [Option<Vec<RawInstrument>>; NUM_SOURCES as usize] =
+            std::array::from_fn(|_| None);
+        instruments[SourceId::BinanceSpot.index()] = Some(vec![
+            raw("BTCUSDT", "BTC", "USDT"),
+            raw("BTCUSD", "BTC", "USDT"),
+            raw("ETHUSD", "ETH", "USDT"),
+        ]);
+        instruments[SourceId::OkxFutures.index()] = Some(vec![raw("ETH-USDT-SWAP", "ETH", "USDT")]);
+        let fetched = FetchResults { instruments };
+
+        let (accepted, report) = normalizer().normalize_all(&fetched);
+        assert_eq!(accepted.len(), 2);
+        assert_eq!(report.accepted[SourceId::BinanceSpot.index()], 1);
+        assert_eq!(report.accepted[SourceId::OkxFutures.index()], 1);
+        assert_eq!(report.rejections.len(), 2);
+        assert_eq!(report.counts()[&("binance_spot", "quote_mismatch")], 2);
+    }
+}
@@ -0,0 +1,600 @@
+//! Manual symbol overrides — config/overrides.toml, optional.
+//!
+//! Applied right after normalization:
+//!   [[ban]]  drop a canonical symbol everywhere, or on one `source` only
+//!   [[map]]  force an exchange symbol to a canonical name (renamed tickers);
+//!            also rescues an instrument the normalizer rejected. Another
+//!            instrument of the same source that normalized to that name
+//!            is dropped, so the mapping always wins
+//!   pin      symbols that must be included: liquidity thresholds and WS
+//!            validation failures are ignored for them, and a pinned symbol
+//!            that ends up missing is reported
+//!
+//! Every override that took effect is returned as an `AppliedOverride` and
+//! ends up in validation_report.txt.
+//!
+//! ```toml
+//! pin = ["BTC-USDT", "ETH-USDT"]
+//!
+//! [[ban]]
+//! symbol = "LUNA-USDT"
+//!
+//! [[ban]]
+//! symbol = "XYZ-USDT"
+//! source = "mexc_futures"
+//!
+//! [[map]]
+//! source = "bybit_spot"
+//! exchange_symbol = "RNDRUSDT"
+//! name = "RENDER-USDT"
+//! multiplier = 1            # optional, see `ForcedMapping::multiplier`
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use tracing::warn;
+
+use common::symbols::{split_name, SymbolRecord};
+use common::types::SourceId;
+
+use crate::normalizer::{split_multiplier, NormalizationReport, NormalizedInstrument};
+use crate::rest_client::FetchResults;
+use crate::validator::ValidationReport;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Ban {
+    pub symbol: String,
+    /// Source name ("okx_spot"); all sources when absent.
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ForcedMapping {
+    pub source: String,
+    pub exchange_symbol: String,
+    /// Canonical "BASE-QUOTE" name.
+    pub name: String,
+    /// Price multiplier; defaults to the denomination prefix of the
+    /// reported base if stripping it gives `name`'s base ("1000RATS" →
+    /// "RATS" = 1000), otherwise 1.
+    #[serde(default)]
+    pub multiplier: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Overrides {
+    #[serde(default)]
+    pub pin: Vec<String>,
+    #[serde(default)]
+    pub ban: Vec<Ban>,
+    #[serde(default)]
+    pub map: Vec<ForcedMapping>,
+}
+
+/// An override that changed the outcome of this run.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppliedOverride {
+    Banned {
+        source: SourceId,
+        exchange_symbol: String,
+        name: String,
+    },
+    /// `from` is None if the normalizer had rejected the instrument.
+    Mapped {
+        source: SourceId,
+        exchange_symbol: String,
+        from: Option<String>,
+        to: String,
+    },
+    /// Dropped because a forced mapping on its source claimed its name.
+    Displaced {
+        source: SourceId,
+        exchange_symbol: String,
+        name: String,
+        by: String,
+    },
+    /// Kept despite failing WS validation.
+    PinnedKept {
+        source: SourceId,
+        exchange_symbol: String,
+        name: String,
+        reason: String,
+    },
+    /// Pinned, but not in the final generation.
+    PinnedMissing { name: String },
+}
+
+impl fmt::Display for AppliedOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppliedOverride::Banned {
+                source,
+                exchange_symbol,
+                name,
+            } => write!(
+                f,
+                "ban     {} {} ({})",
+                source.name(),
+                exchange_symbol,
+                name
+            ),
+            AppliedOverride::Mapped {
+                source,
+                exchange_symbol,
+                from,
+                to,
+            } => write!(
+                f,
+                "map     {} {}: {} -> {}",
+                source.name(),
+                exchange_symbol,
+                from.as_deref().unwrap_or("rejected"),
+                to
+            ),
+            AppliedOverride::Displaced {
+                source,
+                exchange_symbol,
+                name,
+                by,
+            } => write!(
+                f,
+                "map     {} {} ({}) displaced by {}",
+                source.name(),
+                exchange_symbol,
+                name,
+                by
+            ),
+            AppliedOverride::PinnedKept {
+                source,
+                exchange_symbol,
+                name,
+                reason,
+            } => write!(
+                f,
+                "pin     {} {} ({}) kept despite: {}",
+                source.name(),
+                exchange_symbol,
+                name,
+                reason
+            ),
+            AppliedOverride::PinnedMissing { name } => {
+                write!(f, "pin     {} missing from this generation", name)
+            }
+        }
+    }
+}
+
+fn parse_source(name: &str) -> Result<SourceId> {
+    SourceId::from_name(name).with_context(|| format!("unknown source {:?}", name))
+}
+
+impl Overrides {
+    /// Load config/overrides.toml; a missing file means no overrides.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read overrides: {}", path.display()))?;
+        let overrides: Overrides = toml::from_str(&content)
+            .with_context(|| format!("failed to parse overrides: {}", path.display()))?;
+        overrides
+            .check()
+            .with_context(|| format!("invalid overrides: {}", path.display()))?;
+        Ok(overrides)
+    }
+
+    /// Reject unknown sources, malformed names and ban/pin conflicts.
+    pub fn check(&self) -> Result<()> {
+        for name in &self.pin {
+            anyhow::ensure!(split_name(name).is_some(), "pin: bad symbol {:?}", name);
+        }
+        for ban in &self.ban {
+            anyhow::ensure!(
+                split_name(&ban.symbol).is_some(),
+                "ban: bad symbol {:?}",
+                ban.symbol
+            );
+            if let Some(source) = &ban.source {
+                parse_source(source).context("ban")?;
+            } else {
+                anyhow::ensure!(
+                    !self.pin.contains(&ban.symbol),
+                    "{} is both pinned and banned",
+                    ban.symbol
+                );
+            }
+        }
+        let mut seen = BTreeSet::new();
+        let mut targets = BTreeSet::new();
+        for m in &self.map {
+            let source = parse_source(&m.source).context("map")?;
+            anyhow::ensure!(
+                split_name(&m.name).is_some(),
+                "map: bad symbol {:?}",
+                m.name
+            );
+            anyhow::ensure!(
+                m.multiplier.is_none_or(|x| x.is_finite() && x > 0.0),
+                "map: {} {} multiplier must be positive",
+                m.source,
+                m.exchange_symbol
+            );
+            anyhow::ensure!(
+                seen.insert((source.index(), m.exchange_symbol.as_str())),
+                "map: {} {} mapped twice",
+                m.source,
+                m.exchange_symbol
+            );
+            anyhow::ensure!(
+                targets.insert((source.index(), m.name.as_str())),
+                "map: two {} symbols mapped to {}",
+                m.source,
+                m.name
+            );
+        }
+        Ok(())
+    }
+
+    /// Apply forced mappings, then bans, to the normalizer output.
+    /// Rescued instruments are taken out of `report.rejections`.
+    pub fn apply(
+        &self,
+        fetched: &FetchResults,
+        normalized: &mut Vec<NormalizedInstrument>,
+        report: &mut NormalizationReport,
+    ) -> Vec<AppliedOverride> {
+        let mut applied = Vec::new();
+
+        for m in &self.map {
+            // Checked on load
+            let Ok(source) = parse_source(&m.source) else {
+                continue;
+            };
+            let Some((base, quote)) = split_name(&m.name) else {
+                continue;
+            };
+            let Some(raw) = fetched
+                .get(source)
+                .and_then(|list| list.iter().find(|r| r.exchange_symbol == m.exchange_symbol))
+            else {
+                warn!(
+                    "override map: {} {} not listed, skipped",
+                    m.source, m.exchange_symbol
+                );
+                continue;
+            };
+
+            // The mapped instrument keeps its place, so the first-wins
+            // choices of build_candidates do not shift for anything else
+            let existing = normalized
+                .iter()
+                .position(|n| n.source == source && n.raw.exchange_symbol == m.exchange_symbol);
+            let from = match existing {
+                Some(i) => Some(normalized.remove(i).name),
+                None => {
+                    let before = report.rejections.len();
+                    report.rejections.retain(|r| {
+                        !(r.source == source && r.exchange_symbol == m.exchange_symbol)
+                    });
+                    if report.rejections.len() < before {
+                        report.accepted[source.index()] += 1;
+                    }
+                    None
+                }
+            };
+            if from.as_deref() == Some(m.name.as_str()) {
+                warn!(
+                    "override map: {} {} already normalizes to {}",
+                    m.source, m.exchange_symbol, m.name
+                );
+            } else {
+                applied.push(AppliedOverride::Mapped {
+                    source,
+                    exchange_symbol: m.exchange_symbol.clone(),
+                    from,
+                    to: m.name.clone(),
+                });
+            }
+            let price_multiplier =
+                m.multiplier
+                    .unwrap_or_else(|| match split_multiplier(&raw.base_asset) {
+                        (unit_base, multiplier) if unit_base == base => multiplier,
+                        _ => 1.0,
+                    });
+            let mapped = NormalizedInstrument {
+                source,
+                name: m.name.clone(),
+                base: base.to_string(),
+                quote: quote.to_string(),
+                price_multiplier,
+                raw: raw.clone(),
+            };
+            match existing {
+                Some(i) => normalized.insert(i, mapped),
+                None => normalized.push(mapped),
+            }
+
+            // Anything else on this source under the target name would win
+            // the (name, source) slot in build_candidates
+            normalized.retain(|n| {
+                let displaced = n.source == source
+                    && n.name == m.name
+                    && n.raw.exchange_symbol != m.exchange_symbol;
+                if displaced {
+                    applied.push(AppliedOverride::Displaced {
+                        source,
+                        exchange_symbol: n.raw.exchange_symbol.clone(),
+                        name: n.name.clone(),
+                        by: m.exchange_symbol.clone(),
+                    });
+                }
+                !displaced
+            });
+        }
+
+        if !self.ban.is_empty() {
+            normalized.retain(|n| {
+                let banned = self.ban.iter().any(|b| {
+                    b.symbol == n.name && b.source.as_deref().is_none_or(|s| s == n.source.name())
+                });
+                if banned {
+                    applied.push(AppliedOverride::Banned {
+                        source: n.source,
+                        exchange_symbol: n.raw.exchange_symbol.clone(),
+                        name: n.name.clone(),
+                    });
+                }
+                !banned
+            });
+        }
+
+        applied
+    }
+
+    /// Clear WS validation failures on pinned symbols, so
+    /// `ValidationReport::apply` keeps them.
+    pub fn protect_pinned(&self, validation: &mut ValidationReport) -> Vec<AppliedOverride> {
+        let mut applied = Vec::new();
+        for sv in &mut validation.sources {
+            let source = sv.source;
+            sv.invalid.retain(|pair| {
+                if !self.pin.contains(&pair.name) {
+                    return true;
+                }
+                applied.push(AppliedOverride::PinnedKept {
+                    source,
+                    exchange_symbol: pair.exchange_symbol.clone(),
+                    name: pair.name.clone(),
+                    reason: pair.reason.to_string(),
+                });
+                false
+            });
+            sv.valid = sv.total - sv.invalid.len();
+        }
+        applied
+    }
+
+    /// Pinned symbols that did not make it into `candidates`.
+    pub fn missing_pins(
+        &self,
+        candidates: &BTreeMap<String, SymbolRecord>,
+    ) -> Vec<AppliedOverride> {
+        self.pin
+            .iter()
+            .filter(|name| !candidates.contains_key(*name))
+            .map(|name| AppliedOverride::PinnedMissing { name: name.clone() })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::normalizer::Normalizer;
+    use crate::rest_client::RawInstrument;
+    use crate::status::InstrumentState;
+    use crate::validator::{InvalidPair, InvalidReason, SourceValidation};
+
+    fn raw(symbol: &str, base: &str, quote: &str) -> RawInstrument {
+        RawInstrument {
+            exchange_symbol: symbol.to_string(),
+            base_asset: base.to_string(),
+            quote_asset: quote.to_string(),
+            status: "TRADING".to_string(),
+            state: InstrumentState::Trading,
+            min_qty: None,
+            tick_size: None,
+            spec: Default::default(),
+        }
+    }
+
+    fn parse(s: &str) -> Overrides {
+        let o: Overrides = toml::from_str(s).unwrap();
+        o.check().unwrap();
+        o
+    }
+
+    #[test]
+    fn test_check_rejects_bad_entries() {
+        let bad = [
+            "pin = [\"btc-usdt\"]",
+            "[[ban]]\nsymbol = \"BTCUSDT\"",
+            "[[ban]]\nsymbol = \"BTC-USDT\"\nsource = \"okx_swap\"",
+            "pin = [\"BTC-USDT\"]\n[[ban]]\nsymbol = \"BTC-USDT\"",
+            "[[map]]\nsource = \"okx_spot\"\nexchange_symbol = \"A-USDT\"\nname = \"B-USDT\"\n\
+             [[map]]\nsource = \"okx_spot\"\nexchange_symbol = \"A-USDT\"\nname = \"C-USDT\"",
+            "[[map]]\nsource = \"okx_spot\"\nexchange_symbol = \"A-USDT\"\nname = \"C-USDT\"\n\
+             [[map]]\nsource = \"okx_spot\"\nexchange_symbol = \"B-USDT\"\nname = \"C-USDT\"",
+            "pin = [\"BTC-USDT-SWAP\"]",
+        ];
+        for s in bad {
+            let o: Overrides = toml::from_str(s).unwrap();
+            assert!(o.check().is_err(), "{}", s);
+        }
+        assert!(toml::from_str::<Overrides>("[[ban]]\nname = \"BTC-USDT\"").is_err());
+        // Banning a pinned symbol on one source is fine
+        parse("pin = [\"BTC-USDT\"]\n[[ban]]\nsymbol = \"BTC-USDT\"\nsource = \"mexc_spot\"");
+    }
+
+    #[test]
+    fn test_apply_map_and_ban() {
+        let mut fetched = FetchResults {
+            instruments: Default::default(),
+        };
+        fetched.instruments[SourceId::BybitSpot.index()] = Some(vec![
+            // A listing ahead of RNDRUSDT already under the mapped name
+            raw("RENDERUSDT", "RENDER", "USDT"),
+            raw("RNDRUSDT", "RNDR", "USDT"),
+            raw("LUNAUSDT", "LUNA", "USDT"),
+            raw("BTCUSDT", "BTC", "USDT"),
+            raw("1000SATSUSDT", "1000SATS", "USDT"),
+        ]);
+        fetched.instruments[SourceId::OkxSpot.index()] = Some(vec![
+            raw("BTC-USDT", "BTC", "USDT"),
+            raw("LUNA-USDT", "LUNA", "USDT"),
+            // Quote misreported by the venue: rejected, then rescued
+            raw("PEPE-USDT", "PEPE", "USD"),
+        ]);
+        let (mut normalized, mut report) =
+            Normalizer::new(&["USDT".to_string()]).normalize_all(&fetched);
+        assert_eq!(report.rejections.len(), 1);
+
+        let overrides = parse(
+            r#"
+            [[ban]]
+            symbol = "LUNA-USDT"
+
+            [[ban]]
+            symbol = "BTC-USDT"
+            source = "bybit_spot"
+
+            [[map]]
+            source = "bybit_spot"
+            exchange_symbol = "RNDRUSDT"
+            name = "RENDER-USDT"
+
+            [[map]]
+            source = "okx_spot"
+            exchange_symbol = "PEPE-USDT"
+            name = "PEPE-USDT"
+
+            [[map]]
+            source = "bybit_spot"
+            exchange_symbol = "1000SATSUSDT"
+            name = "1000SATS-USDT"
+
+            [[map]]
+            source = "okx_spot"
+            exchange_symbol = "GONE-USDT"
+            name = "GONE-USDT"
+            "#,
+        );
+        let order = |normalized: &[NormalizedInstrument]| -> Vec<String> {
+            normalized
+                .iter()
+                .map(|n| n.raw.exchange_symbol.clone())
+                .collect()
+        };
+        let before = order(&normalized);
+        let applied = overrides.apply(&fetched, &mut normalized, &mut report);
+        // Survivors keep their relative order, the mapped one its place
+        let after = order(&normalized);
+        let survivors: Vec<_> = before.iter().filter(|s| after.contains(s)).collect();
+        let previous: Vec<_> = after.iter().filter(|s| before.contains(s)).collect();
+        assert_eq!(survivors, previous);
+        assert_eq!(after[0], "RNDRUSDT");
+
+        let mut names: Vec<(&str, &str)> = normalized
+            .iter()
+            .map(|n| (n.source.name(), n.name.as_str()))
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                ("bybit_spot", "1000SATS-USDT"),
+                ("bybit_spot", "RENDER-USDT"),
+                ("okx_spot", "BTC-USDT"),
+                ("okx_spot", "PEPE-USDT"),
+            ]
+        );
+        assert!(report.rejections.is_empty());
+        assert_eq!(report.accepted[SourceId::OkxSpot.index()], 3);
+
+        assert_eq!(applied.len(), 7);
+        assert!(applied.contains(&AppliedOverride::Displaced {
+            source: SourceId::BybitSpot,
+            exchange_symbol: "RENDERUSDT".to_string(),
+            name: "RENDER-USDT".to_string(),
+            by: "RNDRUSDT".to_string(),
+        }));
+        // Keeping the denomination in the name keeps the price as quoted
+        let sats = normalized.iter().find(|n| n.base == "1000SATS").unwrap();
+        assert_eq!(sats.price_multiplier, 1.0);
+        assert!(applied.contains(&AppliedOverride::Mapped {
+            source: SourceId::BybitSpot,
+            exchange_symbol: "RNDRUSDT".to_string(),
+            from: Some("RNDR-USDT".to_string()),
+            to: "RENDER-USDT".to_string(),
+        }));
+        assert!(applied.contains(&AppliedOverride::Mapped {
+            source: SourceId::OkxSpot,
+            exchange_symbol: "PEPE-USDT".to_string(),
+            from: None,
+            to: "PEPE-USDT".to_string(),
+        }));
+        let bans = applied
+            .iter()
+            .filter(|a| matches!(a, AppliedOverride::Banned { .. }))
+            .count();
+        assert_eq!(bans, 3);
+    }
+
+    #[test]
+    fn test_pins() {
+        let overrides = parse("pin = [\"BTC-USDT\", \"ETH-USDT\"]");
+        let pair = |name: &str| InvalidPair {
+            name: name.to_string(),
+            exchange_symbol: name.replace('-', ""),
+            reason: InvalidReason::NoResponse,
+        };
+        let mut validation = ValidationReport {
+            sources: vec![SourceValidation {
+                source: SourceId::BinanceSpot,
+                total: 3,
+                valid: 1,
+                invalid: vec![pair("BTC-USDT"), pair("SOL-USDT")],
+                batches: 1,
+                failed_batches: 0,
+                mids: BTreeMap::new(),
+            }],
+        };
+        let kept = overrides.protect_pinned(&mut validation);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(
+            kept[0].to_string(),
+            "pin     binance_spot BTCUSDT (BTC-USDT) kept despite: no response"
+        );
+        assert_eq!(validation.sources[0].invalid, vec![pair("SOL-USDT")]);
+        assert_eq!(validation.sources[0].valid, 2);
+
+        let mut candidates = BTreeMap::new();
+        candidates.insert("BTC-USDT".to_string(), SymbolRecord::new(0, "BTC-USDT"));
+        assert_eq!(
+            overrides.missing_pins(&candidates),
+            vec![AppliedOverride::PinnedMissing {
+                name: "ETH-USDT".to_string()
+            }]
+        );
+    }
+}
@@ -0,0 +1,314 @@
+//! Ticker-collision quarantine.
+//!
+//! Exchanges occasionally list unrelated tokens under the same ticker, which
+//! normalize to the same canonical name and would show up as a huge, fake
+//! spread. For every pair of a direction, the per-unit mids seen during WS
+//! validation on its spot and futures source are compared; a ratio above
+//! `spread.collision_max_price_ratio` quarantines the pair, i.e. removes the
+//! symbol from that direction's `DirectionRecord::symbols`. The symbol keeps
+//! its ID and stays in every direction where the prices agree.
+//!
+//! State lives in generated/quarantine.json. A pair is released only after
+//! `discovery.quarantine_release_runs` consecutive runs within the limit; a
+//! run in which either mid is missing (pair invalid, source not validated)
+//! leaves the count unchanged. Pairs that are no longer listed on both
+//! sides are forgotten.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use common::config::AppConfig;
+use common::directions::DirectionRecord;
+use common::symbols::SymbolRecord;
+use common::types::{price_ratio, SourceId};
+
+use crate::generator::write_atomic;
+use crate::validator::ValidationReport;
+
+pub const QUARANTINE_FILE: &str = "quarantine.json";
+
+#[derive(Debug, Clone, Copy)]
+pub struct QuarantinePolicy {
+    pub max_price_ratio: f64,
+    pub release_runs: u32,
+}
+
+impl QuarantinePolicy {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            max_price_ratio: config.spread.collision_max_price_ratio,
+            release_runs: config.discovery.quarantine_release_runs,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuarantineEntry {
+    pub direction_id: u8,
+    pub direction: String,
+    /// Canonical name, e.g. "NEIRO-USDT".
+    pub symbol: String,
+    /// Latest observed ratio of the two per-unit mids (≥ 1).
+    pub ratio: f64,
+    /// Unix seconds of the run that flagged it.
+    pub since: u64,
+    /// Consecutive runs within the limit so far.
+    pub clean_runs: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Quarantine {
+    pub entries: Vec<QuarantineEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuarantineChange {
+    Flagged {
+        direction: String,
+        symbol: String,
+        ratio: f64,
+    },
+    Released {
+        direction: String,
+        symbol: String,
+        ratio: f64,
+    },
+}
+
+impl fmt::Display for QuarantineChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuarantineChange::Flagged {
+                direction,
+                symbol,
+                ratio,
+            } => write!(
+                f,
+                "quarantine {} in {}: price ratio {:.2}",
+                symbol, direction, ratio
+            ),
+            QuarantineChange::Released {
+                direction,
+                symbol,
+                ratio,
+            } => write!(
+                f,
+                "release    {} in {}: price ratio {:.2}",
+                symbol, direction, ratio
+            ),
+        }
+    }
+}
+
+impl Quarantine {
+    /// Load generated/quarantine.json; a missing file means nothing is
+    /// quarantined.
+    pub fn load(generated_dir: &Path) -> Result<Self> {
+        let path = generated_dir.join(QUARANTINE_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, generated_dir: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        write_atomic(&generated_dir.join(QUARANTINE_FILE), content.as_bytes())
+    }
+
+    pub fn is_quarantined(&self, direction_id: u8, symbol: &str) -> bool {
+        self.entries
+            .iter()
+            .any(|e| e.direction_id == direction_id && e.symbol == symbol)
+    }
+
+    /// Next state, given this run's `directions` (before `apply`) and the mids
+    /// observed during validation.
+    pub fn update(
+        &self,
+        directions: &[DirectionRecord],
+        symbols: &[SymbolRecord],
+        validation: &ValidationReport,
+        policy: &QuarantinePolicy,
+        now: u64,
+    ) -> (Quarantine, Vec<QuarantineChange>) {
+        let mut listed = BTreeSet::new();
+        let mut observed = BTreeMap::new();
+        for d in directions {
+            let (Some(spot), Some(futures)) = (
+                SourceId::from_u8(d.spot_source),
+                SourceId::from_u8(d.futures_source),
+            ) else {
+                continue;
+            };
+            for &id in &d.symbols {
+                let name = symbols[id as usize].name.as_str();
+                listed.insert((d.direction_id, name));
+                if let (Some(a), Some(b)) =
+                    (validation.mid(spot, name), validation.mid(futures, name))
+                {
+                    observed.insert((d.direction_id, name), (d, price_ratio(a, b)));
+                }
+            }
+        }
+
+        let mut next = Quarantine::default();
+        let mut changes = Vec::new();
+        for entry in &self.entries {
+            let key = (entry.direction_id, entry.symbol.as_str());
+            if !listed.contains(&key) {
+                continue;
+            }
+            let mut entry = entry.clone();
+            if let Some(&(_, ratio)) = observed.get(&key) {
+                entry.ratio = ratio;
+                if ratio > policy.max_price_ratio {
+                    entry.clean_runs = 0;
+                } else {
+                    entry.clean_runs += 1;
+                }
+            }
+            if entry.clean_runs >= policy.release_runs {
+                changes.push(QuarantineChange::Released {
+                    direction: entry.direction,
+                    symbol: entry.symbol,
+                    ratio: entry.ratio,
+                });
+            } else {
+                next.entries.push(entry);
+            }
+        }
+
+        for (&(direction_id, symbol), &(d, ratio)) in &observed {
+            if ratio <= policy.max_price_ratio || self.is_quarantined(direction_id, symbol) {
+                continue;
+            }
+            changes.push(QuarantineChange::Flagged {
+                direction: d.name.clone(),
+                symbol: symbol.to_string(),
+                ratio,
+            });
+            next.entries.push(QuarantineEntry {
+                direction_id,
+                direction: d.name.clone(),
+                symbol: symbol.to_string(),
+                ratio,
+                since: now,
+                clean_runs: 0,
+            });
+        }
+        next.entries
+            .sort_by(|a, b| (a.direction_id, &a.symbol).cmp(&(b.direction_id, &b.symbol)));
+        (next, changes)
+    }
+
+    /// Remove quarantined pairs from `directions`.
+    pub fn apply(&self, directions: &mut [DirectionRecord], symbols: &[SymbolRecord]) {
+        for d in directions {
+            d.symbols
+                .retain(|&id| !self.is_quarantined(d.direction_id, &symbols[id as usize].name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::validator::SourceValidation;
+
+    fn record(id: u16, name: &str) -> SymbolRecord {
+        SymbolRecord::new(id, name)
+            .with_source(SourceId::BinanceSpot, name.replace('-', ""))
+            .with_source(SourceId::BybitFutures, name.replace('-', ""))
+    }
+
+    fn direction(symbols: Vec<u16>) -> DirectionRecord {
+        DirectionRecord {
+            direction_id: 3,
+            spot_source: SourceId::BinanceSpot as u8,
+            futures_source: SourceId::BybitFutures as u8,
+            name: "binance_spot_bybit_futures".to_string(),
+            symbols,
+        }
+    }
+
+    fn validation(mids: &[(SourceId, &str, f64)]) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        for source in [SourceId::BinanceSpot, SourceId::BybitFutures] {
+            report.sources.push(SourceValidation {
+                source,
+                total: 0,
+                valid: 0,
+                invalid: Vec::new(),
+                batches: 1,
+                failed_batches: 0,
+                mids: mids
+                    .iter()
+                    .filter(|(s, _, _)| *s == source)
+                    .map(|(_, name, mid)| (name.to_string(), *mid))
+                    .collect(),
+            });
+        }
+        report
+    }
+
+    #[test]
+    fn test_flag_apply_and_release() {
+        let symbols = vec![record(0, "BTC-USDT"), record(1, "NEIRO-USDT")];
+        let directions = vec![direction(vec![0, 1])];
+        let policy = QuarantinePolicy {
+            max_price_ratio: 1.5,
+            release_runs: 2,
+        };
+        let spot = SourceId::BinanceSpot;
+        let fut = SourceId::BybitFutures;
+
+        // Two different NEIRO tokens: 0.0004 vs 0.08
+        let colliding = validation(&[
+            (spot, "BTC-USDT", 60000.0),
+            (fut, "BTC-USDT", 60010.0),
+            (spot, "NEIRO-USDT", 0.0004),
+            (fut, "NEIRO-USDT", 0.08),
+        ]);
+        let (q, changes) =
+            Quarantine::default().update(&directions, &symbols, &colliding, &policy, 100);
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(
+            &changes[0],
+            QuarantineChange::Flagged { symbol, ratio, .. } if symbol == "NEIRO-USDT" && *ratio == 200.0
+        ));
+        let mut applied = directions.clone();
+        q.apply(&mut applied, &symbols);
+        assert_eq!(applied[0].symbols, vec![0]);
+
+        // No observation: unchanged. Then one clean run is not enough.
+        let (q, changes) = q.update(&directions, &symbols, &validation(&[]), &policy, 200);
+        assert!(changes.is_empty());
+        assert_eq!(q.entries[0].clean_runs, 0);
+        let agreeing = validation(&[(spot, "NEIRO-USDT", 0.08), (fut, "NEIRO-USDT", 0.081)]);
+        let (q, changes) = q.update(&directions, &symbols, &agreeing, &policy, 300);
+        assert!(changes.is_empty());
+        assert_eq!((q.entries[0].clean_runs, q.entries[0].since), (1, 100));
+
+        // A relapse resets the count
+        let (q, _) = q.update(&directions, &symbols, &colliding, &policy, 400);
+        assert_eq!(q.entries[0].clean_runs, 0);
+        let (q, _) = q.update(&directions, &symbols, &agreeing, &policy, 500);
+        let (q, changes) = q.update(&directions, &symbols, &agreeing, &policy, 600);
+        assert!(q.entries.is_empty());
+        assert!(matches!(&changes[0], QuarantineChange::Released { .. }));
+
+        // Delisted pairs are forgotten
+        let (q, _) = Quarantine::default().update(&directions, &symbols, &colliding, &policy, 0);
+        let (q, changes) = q.update(&[direction(vec![0])], &symbols, &colliding, &policy, 1);
+        assert!(q.entries.is_empty() && changes.is_empty());
+    }
+}
@@ -0,0 +1,606 @@
+//! Symbol registry — stable, append-only symbol_id assignment.
+//!
+//! A symbol_id indexes PriceStore slots, UpdateBitmap bits and tracker files,
+//! so once assigned it never moves:
+//!   - a symbol seen in the previous generation keeps its ID (matched by name)
+//!   - a symbol that disappears is tombstoned: its record stays in symbols.bin
+//!     with every source name cleared, so SymbolTable stays dense and old IDs
+//!     still resolve to a name
+//!   - a tombstoned ID is handed to a new symbol only after the grace period
+//!     (lowest ID first); otherwise new symbols are appended, up to MAX_SYMBOLS
+//!
+//! Tombstone timestamps are kept in generated/tombstones.json, written
+//! atomically like the rest of generated/ so a crash cannot leave a file the
+//! next run fails to load.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use common::config::{AppConfig, DirectionsConfig};
+use common::symbols::{rate_symbol, split_name, SymbolRecord};
+use common::types::MAX_SYMBOLS;
+
+use crate::generator::write_atomic;
+use crate::normalizer::NormalizedInstrument;
+
+pub const TOMBSTONES_FILE: &str = "tombstones.json";
+
+/// A retired symbol_id waiting out its grace period.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tombstone {
+    pub symbol_id: u16,
+    pub name: String,
+    /// Unix seconds when the symbol disappeared.
+    pub since: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Tombstones {
+    pub tombstones: Vec<Tombstone>,
+}
+
+impl Tombstones {
+    /// Load generated/tombstones.json; a missing file means no tombstones.
+    pub fn load(generated_dir: &Path) -> Result<Self> {
+        let path = generated_dir.join(TOMBSTONES_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, generated_dir: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        write_atomic(&generated_dir.join(TOMBSTONES_FILE), content.as_bytes())
+    }
+}
+
+/// Result of assigning IDs for this generation.
+#[derive(Debug, Default)]
+pub struct Assignment {
+    /// Dense records, `records[i].symbol_id == i`, tombstoned IDs included.
+    pub records: Vec<SymbolRecord>,
+    pub tombstones: Tombstones,
+    /// New symbols and the ID they received.
+    pub added: Vec<(u16, String)>,
+    /// Symbols tombstoned in this run.
+    pub removed: Vec<(u16, String)>,
+    /// Tombstoned symbols that came back under their old ID.
+    pub restored: Vec<(u16, String)>,
+    /// New symbols that found no free ID below MAX_SYMBOLS.
+    pub dropped: Vec<String>,
+}
+
+impl Assignment {
+    pub fn num_active(&self) -> usize {
+        self.records.iter().filter(|r| is_active(r)).count()
+    }
+}
+
+/// A record is active if at least one source still lists it.
+pub fn is_active(record: &SymbolRecord) -> bool {
+    record.source_names.iter().any(|n| n.is_some())
+}
+
+/// What, besides a configured direction, keeps a candidate in the universe.
+#[derive(Debug, Clone, Default)]
+pub struct Pairing {
+    /// A listing also pairs with the same base under another quote
+    /// (`spread.cross_quote`).
+    pub cross_quote: bool,
+    /// Stablecoin rates ("USDC-USDT"), kept wherever listed so the engine
+    /// can convert between quotes.
+    pub rate_symbols: BTreeSet<String>,
+}
+
+impl Pairing {
+    pub fn from_config(config: &AppConfig) -> Self {
+        let quotes = &config.discovery.quote_filter;
+        Self {
+            cross_quote: config.spread.cross_quote,
+            rate_symbols: match quotes.split_first() {
+                Some((reference, others)) => {
+                    others.iter().map(|q| rate_symbol(q, reference)).collect()
+                }
+                None => BTreeSet::new(),
+            },
+        }
+    }
+}
+
+/// Group normalized instruments by canonical name, keeping only symbols that
+/// appear on both sides of at least one configured direction (see
+/// `retain_paired`). Records carry symbol_id 0 until `assign_ids`.
+pub fn build_candidates(
+    normalized: &[NormalizedInstrument],
+    directions: &DirectionsConfig,
+    pairing: &Pairing,
+) -> BTreeMap<String, SymbolRecord> {
+    let mut candidates: BTreeMap<String, SymbolRecord> = BTreeMap::new();
+    for n in normalized {
+        let record = candidates
+            .entry(n.name.clone())
+            .or_insert_with(|| SymbolRecord::new(0, n.name.clone()));
+        let idx = n.source.index();
+        if record.source_names[idx].is_none() {
+            record.source_names[idx] = Some(n.raw.exchange_symbol.clone());
+            record.min_qty[idx] = n.raw.min_qty;
+            record.tick_size[idx] = n.raw.tick_size;
+            record.price_multiplier[idx] = n.price_multiplier;
+            record.specs[idx] = n.raw.spec.clone();
+        }
+    }
+
+    retain_paired(&mut candidates, directions, pairing);
+    candidates
+}
+
+/// Drop candidates that no longer sit on both sides of any direction. Within
+/// one quote the record itself must cover both sides; with
+/// `Pairing::cross_quote`, any record of the same base may cover the other.
+/// Rate symbols are always kept.
+pub fn retain_paired(
+    candidates: &mut BTreeMap<String, SymbolRecord>,
+    directions: &DirectionsConfig,
+    pairing: &Pairing,
+) {
+    let listed = |record: &SymbolRecord| -> u16 {
+        record
+            .source_names
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| n.is_some())
+            .fold(0, |mask, (i, _)| mask | 1 << i)
+    };
+    let base = |name: &str| split_name(name).map_or(name, |(base, _)| base).to_string();
+    let mut by_base: HashMap<String, u16> = HashMap::new();
+    for (name, record) in candidates.iter() {
+        *by_base.entry(base(name)).or_insert(0) |= listed(record);
+    }
+
+    candidates.retain(|name, record| {
+        if pairing.rate_symbols.contains(name) {
+            return true;
+        }
+        let own = listed(record);
+        let other = if pairing.cross_quote {
+            by_base[&base(name)]
+        } else {
+            own
+        };
+        directions.direction.iter().any(|d| {
+            let (spot, futures) = (1 << d.spot_source, 1 << d.futures_source);
+            (own & spot != 0 && other & futures != 0) || (own & futures != 0 && other & spot != 0)
+        })
+    });
+}
+
+/// Assign symbol_ids for `candidates` on top of the previous generation.
+///
+/// `previous` must be dense (as loaded by SymbolTable). `now` and
+/// `grace_secs` are in seconds.
+pub fn assign_ids(
+    previous: &[SymbolRecord],
+    previous_tombstones: &Tombstones,
+    mut candidates: BTreeMap<String, SymbolRecord>,
+    now: u64,
+    grace_secs: u64,
+) -> Result<Assignment> {
+    for (i, record) in previous.iter().enumerate() {
+        anyhow::ensure!(
+            record.symbol_id as usize == i,
+            "previous symbols.bin is not dense: record {} has symbol_id {}",
+            i,
+            record.symbol_id
+        );
+    }
+
+    let mut since_by_id: HashMap<u16, u64> = previous_tombstones
+        .tombstones
+        .iter()
+        .map(|t| (t.symbol_id, t.since))
+        .collect();
+
+    let mut out = Assignment {
+        records: previous.to_vec(),
+        ..Default::default()
+    };
+
+    // 1. Existing IDs: refresh, restore or tombstone.
+    for record in &mut out.records {
+        let id = record.symbol_id;
+        match candidates.remove(&record.name) {
+            Some(candidate) => {
+                // Also drops a stale tombstone left for a record that stayed
+                // active, e.g. by a run that failed after saving tombstones
+                since_by_id.remove(&id);
+                if !is_active(record) {
+                    out.restored.push((id, record.name.clone()));
+                }
+                *record = SymbolRecord {
+                    symbol_id: id,
+                    ..candidate
+                };
+            }
+            None if is_active(record) => {
+                *record = SymbolRecord::new(id, std::mem::take(&mut record.name));
+                since_by_id.insert(id, now);
+                out.removed.push((id, record.name.clone()));
+            }
+            None => {
+                // Still gone. A tombstone without a timestamp starts its grace now.
+                since_by_id.entry(id).or_insert(now);
+            }
+        }
+    }
+
+    // Only a retired record can be reused, whatever tombstones.json says.
+    since_by_id.retain(|&id, _| out.records.get(id as usize).is_some_and(|r| !is_active(r)));
+
+    // 2. New symbols: expired tombstones first (lowest ID), then append.
+    let mut free: Vec<u16> = since_by_id
+        .iter()
+        .filter(|(_, &since)| now.saturating_sub(since) >= grace_secs)
+        .map(|(&id, _)| id)
+        .collect();
+    free.sort_unstable_by(|a, b| b.cmp(a));
+
+    for (name, candidate) in candidates {
+        let id = match free.pop() {
+            Some(id) => {
+                since_by_id.remove(&id);
+                id
+            }
+            None if out.records.len() < MAX_SYMBOLS as usize => {
+                let id = out.records.len() as u16;
+                out.records.push(SymbolRecord::new(id, String::new()));
+                id
+            }
+            None => {
+                out.dropped.push(name);
+                continue;
+            }
+        };
+        out.records[id as usize] = SymbolRecord {
+            symbol_id: id,
+            ..candidate
+        };
+        out.added.push((id, name));
+    }
+
+    if !out.dropped.is_empty() {
+        warn!(
+            "Reached MAX_SYMBOLS={}, {} new symbols dropped",
+            MAX_SYMBOLS,
+            out.dropped.len()
+        );
+    }
+
+    let mut tombstones: Vec<Tombstone> = since_by_id
+        .into_iter()
+        .map(|(symbol_id, since)| Tombstone {
+            symbol_id,
+            name: out.records[symbol_id as usize].name.clone(),
+            since,
+        })
+        .collect();
+    tombstones.sort_by_key(|t| t.symbol_id);
+    out.tombstones = Tombstones { tombstones };
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::types::SourceId;
+
+    const HOUR: u64 = 3600;
+
+    fn candidate(name: &str) -> SymbolRecord {
+        SymbolRecord::new(0, name)
+            .with_source(SourceId::OkxSpot, name)
+            .with_source(SourceId::MexcFutures, name.replace('-', "_"))
+    }
+
+    fn candidates(names: &[&str]) -> BTreeMap<String, SymbolRecord> {
+        names
+            .iter()
+            .map(|n| (n.to_string(), candidate(n)))
+            .collect()
+    }
+
+    fn ids(a: &Assignment) -> Vec<(u16, &str, bool)> {
+        a.records
+            .iter()
+            .map(|r| (r.symbol_id, r.name.as_str(), is_active(r)))
+            .collect()
+    }
+
+    #[test]
+    fn test_first_run_is_sorted() {
+        let a = assign_ids(
+            &[],
+            &Tombstones::default(),
+            candidates(&["ETH-USDT", "BTC-USDT", "SOL-USDT"]),
+            0,
+            HOUR,
+        )
+        .unwrap();
+        assert_eq!(
+            ids(&a),
+            vec![
+                (0, "BTC-USDT", true),
+                (1, "ETH-USDT", true),
+                (2, "SOL-USDT", true)
+            ]
+        );
+        assert_eq!(a.added.len(), 3);
+    }
+
+    #[test]
+    fn test_new_listing_does_not_shift_ids() {
+        let first = assign_ids(
+            &[],
+            &Tombstones::default(),
+            candidates(&["BTC-USDT", "ETH-USDT"]),
+            0,
+            HOUR,
+        )
+        .unwrap();
+        // "ADA" sorts first but must not take ID 0.
+        let second = assign_ids(
+            &first.records,
+            &first.tombstones,
+            candidates(&["ADA-USDT", "BTC-USDT", "ETH-USDT"]),
+            10,
+            HOUR,
+        )
+        .unwrap();
+        assert_eq!(
+            ids(&second),
+            vec![
+                (0, "BTC-USDT", true),
+                (1, "ETH-USDT", true),
+                (2, "ADA-USDT", true)
+            ]
+        );
+        assert_eq!(second.added, vec![(2, "ADA-USDT".to_string())]);
+    }
+
+    #[test]
+    fn test_tombstone_grace_and_reuse() {
+        let first = assign_ids(
+            &[],
+            &Tombstones::default(),
+            candidates(&["BTC-USDT", "ETH-USDT", "LUNA-USDT"]),
+            0,
+            HOUR,
+        )
+        .unwrap();
+
+        // LUNA delisted: ID 2 tombstoned, record kept with no sources.
+        let second = assign_ids(
+            &first.records,
+            &first.tombstones,
+            candidates(&["BTC-USDT", "ETH-USDT"]),
+            100,
+            HOUR,
+        )
+        .unwrap();
+        assert_eq!(second.removed, vec![(2, "LUNA-USDT".to_string())]);
+        assert_eq!(second.records[2].name, "LUNA-USDT");
+        assert!(!is_active(&second.records[2]));
+        assert_eq!(second.tombstones.tombstones[0].since, 100);
+
+        // Within grace: the new symbol is appended, ID 2 stays retired.
+        let third = assign_ids(
+            &second.records,
+            &second.tombstones,
+            candidates(&["BTC-USDT", "ETH-USDT", "SOL-USDT"]),
+            100 + HOUR - 1,
+            HOUR,
+        )
+        .unwrap();
+        assert_eq!(third.added, vec![(3, "SOL-USDT".to_string())]);
+        assert_eq!(third.tombstones.tombstones.len(), 1);
+
+        // After grace: ID 2 is reused.
+        let fourth = assign_ids(
+            &third.records,
+            &third.tombstones,
+            candidates(&["ADA-USDT", "BTC-USDT", "ETH-USDT", "SOL-USDT"]),
+            100 + HOUR,
+            HOUR,
+        )
+        .unwrap();
+        assert_eq!(fourth.added, vec![(2, "ADA-USDT".to_string())]);
+        assert_eq!(fourth.records.len(), 4);
+        assert!(fourth.tombstones.tombstones.is_empty());
+    }
+
+    #[test]
+    fn test_stale_tombstone_for_active_symbol_is_dropped() {
+        let first = assign_ids(
+            &[],
+            &Tombstones::default(),
+            candidates(&["BTC-USDT", "ETH-USDT"]),
+            0,
+            HOUR,
+        )
+        .unwrap();
+        // Left behind by a run that saved tombstones.json but never
+        // published: BTC is still listed, and so is ETH under ID 1. ID 7
+        // does not exist at all.
+        let stale = Tombstones {
+            tombstones: [(0, "BTC-USDT"), (1, "ETH-USDT"), (7, "GONE-USDT")]
+                .into_iter()
+                .map(|(symbol_id, name)| Tombstone {
+                    symbol_id,
+                    name: name.to_string(),
+                    since: 0,
+                })
+                .collect(),
+        };
+        let second = assign_ids(
+            &first.records,
+            &stale,
+            candidates(&["ADA-USDT", "BTC-USDT", "ETH-USDT"]),
+            5 * HOUR,
+            HOUR,
+        )
+        .unwrap();
+        assert_eq!(
+            ids(&second),
+            vec![
+                (0, "BTC-USDT", true),
+                (1, "ETH-USDT", true),
+                (2, "ADA-USDT", true)
+            ]
+        );
+        assert!(second.restored.is_empty());
+        assert!(second.tombstones.tombstones.is_empty());
+    }
+
+    #[test]
+    fn test_relisted_symbol_gets_old_id() {
+        let first = assign_ids(
+            &[],
+            &Tombstones::default(),
+            candidates(&["BTC-USDT", "ETH-USDT"]),
+            0,
+            HOUR,
+        )
+        .unwrap();
+        let second = assign_ids(
+            &first.records,
+            &first.tombstones,
+            candidates(&["ETH-USDT"]),
+            10,
+            HOUR,
+        )
+        .unwrap();
+        // Back after the grace period, ID 0 was never reused.
+        let third = assign_ids(
+            &second.records,
+            &second.tombstones,
+            candidates(&["BTC-USDT", "ETH-USDT"]),
+            10 + 5 * HOUR,
+            HOUR,
+        )
+        .unwrap();
+        assert_eq!(third.restored, vec![(0, "BTC-USDT".to_string())]);
+        assert!(third.added.is_empty());
+        assert!(third.tombstones.tombstones.is_empty());
+    }
+
+    #[test]
+    fn test_max_symbols_cap() {
+        let names: Vec<String> = (0..MAX_SYMBOLS as usize + 5)
+            .map(|i| format!("S{:05}-USDT", i))
+            .collect();
+        let all: BTreeMap<String, SymbolRecord> =
+            names.iter().map(|n| (n.clone(), candidate(n))).collect();
+        let a = assign_ids(&[], &Tombstones::default(), all, 0, HOUR).unwrap();
+        assert_eq!(a.records.len(), MAX_SYMBOLS as usize);
+        assert_eq!(a.dropped.len(), 5);
+    }
+
+    #[test]
+    fn test_build_candidates_requires_direction() {
+        use crate::rest_client::RawInstrument;
+        use crate::status::InstrumentState;
+        use common::config::DirectionConfigEntry;
+
+        let inst = |source: SourceId, symbol: &str, base: &str| NormalizedInstrument {
+            source,
+            name: format!("{}-USDT", base),
+            base: base.to_string(),
+            quote: "USDT".to_string(),
+            price_multiplier: 1.0,
+            raw: RawInstrument {
+                exchange_symbol: symbol.to_string(),
+                base_asset: base.to_string(),
+                quote_asset: "USDT".to_string(),
+                status: "TRADING".to_string(),
+                state: InstrumentState::Trading,
+                min_qty: Some(0.001),
+                tick_size: Some(0.1),
+                spec: Default::default(),
+            },
+        };
+        let normalized = vec![
+            inst(SourceId::OkxSpot, "BTC-USDT", "BTC"),
+            inst(SourceId::MexcFutures, "BTC_USDT", "BTC"),
+            // Spot only: no direction pairs it with a futures source.
+            inst(SourceId::OkxSpot, "ETH-USDT", "ETH"),
+        ];
+        let directions = DirectionsConfig {
+            direction: vec![DirectionConfigEntry {
+                id: 0,
+                spot_source: SourceId::OkxSpot as u8,
+                futures_source: SourceId::MexcFutures as u8,
+                name: "okx_spot_mexc_futures".to_string(),
+            }],
+        };
+
+        let c = build_candidates(&normalized, &directions, &Pairing::default());
+        assert_eq!(c.keys().collect::<Vec<_>>(), vec!["BTC-USDT"]);
+        let btc = &c["BTC-USDT"];
+        assert_eq!(
+            btc.source_names[SourceId::MexcFutures.index()].as_deref(),
+            Some("BTC_USDT")
+        );
+        assert_eq!(btc.min_qty[SourceId::OkxSpot.index()], Some(0.001));
+    }
+
+    #[test]
+    fn test_pairing_across_quotes() {
+        use common::config::DirectionConfigEntry;
+
+        let record = |name: &str, sources: &[SourceId]| {
+            let r = sources.iter().fold(SymbolRecord::new(0, name), |r, &s| {
+                r.with_source(s, name.replace('-', ""))
+            });
+            (name.to_string(), r)
+        };
+        let candidates = BTreeMap::from([
+            record("BTC-USDT", &[SourceId::OkxSpot, SourceId::BybitFutures]),
+            // USDC spot only, USDT perp only: pair across quotes
+            record("SOL-USDC", &[SourceId::OkxSpot]),
+            record("SOL-USDT", &[SourceId::BybitFutures]),
+            // Spot-only rate
+            record("USDC-USDT", &[SourceId::OkxSpot]),
+        ]);
+        let directions = DirectionsConfig {
+            direction: vec![DirectionConfigEntry {
+                id: 1,
+                spot_source: SourceId::OkxSpot as u8,
+                futures_source: SourceId::BybitFutures as u8,
+                name: "okx_spot_bybit_futures".to_string(),
+            }],
+        };
+        let names = |pairing: &Pairing| {
+            let mut c = candidates.clone();
+            retain_paired(&mut c, &directions, pairing);
+            c.into_keys().collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(&Pairing::default()), vec!["BTC-USDT"]);
+        let pairing = Pairing {
+            cross_quote: true,
+            rate_symbols: BTreeSet::from(["USDC-USDT".to_string()]),
+        };
+        assert_eq!(
+            names(&pairing),
+            vec!["BTC-USDT", "SOL-USDC", "SOL-USDT", "USDC-USDT"]
+        );
+    }
+}
@@ -0,0 +1,1388 @@
+//! REST instrument fetchers — one request (or page loop) per source.
+//!
+//! URLs come from config/exchanges.toml (`rest_spot`/`rest_futures` +
+//! `instruments_path_*`), so tests can point every source at a local server.
+//! Each source is retried with exponential backoff; discovery continues as
+//! long as at least MIN_SOURCES of the 8 sources answer.
+//!
+//! Futures sources keep only linear perpetuals; dated and inverse contracts
+//! are dropped before their specs are built, so every published
+//! `contract_type` is `Spot` or `LinearPerpetual`.
+//!
+//! 24h ticker statistics (`ticker_path_*`) are fetched the same way for the
+//! liquidity filter. They are best effort: a source whose tickers fail is
+//! simply not filtered.
+//!
+//! With a `Capture` every body is also saved (recording) or read from the
+//! capture instead of the network (replay); see `capture`.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use common::config::{ExchangeEntry, ExchangesConfig};
+use common::symbols::{ContractType, InstrumentSpec};
+use common::types::{SourceId, NUM_SOURCES};
+
+use crate::capture::{rest_key, Capture};
+use crate::error::DiscoveryError;
+use crate::status::InstrumentState;
+
+/// Minimum number of sources that must answer for discovery to proceed.
+pub const MIN_SOURCES: usize = 6;
+
+/// Safety cap on cursor pagination (Bybit linear is ~2 pages today).
+const MAX_PAGES: usize = 20;
+
+/// A tradable instrument as reported by one source, before normalization.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawInstrument {
+    /// Symbol exactly as the exchange spells it: "BTCUSDT", "BTC-USDT-SWAP", "BTC_USDT".
+    pub exchange_symbol: String,
+    pub base_asset: String,
+    pub quote_asset: String,
+    /// Status exactly as the exchange spells it.
+    pub status: String,
+    pub state: InstrumentState,
+    pub min_qty: Option<f64>,
+    pub tick_size: Option<f64>,
+    pub spec: InstrumentSpec,
+}
+
+/// 24h statistics of one instrument, as reported by its source's ticker
+/// endpoint. Fields the endpoint does not carry are `None` (Binance futures
+/// has no book prices in its 24h ticker).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Ticker24h {
+    /// Traded volume over the last 24h, in quote currency.
+    pub quote_volume: Option<f64>,
+    pub best_bid: Option<f64>,
+    pub best_ask: Option<f64>,
+}
+
+impl Ticker24h {
+    /// Bid/ask width in % of mid; `None` without a two-sided book.
+    pub fn book_width_pct(&self) -> Option<f64> {
+        match (self.best_bid, self.best_ask) {
+            (Some(bid), Some(ask)) if bid > 0.0 && ask > 0.0 => {
+                Some((ask - bid) / ((ask + bid) / 2.0) * 100.0)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Tickers of one source, keyed by exchange symbol.
+pub type Tickers = HashMap<String, Ticker24h>;
+
+/// Retry schedule for a single source: base_delay * 2^attempt between tries.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub request_timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(100),
+            request_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.base_delay * 2u32.saturating_pow(attempt)
+    }
+}
+
+/// Per-source REST results. `None` means the source failed after all retries.
+#[derive(Debug)]
+pub struct FetchResults {
+    pub instruments: [Option<Vec<RawInstrument>>; NUM_SOURCES as usize],
+}
+
+impl FetchResults {
+    pub fn successful(&self) -> usize {
+        self.instruments.iter().filter(|i| i.is_some()).count()
+    }
+
+    pub fn get(&self, source: SourceId) -> Option<&[RawInstrument]> {
+        self.instruments[source.index()].as_deref()
+    }
+}
+
+/// Per-source 24h tickers. `None` means the source's ticker fetch failed.
+#[derive(Debug)]
+pub struct TickerResults {
+    pub tickers: [Option<Tickers>; NUM_SOURCES as usize],
+}
+
+impl TickerResults {
+    pub fn get(&self, source: SourceId) -> Option<&Tickers> {
+        self.tickers[source.index()].as_ref()
+    }
+}
+
+/// One parsed page of instruments plus the cursor for the next page, if any.
+struct Page {
+    instruments: Vec<RawInstrument>,
+    next_cursor: Option<String>,
+}
+
+pub struct RestClient {
+    http: reqwest::Client,
+    retry: RetryPolicy,
+    capture: Capture,
+}
+
+impl RestClient {
+    pub fn new(retry: RetryPolicy) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(retry.request_timeout)
+            .build()
+            .context("failed to build HTTP client")?;
+        Ok(Self {
+            http,
+            retry,
+            capture: Capture::Live,
+        })
+    }
+
+    pub fn with_capture(mut self, capture: Capture) -> Self {
+        self.capture = capture;
+        self
+    }
+
+    /// Fetch all 8 sources in parallel.
+    /// Fails with `DiscoveryError::InsufficientSources` if fewer than MIN_SOURCES answer.
+    pub async fn fetch_all(&self, exchanges: &ExchangesConfig) -> Result<FetchResults> {
+        let fetches = SourceId::ALL.map(|source| async move {
+            let result = match exchanges.entry(source) {
+                Some(entry) => self.fetch_source(source, entry).await,
+                None => Err(anyhow::anyhow!(
+                    "exchange {} missing from exchanges config",
+                    source.exchange()
+                )),
+            };
+            match result {
+                Ok(instruments) => {
+                    info!("{}: {} instruments", source.name(), instruments.len());
+                    Some(instruments)
+                }
+                Err(e) => {
+                    warn!("{}: REST fetch failed: {:#}", source.name(), e);
+                    None
+                }
+            }
+        });
+
+        let results = futures_util::future::join_all(fetches).await;
+        let mut instruments: [Option<Vec<RawInstrument>>; NUM_SOURCES as usize] =
+            std::array::from_fn(|_| None);
+        for (slot, result) in instruments.iter_mut().zip(results) {
+            *slot = result;
+        }
+
+        let fetched = FetchResults { instruments };
+        let successful = fetched.successful();
+        if successful < MIN_SOURCES {
+            return Err(DiscoveryError::InsufficientSources {
+                successful,
+                required: MIN_SOURCES,
+            }
+            .into());
+        }
+        if successful < NUM_SOURCES as usize {
+            warn!(
+                "Running with degraded universe: {} sources unavailable",
+                NUM_SOURCES as usize - successful
+            );
+        }
+        Ok(fetched)
+    }
+
+    /// Fetch every tradable instrument of one source, following pagination.
+    pub async fn fetch_source(
+        &self,
+        source: SourceId,
+        entry: &ExchangeEntry,
+    ) -> Result<Vec<RawInstrument>> {
+        let base_url = entry.instruments_url(source);
+        let mut instruments = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        for page in 0..MAX_PAGES {
+            let url = page_url(source, &base_url, cursor.as_deref());
+            let body = self.get(&rest_key(source, Some(page)), &url).await?;
+            let page = parse_instruments(source, &body)
+                .with_context(|| format!("failed to parse {}", url))?;
+            instruments.extend(page.instruments);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return Ok(instruments),
+            }
+        }
+
+        anyhow::bail!("{}: more than {} pages", source.name(), MAX_PAGES)
+    }
+
+    /// Fetch the 24h tickers of all 8 sources in parallel. Never fails as a
+    /// whole; a failed source is logged and left `None`.
+    pub async fn fetch_tickers(&self, exchanges: &ExchangesConfig) -> TickerResults {
+        let fetches = SourceId::ALL.map(|source| async move {
+            let result = match exchanges.entry(source) {
+                Some(entry) => self.fetch_source_tickers(source, entry).await,
+                None => Err(anyhow::anyhow!(
+                    "exchange {} missing from exchanges config",
+                    source.exchange()
+                )),
+            };
+            match result {
+                Ok(tickers) => Some(tickers),
+                Err(e) => {
+                    warn!(
+                        "{}: ticker fetch failed, no liquidity filter: {:#}",
+                        source.name(),
+                        e
+                    );
+                    None
+                }
+            }
+        });
+
+        let results = futures_util::future::join_all(fetches).await;
+        let mut tickers: [Option<Tickers>; NUM_SOURCES as usize] = std::array::from_fn(|_| None);
+        for (slot, result) in tickers.iter_mut().zip(results) {
+            *slot = result;
+        }
+        TickerResults { tickers }
+    }
+
+    /// Fetch the 24h tickers of one source (a single request everywhere).
+    pub async fn fetch_source_tickers(
+        &self,
+        source: SourceId,
+        entry: &ExchangeEntry,
+    ) -> Result<Tickers> {
+        let url = entry.ticker_url(source);
+        let body = self.get(&rest_key(source, None), &url).await?;
+        parse_tickers(source, &body).with_context(|| format!("failed to parse {}", url))
+    }
+
+    /// `url` through the capture: read from it on replay, saved to it while
+    /// recording. A recording that cannot be written does not fail the run.
+    async fn get(&self, key: &str, url: &str) -> Result<String> {
+        if self.capture.is_replay() {
+            return self.capture.load_rest(key);
+        }
+        let body = self.get_with_retry(url).await?;
+        if let Err(e) = self.capture.save_rest(key, &body) {
+            warn!("{:#}", e);
+        }
+        Ok(body)
+    }
+
+    async fn get_with_retry(&self, url: &str) -> Result<String> {
+        let mut attempt = 0;
+        loop {
+            match self.get_once(url).await {
+                Ok(body) => return Ok(body),
+                Err(e) if attempt < self.retry.max_retries => {
+                    let backoff = self.retry.backoff(attempt);
+                    warn!(
+                        "GET {} failed ({:#}), retry {}/{} after {:?}",
+                        url,
+                        e,
+                        attempt + 1,
+                        self.retry.max_retries,
+                        backoff
+                    );
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| {
+                        format!(
+                            "GET {} failed after {} retries",
+                            url, self.retry.max_retries
+                        )
+                    })
+                }
+            }
+        }
+    }
+
+    async fn get_once(&self, url: &str) -> Result<String> {
+        let response = self.http.get(url).send().await?.error_for_status()?;
+        Ok(response.text().await?)
+    }
+}
+
+/// Bybit pages via `cursor`; every other source answers in one response.
+fn page_url(source: SourceId, base_url: &str, cursor: Option<&str>) -> String {
+    match source {
+        SourceId::BybitSpot | SourceId::BybitFutures => {
+            let sep = if base_url.contains('?') { '&' } else { '?' };
+            match cursor {
+                Some(c) => format!("{}{}limit=1000&cursor={}", base_url, sep, c),
+                None => format!("{}{}limit=1000", base_url, sep),
+            }
+        }
+        _ => base_url.to_string(),
+    }
+}
+
+/// Parse one response body for `source`, keeping only tradable perpetual/spot instruments.
+fn parse_instruments(source: SourceId, body: &str) -> Result<Page> {
+    let instruments = match source {
+        SourceId::BinanceSpot | SourceId::BinanceFutures => parse_binance(source, body)?,
+        SourceId::BybitSpot | SourceId::BybitFutures => return parse_bybit(source, body),
+        SourceId::MexcSpot => parse_mexc_spot(body)?,
+        SourceId::MexcFutures => parse_mexc_futures(body)?,
+        SourceId::OkxSpot | SourceId::OkxFutures => parse_okx(source, body)?,
+    };
+    Ok(Page {
+        instruments,
+        next_cursor: None,
+    })
+}
+
+fn parse_decimal(s: Option<&str>) -> Option<f64> {
+    s.filter(|s| !s.is_empty()).and_then(|s| s.parse().ok())
+}
+
+// === Binance ===
+
+#[derive(Deserialize)]
+struct BinanceExchangeInfo {
+    symbols: Vec<BinanceSymbol>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceSymbol {
+    symbol: String,
+    status: String,
+    base_asset: String,
+    quote_asset: String,
+    #[serde(default)]
+    contract_type: Option<String>,
+    #[serde(default)]
+    margin_asset: Option<String>,
+    #[serde(default)]
+    onboard_date: Option<u64>,
+    #[serde(default)]
+    delivery_date: Option<u64>,
+    #[serde(default)]
+    filters: Vec<BinanceFilter>,
+}
+
+/// `deliveryDate` of a perpetual without a scheduled delisting (2100-12-25).
+const BINANCE_NO_DELIVERY: u64 = 4_133_404_800_000;
+
+/// `None` for listings that are gone (CLOSE, END_OF_DAY, DELIVERED, ...).
+fn binance_state(status: &str) -> Option<InstrumentState> {
+    match status {
+        "TRADING" => Some(InstrumentState::Trading),
+        "BREAK" | "HALT" | "AUCTION_MATCH" => Some(InstrumentState::Halted),
+        "PRE_TRADING" | "PENDING_TRADING" => Some(InstrumentState::PreLaunch),
+        "PRE_DELIVERING" | "DELIVERING" | "PRE_SETTLE" | "SETTLING" => {
+            Some(InstrumentState::Delivering)
+        }
+        _ => None,
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceFilter {
+    filter_type: String,
+    #[serde(default)]
+    tick_size: Option<String>,
+    #[serde(default)]
+    min_qty: Option<String>,
+    #[serde(default)]
+    max_qty: Option<String>,
+    #[serde(default)]
+    step_size: Option<String>,
+    /// Spot NOTIONAL / MIN_NOTIONAL.
+    #[serde(default)]
+    min_notional: Option<String>,
+    /// Futures MIN_NOTIONAL.
+    #[serde(default)]
+    notional: Option<String>,
+}
+
+fn parse_binance(source: SourceId, body: &str) -> Result<Vec<RawInstrument>> {
+    let info: BinanceExchangeInfo = serde_json::from_str(body)?;
+    Ok(info
+        .symbols
+        .into_iter()
+        .filter(|s| source.is_spot() || s.contract_type.as_deref() == Some("PERPETUAL"))
+        .filter_map(|s| {
+            let state = binance_state(&s.status)?;
+            let filter = |name: &str| s.filters.iter().find(|f| f.filter_type == name);
+            let lot = filter("LOT_SIZE");
+            let notional = filter("NOTIONAL").or_else(|| filter("MIN_NOTIONAL"));
+            let spec = InstrumentSpec {
+                max_qty: parse_decimal(lot.and_then(|f| f.max_qty.as_deref())),
+                min_notional: parse_decimal(
+                    notional.and_then(|f| f.min_notional.as_deref().or(f.notional.as_deref())),
+                ),
+                lot_size: parse_decimal(lot.and_then(|f| f.step_size.as_deref())),
+                contract_type: Some(if source.is_spot() {
+                    ContractType::Spot
+                } else {
+                    ContractType::LinearPerpetual
+                }),
+                settle_asset: s.margin_asset.clone().filter(|_| source.is_futures()),
+                listed_at: s.onboard_date,
+                delist_at: s.delivery_date.filter(|&d| d < BINANCE_NO_DELIVERY),
+            };
+            Some(RawInstrument {
+                tick_size: parse_decimal(
+                    filter("PRICE_FILTER").and_then(|f| f.tick_size.as_deref()),
+                ),
+                min_qty: parse_decimal(lot.and_then(|f| f.min_qty.as_deref())),
+                spec,
+                exchange_symbol: s.symbol,
+                base_asset: s.base_asset,
+                quote_asset: s.quote_asset,
+                status: s.status,
+                state,
+            })
+        })
+        .collect())
+}
+
+// === Bybit ===
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BybitResponse {
+    ret_code: i64,
+    #[serde(default)]
+    ret_msg: String,
+    result: Option<BybitResult>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BybitResult {
+    list: Vec<BybitInstrument>,
+    #[serde(default)]
+    next_page_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BybitInstrument {
+    symbol: String,
+    status: String,
+    base_coin: String,
+    quote_coin: String,
+    #[serde(default)]
+    contract_type: Option<String>,
+    #[serde(default)]
+    settle_coin: Option<String>,
+    #[serde(default)]
+    launch_time: Option<String>,
+    /// "0" unless a delivery/delisting is scheduled.
+    #[serde(default)]
+    delivery_time: Option<String>,
+    #[serde(default)]
+    price_filter: Option<BybitPriceFilter>,
+    #[serde(default)]
+    lot_size_filter: Option<BybitLotSizeFilter>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BybitPriceFilter {
+    tick_size: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BybitLotSizeFilter {
+    min_order_qty: Option<String>,
+    #[serde(default)]
+    max_order_qty: Option<String>,
+    /// Linear quantity step.
+    #[serde(default)]
+    qty_step: Option<String>,
+    /// Spot quantity step.
+    #[serde(default)]
+    base_precision: Option<String>,
+    /// Spot minimum order value.
+    #[serde(default)]
+    min_order_amt: Option<String>,
+    /// Linear minimum order value.
+    #[serde(default)]
+    min_notional_value: Option<String>,
+}
+
+/// `None` for closed listings.
+fn bybit_state(status: &str) -> Option<InstrumentState> {
+    match status {
+        "Trading" => Some(InstrumentState::Trading),
+        "PreLaunch" => Some(InstrumentState::PreLaunch),
+        "Delivering" => Some(InstrumentState::Delivering),
+        _ => None,
+    }
+}
+
+fn parse_bybit(source: SourceId, body: &str) -> Result<Page> {
+    let resp: BybitResponse = serde_json::from_str(body)?;
+    anyhow::ensure!(
+        resp.ret_code == 0,
+        "bybit retCode {}: {}",
+        resp.ret_code,
+        resp.ret_msg
+    );
+    let result = resp.result.context("bybit response without result")?;
+    let instruments = result
+        .list
+        .into_iter()
+        .filter(|i| source.is_spot() || i.contract_type.as_deref() == Some("LinearPerpetual"))
+        .filter_map(|i| {
+            let state = bybit_state(&i.status)?;
+            let lot = i.lot_size_filter.as_ref();
+            let lot_field = |f: fn(&BybitLotSizeFilter) -> Option<&String>| {
+                parse_decimal(lot.and_then(f).map(String::as_str))
+            };
+            let spec = InstrumentSpec {
+                max_qty: lot_field(|f| f.max_order_qty.as_ref()),
+                min_notional: lot_field(|f| {
+                    f.min_notional_value.as_ref().or(f.min_order_amt.as_ref())
+                }),
+                lot_size: lot_field(|f| f.qty_step.as_ref().or(f.base_precision.as_ref())),
+                contract_type: Some(if source.is_spot() {
+                    ContractType::Spot
+                } else {
+                    ContractType::LinearPerpetual
+                }),
+                settle_asset: i.settle_coin.clone().filter(|c| !c.is_empty()),
+                listed_at: i.launch_time.as_deref().and_then(|t| t.parse().ok()),
+                delist_at: i
+                    .delivery_time
+                    .as_deref()
+                    .and_then(|t| t.parse().ok())
+                    .filter(|&t| t > 0),
+            };
+            Some(RawInstrument {
+                tick_size: parse_decimal(
+                    i.price_filter.as_ref().and_then(|f| f.tick_size.as_deref()),
+                ),
+                min_qty: lot_field(|f| f.min_order_qty.as_ref()),
+                spec,
+                exchange_symbol: i.symbol,
+                base_asset: i.base_coin,
+                quote_asset: i.quote_coin,
+                status: i.status,
+                state,
+            })
+        })
+        .collect();
+    Ok(Page {
+        instruments,
+        next_cursor: result.next_page_cursor.filter(|c| !c.is_empty()),
+    })
+}
+
+// === MEXC ===
+
+#[derive(Deserialize)]
+struct MexcSpotExchangeInfo {
+    symbols: Vec<MexcSpotSymbol>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MexcSpotSymbol {
+    symbol: String,
+    status: String,
+    base_asset: String,
+    quote_asset: String,
+    #[serde(default)]
+    quote_precision: Option<i32>,
+    #[serde(default)]
+    base_size_precision: Option<String>,
+    /// Minimum order value in quote.
+    #[serde(default)]
+    quote_amount_precision: Option<String>,
+    #[serde(default = "default_true")]
+    is_spot_trading_allowed: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn parse_mexc_spot(body: &str) -> Result<Vec<RawInstrument>> {
+    let info: MexcSpotExchangeInfo = serde_json::from_str(body)?;
+    Ok(info
+        .symbols
+        .into_iter()
+        .filter_map(|s| {
+            // 1 online, 2 paused, 3 offline
+            let state = match (s.status.as_str(), s.is_spot_trading_allowed) {
+                ("1", true) => InstrumentState::Trading,
+                ("1", false) | ("2", _) => InstrumentState::Halted,
+                _ => return None,
+            };
+            Some((state, s))
+        })
+        .map(|(state, s)| RawInstrument {
+            tick_size: s.quote_precision.map(|p| 10f64.powi(-p)),
+            min_qty: parse_decimal(s.base_size_precision.as_deref()),
+            spec: InstrumentSpec {
+                min_notional: parse_decimal(s.quote_amount_precision.as_deref()),
+                lot_size: parse_decimal(s.base_size_precision.as_deref()),
+                contract_type: Some(ContractType::Spot),
+                ..Default::default()
+            },
+            exchange_symbol: s.symbol,
+            base_asset: s.base_asset,
+            quote_asset: s.quote_asset,
+            status: s.status,
+            state,
+        })
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct MexcFuturesResponse {
+    success: bool,
+    #[serde(default)]
+    code: i64,
+    #[serde(default)]
+    data: Vec<MexcContract>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MexcContract {
+    symbol: String,
+    base_coin: String,
+    quote_coin: String,
+    state: i64,
+    #[serde(default)]
+    contract_size: Option<f64>,
+    #[serde(default)]
+    min_vol: Option<f64>,
+    #[serde(default)]
+    max_vol: Option<f64>,
+    /// Volume step, in contracts.
+    #[serde(default)]
+    vol_unit: Option<f64>,
+    #[serde(default)]
+    price_unit: Option<f64>,
+    #[serde(default)]
+    settle_coin: Option<String>,
+    /// Unix milliseconds.
+    #[serde(default)]
+    create_time: Option<u64>,
+}
+
+fn parse_mexc_futures(body: &str) -> Result<Vec<RawInstrument>> {
+    let resp: MexcFuturesResponse = serde_json::from_str(body)?;
+    // The contract API answers 200 with success=false when access is denied.
+    anyhow::ensure!(resp.success, "mexc contract API error code {}", resp.code);
+    Ok(resp
+        .data
+        .into_iter()
+        .filter_map(|c| {
+            // 0 enabled, 1 delivery, 2 completed, 3 offline, 4 paused
+            let state = match c.state {
+                0 => InstrumentState::Trading,
+                1 => InstrumentState::Delivering,
+                4 => InstrumentState::Halted,
+                _ => return None,
+            };
+            Some((state, c))
+        })
+        .map(|(state, c)| RawInstrument {
+            min_qty: match (c.min_vol, c.contract_size) {
+                (Some(vol), Some(size)) => Some(vol * size),
+                _ => None,
+            },
+            spec: InstrumentSpec {
+                max_qty: c.max_vol.zip(c.contract_size).map(|(v, size)| v * size),
+                lot_size: c.vol_unit.zip(c.contract_size).map(|(v, size)| v * size),
+                contract_type: Some(ContractType::LinearPerpetual),
+                settle_asset: c.settle_coin.clone(),
+                listed_at: c.create_time,
+                ..Default::default()
+            },
+            tick_size: c.price_unit,
+            exchange_symbol: c.symbol,
+            base_asset: c.base_coin,
+            quote_asset: c.quote_coin,
+            status: c.state.to_string(),
+            state,
+        })
+        .collect())
+}
+
+// === OKX ===
+
+#[derive(Deserialize)]
+struct OkxResponse {
+    code: String,
+    #[serde(default)]
+    msg: String,
+    #[serde(default)]
+    data: Vec<OkxInstrument>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OkxInstrument {
+    inst_id: String,
+    state: String,
+    #[serde(default)]
+    base_ccy: String,
+    #[serde(default)]
+    quote_ccy: String,
+    #[serde(default)]
+    settle_ccy: String,
+    #[serde(default)]
+    ct_val: String,
+    #[serde(default)]
+    ct_val_ccy: String,
+    #[serde(default)]
+    ct_type: String,
+    #[serde(default)]
+    tick_sz: String,
+    #[serde(default)]
+    min_sz: String,
+    #[serde(default)]
+    lot_sz: String,
+    /// Max quantity of a limit order.
+    #[serde(default)]
+    max_lmt_sz: String,
+    /// Unix milliseconds.
+    #[serde(default)]
+    list_time: String,
+    /// Unix milliseconds; set on a swap once its delisting is announced.
+    #[serde(default)]
+    exp_time: String,
+}
+
+/// `None` for test and expired instruments.
+fn okx_state(state: &str) -> Option<InstrumentState> {
+    match state {
+        "live" => Some(InstrumentState::Trading),
+        "suspend" => Some(InstrumentState::Halted),
+        "preopen" => Some(InstrumentState::PreLaunch),
+        _ => None,
+    }
+}
+
+fn parse_okx(source: SourceId, body: &str) -> Result<Vec<RawInstrument>> {
+    let resp: OkxResponse = serde_json::from_str(body)?;
+    anyhow::ensure!(resp.code == "0", "okx code {}: {}", resp.code, resp.msg);
+    Ok(resp
+        .data
+        .into_iter()
+        .filter(|i| source.is_spot() || i.ct_type == "linear")
+        .filter_map(|i| {
+            let state = okx_state(&i.state)?;
+            let tick_size = parse_decimal(Some(&i.tick_sz));
+            let min_sz = parse_decimal(Some(&i.min_sz));
+            let listed_at = i.list_time.parse().ok();
+            let delist_at = i.exp_time.parse().ok();
+            Some(if source.is_spot() {
+                RawInstrument {
+                    min_qty: min_sz,
+                    tick_size,
+                    spec: InstrumentSpec {
+                        max_qty: parse_decimal(Some(&i.max_lmt_sz)),
+                        lot_size: parse_decimal(Some(&i.lot_sz)),
+                        contract_type: Some(ContractType::Spot),
+                        listed_at,
+                        delist_at,
+                        ..Default::default()
+                    },
+                    exchange_symbol: i.inst_id,
+                    base_asset: i.base_ccy,
+                    quote_asset: i.quote_ccy,
+                    status: i.state,
+                    state,
+                }
+            } else {
+                // SWAP leaves baseCcy/quoteCcy empty: the base is the contract
+                // value currency and a linear swap is quoted in its settle currency.
+                // Sizes are in contracts, so scale by ctVal to get base units.
+                let ct_val = parse_decimal(Some(&i.ct_val));
+                let base_units = |sz: &str| {
+                    parse_decimal(Some(sz))
+                        .zip(ct_val)
+                        .map(|(sz, val)| sz * val)
+                };
+                RawInstrument {
+                    min_qty: base_units(&i.min_sz),
+                    tick_size,
+                    spec: InstrumentSpec {
+                        max_qty: base_units(&i.max_lmt_sz),
+                        lot_size: base_units(&i.lot_sz),
+                        contract_type: Some(ContractType::LinearPerpetual),
+                        settle_asset: Some(i.settle_ccy.clone()),
+                        listed_at,
+                        delist_at,
+                        ..Default::default()
+                    },
+                    exchange_symbol: i.inst_id,
+                    base_asset: i.ct_val_ccy,
+                    quote_asset: i.settle_ccy,
+                    status: i.state,
+                    state,
+                }
+            })
+        })
+        .collect())
+}
+
+// === 24h tickers ===
+
+/// Parse one ticker response body for `source`.
+fn parse_tickers(source: SourceId, body: &str) -> Result<Tickers> {
+    match source {
+        SourceId::BinanceSpot | SourceId::BinanceFutures | SourceId::MexcSpot => {
+            parse_binance_tickers(body)
+        }
+        SourceId::BybitSpot | SourceId::BybitFutures => parse_bybit_tickers(body),
+        SourceId::OkxSpot | SourceId::OkxFutures => parse_okx_tickers(source, body),
+        SourceId::MexcFutures => parse_mexc_futures_tickers(body),
+    }
+}
+
+/// Binance spot/futures and MEXC spot share the `/ticker/24hr` shape.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceTicker {
+    symbol: String,
+    #[serde(default)]
+    quote_volume: Option<String>,
+    #[serde(default)]
+    bid_price: Option<String>,
+    #[serde(default)]
+    ask_price: Option<String>,
+}
+
+fn parse_binance_tickers(body: &str) -> Result<Tickers> {
+    let tickers: Vec<BinanceTicker> = serde_json::from_str(body)?;
+    Ok(tickers
+        .into_iter()
+        .map(|t| {
+            let ticker = Ticker24h {
+                quote_volume: parse_decimal(t.quote_volume.as_deref()),
+                best_bid: parse_decimal(t.bid_price.as_deref()),
+                best_ask: parse_decimal(t.ask_price.as_deref()),
+            };
+            (t.symbol, ticker)
+        })
+        .collect())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BybitTickersResponse {
+    ret_code: i64,
+    #[serde(default)]
+    ret_msg: String,
+    result: Option<BybitTickersResult>,
+}
+
+#[derive(Deserialize)]
+struct BybitTickersResult {
+    list: Vec<BybitTicker>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BybitTicker {
+    symbol: String,
+    #[serde(default)]
+    bid1_price: Option<String>,
+    #[serde(default)]
+    ask1_price: Option<String>,
+    #[serde(default)]
+    turnover24h: Option<String>,
+}
+
+fn parse_bybit_tickers(body: &str) -> Result<Tickers> {
+    let resp: BybitTickersResponse = serde_json::from_str(body)?;
+    anyhow::ensure!(
+        resp.ret_code == 0,
+        "bybit retCode {}: {}",
+        resp.ret_code,
+        resp.ret_msg
+    );
+    let result = resp.result.context("bybit response without result")?;
+    Ok(result
+        .list
+        .into_iter()
+        .map(|t| {
+            let ticker = Ticker24h {
+                quote_volume: parse_decimal(t.turnover24h.as_deref()),
+                best_bid: parse_decimal(t.bid1_price.as_deref()),
+                best_ask: parse_decimal(t.ask1_price.as_deref()),
+            };
+            (t.symbol, ticker)
+        })
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct OkxTickersResponse {
+    code: String,
+    #[serde(default)]
+    msg: String,
+    #[serde(default)]
+    data: Vec<OkxTicker>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OkxTicker {
+    inst_id: String,
+    #[serde(default)]
+    bid_px: String,
+    #[serde(default)]
+    ask_px: String,
+    #[serde(default)]
+    last: String,
+    #[serde(default)]
+    vol_ccy24h: String,
+}
+
+fn parse_okx_tickers(source: SourceId, body: &str) -> Result<Tickers> {
+    let resp: OkxTickersResponse = serde_json::from_str(body)?;
+    anyhow::ensure!(resp.code == "0", "okx code {}: {}", resp.code, resp.msg);
+    Ok(resp
+        .data
+        .into_iter()
+        .map(|t| {
+            let vol_ccy = parse_decimal(Some(&t.vol_ccy24h));
+            // volCcy24h is in quote currency for SPOT but in base currency
+            // for SWAP; price the latter at the last trade.
+            let quote_volume = if source.is_spot() {
+                vol_ccy
+            } else {
+                vol_ccy
+                    .zip(parse_decimal(Some(&t.last)))
+                    .map(|(v, p)| v * p)
+            };
+            let ticker = Ticker24h {
+                quote_volume,
+                best_bid: parse_decimal(Some(&t.bid_px)),
+                best_ask: parse_decimal(Some(&t.ask_px)),
+            };
+            (t.inst_id, ticker)
+        })
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct MexcTickersResponse {
+    success: bool,
+    #[serde(default)]
+    code: i64,
+    #[serde(default)]
+    data: Vec<MexcTicker>,
+}
+
+#[derive(Deserialize)]
+struct MexcTicker {
+    symbol: String,
+    #[serde(default)]
+    bid1: Option<f64>,
+    #[serde(default)]
+    ask1: Option<f64>,
+    /// 24h turnover in quote currency.
+    #[serde(default)]
+    amount24: Option<f64>,
+}
+
+fn parse_mexc_futures_tickers(body: &str) -> Result<Tickers> {
+    let resp: MexcTickersResponse = serde_json::from_str(body)?;
+    anyhow::ensure!(resp.success, "mexc contract API error code {}", resp.code);
+    Ok(resp
+        .data
+        .into_iter()
+        .map(|t| {
+            let ticker = Ticker24h {
+                quote_volume: t.amount24,
+                best_bid: t.bid1,
+                best_ask: t.ask1,
+            };
+            (t.symbol, ticker)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_http::{Response, TestServer};
+
+    fn fixture(name: &str) -> String {
+        let path = format!("{}/fixtures/rest/{}", env!("CARGO_MANIFEST_DIR"), name);
+        std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+    }
+
+    fn fast_retry() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::from_millis(1),
+            request_timeout: Duration::from_secs(5),
+        }
+    }
+
+    fn exchanges(base: &str) -> ExchangesConfig {
+        let entry = |name: &str, (spot, futures): (&str, &str), (tspot, tfut): (&str, &str)| {
+            ExchangeEntry {
+                name: name.to_string(),
+                rest_spot: format!("{}/{}", base, name),
+                rest_futures: format!("{}/{}", base, name),
+                ws_spot: String::new(),
+                ws_futures: String::new(),
+                ws_spot_standby: None,
+                ws_futures_standby: None,
+                max_ws_subscriptions: 200,
+                validation_batch_size: 50,
+                instruments_path_spot: spot.to_string(),
+                instruments_path_futures: futures.to_string(),
+                ticker_path_spot: tspot.to_string(),
+                ticker_path_futures: tfut.to_string(),
+                ws_channel: None,
+            }
+        };
+        ExchangesConfig {
+            exchange: vec![
+                entry(
+                    "binance",
+                    ("/api/v3/exchangeInfo", "/fapi/v1/exchangeInfo"),
+                    ("/api/v3/ticker/24hr", "/fapi/v1/ticker/24hr"),
+                ),
+                entry(
+                    "bybit",
+                    (
+                        "/v5/market/instruments-info?category=spot",
+                        "/v5/market/instruments-info?category=linear",
+                    ),
+                    (
+                        "/v5/market/tickers?category=spot",
+                        "/v5/market/tickers?category=linear",
+                    ),
+                ),
+                entry(
+                    "okx",
+                    (
+                        "/api/v5/public/instruments?instType=SPOT",
+                        "/api/v5/public/instruments?instType=SWAP",
+                    ),
+                    (
+                        "/api/v5/market/tickers?instType=SPOT",
+                        "/api/v5/market/tickers?instType=SWAP",
+                    ),
+                ),
+                entry(
+                    "mexc",
+                    ("/api/v3/exchangeInfo", "/api/v1/contract/detail"),
+                    ("/api/v3/ticker/24hr", "/api/v1/contract/ticker"),
+                ),
+            ],
+        }
+    }
+
+    fn all_routes(server: &TestServer) {
+        server.route(
+            "/binance/api/v3/exchangeInfo",
+            Response::ok(fixture("binance_spot.json")),
+        );
+        server.route(
+            "/binance/fapi/v1/exchangeInfo",
+            Response::ok(fixture("binance_futures.json")),
+        );
+        server.route(
+            "/bybit/v5/market/instruments-info?category=spot&limit=1000",
+            Response::ok(fixture("bybit_spot.json")),
+        );
+        server.route(
+            "/bybit/v5/market/instruments-info?category=linear&limit=1000",
+            Response::ok(fixture("bybit_linear_page1.json")),
+        );
+        server.route(
+            "/bybit/v5/market/instruments-info?category=linear&limit=1000&cursor=page2",
+            Response::ok(fixture("bybit_linear_page2.json")),
+        );
+        server.route(
+            "/okx/api/v5/public/instruments?instType=SPOT",
+            Response::ok(fixture("okx_spot.json")),
+        );
+        server.route(
+            "/okx/api/v5/public/instruments?instType=SWAP",
+            Response::ok(fixture("okx_swap.json")),
+        );
+        server.route(
+            "/mexc/api/v3/exchangeInfo",
+            Response::ok(fixture("mexc_spot.json")),
+        );
+        server.route(
+            "/mexc/api/v1/contract/detail",
+            Response::ok(fixture("mexc_futures.json")),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_all_from_recorded_responses() {
+        let server = TestServer::start().await;
+        all_routes(&server);
+
+        let client = RestClient::new(fast_retry()).unwrap();
+        let results = client
+            .fetch_all(&exchanges(&server.base_url()))
+            .await
+            .unwrap();
+        assert_eq!(results.successful(), 8);
+
+        // Binance spot: BREAK kept as halted, filters extracted
+        let binance = results.get(SourceId::BinanceSpot).unwrap();
+        let btc = binance
+            .iter()
+            .find(|i| i.exchange_symbol == "BTCUSDT")
+            .unwrap();
+        assert_eq!(btc.base_asset, "BTC");
+        assert_eq!(btc.tick_size, Some(0.01));
+        assert_eq!(btc.min_qty, Some(0.00001));
+        assert_eq!(btc.spec.max_qty, Some(9000.0));
+        assert_eq!(btc.spec.min_notional, Some(5.0));
+        assert_eq!(btc.spec.contract_type, Some(ContractType::Spot));
+        let luna = binance
+            .iter()
+            .find(|i| i.exchange_symbol == "LUNAUSDT")
+            .unwrap();
+        assert_eq!(
+            (luna.status.as_str(), luna.state),
+            ("BREAK", InstrumentState::Halted)
+        );
+
+        // Binance futures: only PERPETUAL contracts, delivery date when scheduled
+        let futures = results.get(SourceId::BinanceFutures).unwrap();
+        assert!(futures.iter().all(|i| !i.exchange_symbol.contains('_')));
+        let xem = futures
+            .iter()
+            .find(|i| i.exchange_symbol == "XEMUSDT")
+            .unwrap();
+        assert_eq!(xem.state, InstrumentState::Delivering);
+        assert_eq!(xem.spec.delist_at, Some(1701417600000));
+        assert!(futures
+            .iter()
+            .filter(|i| i.state == InstrumentState::Trading)
+            .all(|i| i.spec.delist_at.is_none()));
+
+        // Bybit spot: PreLaunch kept, linear: Closed dropped
+        let bybit = results.get(SourceId::BybitSpot).unwrap();
+        let new = bybit
+            .iter()
+            .find(|i| i.exchange_symbol == "NEWTOKENUSDT")
+            .unwrap();
+        assert_eq!(new.state, InstrumentState::PreLaunch);
+
+        // Bybit linear: both cursor pages collected
+        let linear = results.get(SourceId::BybitFutures).unwrap();
+        assert!(linear.iter().any(|i| i.exchange_symbol == "BTCUSDT"));
+        assert!(linear.iter().any(|i| i.exchange_symbol == "SOLUSDT"));
+        assert!(!linear.iter().any(|i| i.exchange_symbol == "OLDUSDT"));
+
+        // OKX spot: preopen; MEXC spot: paused
+        let okx = results.get(SourceId::OkxSpot).unwrap();
+        let abc = okx
+            .iter()
+            .find(|i| i.exchange_symbol == "ABC-USDT")
+            .unwrap();
+        assert_eq!(abc.state, InstrumentState::PreLaunch);
+        let mexc = results.get(SourceId::MexcSpot).unwrap();
+        let dead = mexc
+            .iter()
+            .find(|i| i.exchange_symbol == "DEADUSDT")
+            .unwrap();
+        assert_eq!(dead.state, InstrumentState::Halted);
+
+        // OKX swap: base/quote derived from ctValCcy/settleCcy, minSz scaled by ctVal
+        let swap = results.get(SourceId::OkxFutures).unwrap();
+        let btc = swap
+            .iter()
+            .find(|i| i.exchange_symbol == "BTC-USDT-SWAP")
+            .unwrap();
+        assert_eq!(
+            (btc.base_asset.as_str(), btc.quote_asset.as_str()),
+            ("BTC", "USDT")
+        );
+        assert_eq!(btc.min_qty, Some(0.0001));
+        assert_eq!(btc.spec.lot_size, Some(0.0001));
+        assert_eq!(btc.spec.max_qty, Some(1_000_000.0));
+        assert_eq!(btc.spec.contract_type, Some(ContractType::LinearPerpetual));
+        assert_eq!(btc.spec.settle_asset.as_deref(), Some("USDT"));
+        assert_eq!(btc.spec.listed_at, Some(1573557408000));
+
+        // MEXC futures: minVol * contractSize
+        let mexc = results.get(SourceId::MexcFutures).unwrap();
+        let btc = mexc
+            .iter()
+            .find(|i| i.exchange_symbol == "BTC_USDT")
+            .unwrap();
+        assert_eq!(btc.min_qty, Some(0.0001));
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay_offline() {
+        let dir =
+            std::env::temp_dir().join(format!("discovery-rest-replay-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let server = TestServer::start().await;
+        all_routes(&server);
+        // OKX swap down while recording: it stays down on replay
+        server.route(
+            "/okx/api/v5/public/instruments?instType=SWAP",
+            Response::status(500),
+        );
+
+        let recording = RestClient::new(fast_retry())
+            .unwrap()
+            .with_capture(Capture::record(&dir).unwrap());
+        let live = recording
+            .fetch_all(&exchanges(&server.base_url()))
+            .await
+            .unwrap();
+        assert!(dir.join("rest/bybit_futures_instruments_1.json").exists());
+
+        // Nothing listens here
+        let replay = RestClient::new(fast_retry())
+            .unwrap()
+            .with_capture(Capture::replay(&dir).unwrap());
+        let replayed = replay
+            .fetch_all(&exchanges("http://127.0.0.1:1"))
+            .await
+            .unwrap();
+        assert_eq!(replayed.instruments, live.instruments);
+        assert!(replayed.get(SourceId::OkxFutures).is_none());
+        assert_eq!(replayed.successful(), 7);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_retry_then_degrade() {
+        let server = TestServer::start().await;
+        all_routes(&server);
+        // Binance spot recovers after two 503s; MEXC futures and OKX swap never answer.
+        server.route_sequence(
+            "/binance/api/v3/exchangeInfo",
+            vec![
+                Response::status(503),
+                Response::status(503),
+                Response::ok(fixture("binance_spot.json")),
+            ],
+        );
+        server.route(
+            "/mexc/api/v1/contract/detail",
+            Response::ok(r#"{"success":false,"code":1002,"message":"Contract not allow"}"#),
+        );
+        server.route(
+            "/okx/api/v5/public/instruments?instType=SWAP",
+            Response::status(500),
+        );
+
+        let client = RestClient::new(fast_retry()).unwrap();
+        let results = client
+            .fetch_all(&exchanges(&server.base_url()))
+            .await
+            .unwrap();
+        assert_eq!(results.successful(), 6);
+        assert!(results.get(SourceId::BinanceSpot).is_some());
+        assert!(results.get(SourceId::MexcFutures).is_none());
+        assert!(results.get(SourceId::OkxFutures).is_none());
+
+        // A third failing source drops below MIN_SOURCES.
+        server.route("/binance/fapi/v1/exchangeInfo", Response::status(429));
+        let err = client
+            .fetch_all(&exchanges(&server.base_url()))
+            .await
+            .unwrap_err();
+        match err.downcast_ref::<DiscoveryError>() {
+            Some(DiscoveryError::InsufficientSources {
+                successful,
+                required,
+            }) => {
+                assert_eq!((*successful, *required), (5, MIN_SOURCES));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_tickers_from_recorded_responses() {
+        let server = TestServer::start().await;
+        let routes = [
+            ("/binance/api/v3/ticker/24hr", "binance_spot_ticker.json"),
+            (
+                "/binance/fapi/v1/ticker/24hr",
+                "binance_futures_ticker.json",
+            ),
+            (
+                "/bybit/v5/market/tickers?category=spot",
+                "bybit_spot_ticker.json",
+            ),
+            (
+                "/bybit/v5/market/tickers?category=linear",
+                "bybit_linear_ticker.json",
+            ),
+            (
+                "/okx/api/v5/market/tickers?instType=SPOT",
+                "okx_spot_ticker.json",
+            ),
+            (
+                "/okx/api/v5/market/tickers?instType=SWAP",
+                "okx_swap_ticker.json",
+            ),
+            ("/mexc/api/v3/ticker/24hr", "mexc_spot_ticker.json"),
+        ];
+        for (path, file) in routes {
+            server.route(path, Response::ok(fixture(file)));
+        }
+        // MEXC futures tickers down: that source is just not filtered
+        server.route("/mexc/api/v1/contract/ticker", Response::status(500));
+
+        let client = RestClient::new(fast_retry()).unwrap();
+        let results = client.fetch_tickers(&exchanges(&server.base_url())).await;
+        assert!(results.get(SourceId::MexcFutures).is_none());
+
+        let btc = results.get(SourceId::BinanceSpot).unwrap()["BTCUSDT"];
+        assert_eq!(btc.quote_volume, Some(1408101633.4852001));
+        assert!(btc.book_width_pct().unwrap() < 0.001);
+        // Binance futures 24hr carries no book
+        let pepe = results.get(SourceId::BinanceFutures).unwrap()["1000PEPEUSDT"];
+        assert_eq!(pepe.book_width_pct(), None);
+
+        // Bybit: empty bid parses as None
+        let new = results.get(SourceId::BybitSpot).unwrap()["NEWTOKENUSDT"];
+        assert_eq!((new.best_bid, new.quote_volume), (None, Some(0.0)));
+
+        // OKX swap: volCcy24h is in BTC, priced at last
+        let swap = results.get(SourceId::OkxFutures).unwrap()["BTC-USDT-SWAP"];
+        assert_eq!(swap.quote_volume, Some(45123.21 * 66880.0));
+        let spot = results.get(SourceId::OkxSpot).unwrap()["BTC-USDT"];
+        assert_eq!(spot.quote_volume, Some(501234567.89));
+    }
+}
@@ -0,0 +1,267 @@
+//! Exchange status filter — keeps listings that trade now and will keep
+//! trading for a while.
+//!
+//! The REST parsers map each venue's status onto `InstrumentState`. Closed
+//! and offline listings are dropped there; the intermediate states (Binance
+//! `BREAK`/`PRE_DELIVERING`, Bybit `PreLaunch`, OKX `suspend`/`preopen`, ...)
+//! are kept so they can be reported here. Announced delist times (Binance
+//! futures `deliveryDate`, Bybit `deliveryTime`, OKX `expTime`) end up in
+//! `InstrumentSpec::delist_at`.
+//!
+//! A listing that is not `Trading`, or that delists within
+//! `discovery.delist_horizon_hours`, is removed from its source; the same
+//! symbol elsewhere is unaffected. One delisting later is kept and reported
+//! as scheduled, and its time is published in symbols.bin for the tracker.
+//!
+//! Runs after overrides, before the liquidity filter.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use common::config::DiscoveryConfig;
+use common::types::SourceId;
+
+use crate::normalizer::NormalizedInstrument;
+
+/// A listing's trading state, normalized across venues.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstrumentState {
+    Trading,
+    /// Temporarily not trading: maintenance, circuit breaker, paused.
+    Halted,
+    /// Listed but not open yet.
+    PreLaunch,
+    /// Being delivered, settled or delisted.
+    Delivering,
+}
+
+impl InstrumentState {
+    pub fn name(self) -> &'static str {
+        match self {
+            InstrumentState::Trading => "trading",
+            InstrumentState::Halted => "halted",
+            InstrumentState::PreLaunch => "prelaunch",
+            InstrumentState::Delivering => "delivering",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct StatusFilter {
+    pub delist_horizon_ms: u64,
+}
+
+impl StatusFilter {
+    pub fn from_config(config: &DiscoveryConfig) -> Self {
+        Self {
+            delist_horizon_ms: config.delist_horizon_hours * 3_600_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnavailableReason {
+    /// Not trading; `status` as the venue spells it.
+    State {
+        state: InstrumentState,
+        status: String,
+    },
+    /// Trading, but delists within the horizon.
+    DelistsSoon { delist_at: u64, hours_left: f64 },
+}
+
+impl UnavailableReason {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            UnavailableReason::State { state, .. } => state.name(),
+            UnavailableReason::DelistsSoon { .. } => "delists_soon",
+        }
+    }
+}
+
+impl fmt::Display for UnavailableReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnavailableReason::State { state, status } => {
+                write!(f, "{} ({})", state.name(), status)
+            }
+            UnavailableReason::DelistsSoon {
+                delist_at,
+                hours_left,
+            } => write!(f, "delists in {:.1}h (at {})", hours_left, delist_at),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unavailable {
+    pub source: SourceId,
+    pub exchange_symbol: String,
+    /// Canonical name, e.g. "BTC-USDT".
+    pub name: String,
+    pub reason: UnavailableReason,
+}
+
+/// A kept listing with an announced delist time beyond the horizon.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledDelisting {
+    pub source: SourceId,
+    pub exchange_symbol: String,
+    pub name: String,
+    /// Unix milliseconds.
+    pub delist_at: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StatusReport {
+    pub excluded: Vec<Unavailable>,
+    pub scheduled: Vec<ScheduledDelisting>,
+}
+
+impl StatusReport {
+    /// Exclusion counts keyed by (source name, reason kind).
+    pub fn counts(&self) -> BTreeMap<(&'static str, &'static str), usize> {
+        let mut counts = BTreeMap::new();
+        for u in &self.excluded {
+            *counts
+                .entry((u.source.name(), u.reason.kind()))
+                .or_insert(0) += 1;
+        }
+        counts
+    }
+}
+
+impl StatusFilter {
+    /// Remove listings that are not trading or delist within the horizon
+    /// from `normalized`. `now_ms` is unix milliseconds.
+    pub fn apply(&self, normalized: &mut Vec<NormalizedInstrument>, now_ms: u64) -> StatusReport {
+        let mut report = StatusReport::default();
+        normalized.retain(|inst| {
+            let reason = match (inst.raw.state, inst.raw.spec.delist_at) {
+                (InstrumentState::Trading, Some(at))
+                    if at <= now_ms.saturating_add(self.delist_horizon_ms) =>
+                {
+                    Some(UnavailableReason::DelistsSoon {
+                        delist_at: at,
+                        hours_left: at.saturating_sub(now_ms) as f64 / 3_600_000.0,
+                    })
+                }
+                (InstrumentState::Trading, _) => None,
+                (state, _) => Some(UnavailableReason::State {
+                    state,
+                    status: inst.raw.status.clone(),
+                }),
+            };
+            match reason {
+                Some(reason) => {
+                    report.excluded.push(Unavailable {
+                        source: inst.source,
+                        exchange_symbol: inst.raw.exchange_symbol.clone(),
+                        name: inst.name.clone(),
+                        reason,
+                    });
+                    false
+                }
+                None => {
+                    if let Some(delist_at) = inst.raw.spec.delist_at {
+                        report.scheduled.push(ScheduledDelisting {
+                            source: inst.source,
+                            exchange_symbol: inst.raw.exchange_symbol.clone(),
+                            name: inst.name.clone(),
+                            delist_at,
+                        });
+                    }
+                    true
+                }
+            }
+        });
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::symbols::InstrumentSpec;
+
+    use crate::rest_client::RawInstrument;
+
+    const HOUR: u64 = 3_600_000;
+
+    fn inst(
+        source: SourceId,
+        name: &str,
+        state: InstrumentState,
+        delist_at: Option<u64>,
+    ) -> NormalizedInstrument {
+        let (base, quote) = name.split_once('-').unwrap();
+        NormalizedInstrument {
+            source,
+            name: name.to_string(),
+            base: base.to_string(),
+            quote: quote.to_string(),
+            price_multiplier: 1.0,
+            raw: RawInstrument {
+                exchange_symbol: name.replace('-', ""),
+                base_asset: base.to_string(),
+                quote_asset: quote.to_string(),
+                status: "BREAK".to_string(),
+                state,
+                min_qty: None,
+                tick_size: None,
+                spec: InstrumentSpec {
+                    delist_at,
+                    ..Default::default()
+                },
+            },
+        }
+    }
+
+    #[test]
+    fn test_status_filter() {
+        let spot = SourceId::BinanceSpot;
+        let fut = SourceId::BinanceFutures;
+        let now = 1_700_000_000_000;
+        let mut normalized = vec![
+            inst(spot, "BTC-USDT", InstrumentState::Trading, None),
+            inst(spot, "LUNA-USDT", InstrumentState::Halted, None),
+            inst(fut, "LUNA-USDT", InstrumentState::Trading, None),
+            inst(
+                fut,
+                "XEM-USDT",
+                InstrumentState::Trading,
+                Some(now + HOUR / 2),
+            ),
+            inst(
+                fut,
+                "OLD-USDT",
+                InstrumentState::Trading,
+                Some(now + 72 * HOUR),
+            ),
+        ];
+        let filter = StatusFilter {
+            delist_horizon_ms: 24 * HOUR,
+        };
+        let report = filter.apply(&mut normalized, now);
+
+        let kept: Vec<_> = normalized
+            .iter()
+            .map(|i| (i.source, i.name.as_str()))
+            .collect();
+        assert_eq!(
+            kept,
+            vec![(spot, "BTC-USDT"), (fut, "LUNA-USDT"), (fut, "OLD-USDT")]
+        );
+        assert_eq!(report.excluded[0].reason.to_string(), "halted (BREAK)");
+        assert_eq!(
+            report.excluded[1].reason,
+            UnavailableReason::DelistsSoon {
+                delist_at: now + HOUR / 2,
+                hours_left: 0.5
+            }
+        );
+        assert_eq!(report.counts()[&("binance_futures", "delists_soon")], 1);
+        assert_eq!(report.scheduled.len(), 1);
+        assert_eq!(report.scheduled[0].delist_at, now + 72 * HOUR);
+    }
+}
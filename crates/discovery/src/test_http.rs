@@ -0,0 +1,111 @@
+//! Minimal HTTP/1.1 stand-in for exchange REST APIs (tests only).
+//!
+//! Routes are matched on the exact request target (path + query). A route can
+//! hold a sequence of responses; each request consumes one and the last one
+//! sticks, which is enough to script retry scenarios.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    pub fn ok(body: impl Into<String>) -> Self {
+        Self {
+            status: 200,
+            body: body.into(),
+        }
+    }
+
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            body: String::new(),
+        }
+    }
+}
+
+type Routes = Arc<Mutex<HashMap<String, Vec<Response>>>>;
+
+pub struct TestServer {
+    addr: SocketAddr,
+    routes: Routes,
+}
+
+impl TestServer {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let routes: Routes = Arc::default();
+
+        let accept_routes = routes.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle(stream, accept_routes.clone()));
+            }
+        });
+
+        Self { addr, routes }
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn route(&self, target: &str, response: Response) {
+        self.route_sequence(target, vec![response]);
+    }
+
+    pub fn route_sequence(&self, target: &str, responses: Vec<Response>) {
+        assert!(!responses.is_empty());
+        self.routes
+            .lock()
+            .unwrap()
+            .insert(target.to_string(), responses);
+    }
+}
+
+async fn handle(mut stream: TcpStream, routes: Routes) {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+        }
+    }
+
+    let request = String::from_utf8_lossy(&buf);
+    let target = request
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or("/")
+        .to_string();
+
+    let response = {
+        let mut routes = routes.lock().unwrap();
+        match routes.get_mut(&target) {
+            Some(seq) if seq.len() > 1 => seq.remove(0),
+            Some(seq) => seq[0].clone(),
+            None => Response::status(404),
+        }
+    };
+
+    let head = format!(
+        "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.body.len()
+    );
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(response.body.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
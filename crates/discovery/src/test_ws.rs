@@ -0,0 +1,47 @@
+//! Minimal WebSocket stand-in for exchange streams (tests only).
+//!
+//! Every connection is sent the same scripted text frames once the client
+//! has subscribed, then held open until the client goes away.
+
+use std::net::SocketAddr;
+
+use futures_util::{SinkExt, StreamExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::Message;
+
+pub struct TestWsServer {
+    addr: SocketAddr,
+}
+
+impl TestWsServer {
+    pub async fn start(frames: Vec<String>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle(stream, frames.clone()));
+            }
+        });
+        Self { addr }
+    }
+
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+}
+
+async fn handle(stream: TcpStream, frames: Vec<String>) {
+    let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    // Wait for the subscription
+    if !matches!(ws.next().await, Some(Ok(Message::Text(_)))) {
+        return;
+    }
+    for frame in frames {
+        if ws.send(Message::Text(frame)).await.is_err() {
+            return;
+        }
+    }
+    while let Some(Ok(_)) = ws.next().await {}
+}
@@ -0,0 +1,1058 @@
+//! WebSocket validation — proves each candidate pair actually streams.
+//!
+//! Every source is validated in parallel; within a source, candidates are
+//! subscribed in batches of `validation_batch_size` (one connection per
+//! batch, sequential, with a pause in between). Frames go through the same
+//! `feeds` parser the production feed uses, so a pair that validates here is
+//! guaranteed to parse there.
+//!
+//! A pair is valid once it delivers a snapshot with 0 < bid ≤ ask. Otherwise
+//! it is classified by what was seen when the batch ended: nothing
+//! (NoResponse), a zero side (ZeroBid), bid > ask (Crossed) or an explicit
+//! subscription error (Rejected). A batch that fails outright (connect error,
+//! closed socket, batch timeout) marks all of its pairs NoResponse, and so
+//! does a source whose parser cannot be built: nothing passes unvalidated.
+//!
+//! While recording (see `capture`) every frame of a batch is saved with its
+//! receive time. A replayed batch feeds the saved frames to the parser in
+//! order, without sockets or timers, and fails the way the recorded one did.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use tokio::time::{sleep_until, Instant};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+use common::config::{AppConfig, DirectionsConfig, ExchangesConfig};
+use common::symbols::{SymbolRecord, SymbolSub, SymbolTable};
+use common::types::{PriceSnapshot, SourceId, NUM_SOURCES};
+use feeds::parser::{create_parser, Frame, FrameKind, Parser, PriceUpdate};
+use feeds::ws::PingTimer;
+
+use crate::capture::{Capture, FrameData, WsCapture, WsFrame};
+use crate::error::DiscoveryError;
+use crate::registry::{retain_paired, Pairing};
+use crate::rest_client::MIN_SOURCES;
+
+#[derive(Debug, Clone)]
+pub struct ValidationConfig {
+    /// Hard cap on one batch, connect and subscribe included.
+    pub batch_timeout: Duration,
+    /// How long a batch collects data at most.
+    pub collect_timeout: Duration,
+    /// End the batch early after this long without a data frame.
+    pub idle_timeout: Duration,
+    /// Pause between batches on the same source.
+    pub batch_pause: Duration,
+    /// Application-level keepalive interval for exchanges that need one.
+    pub ping_interval: Duration,
+}
+
+impl ValidationConfig {
+    pub fn from_config(config: &AppConfig) -> Self {
+        let d = &config.discovery;
+        Self {
+            batch_timeout: Duration::from_secs(d.validation_batch_timeout_sec),
+            collect_timeout: Duration::from_secs(d.validation_timeout_sec),
+            idle_timeout: Duration::from_secs(d.validation_idle_timeout_sec),
+            batch_pause: Duration::from_millis(d.validation_batch_pause_ms),
+            ping_interval: Duration::from_secs(config.ws.ping_interval_sec),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InvalidReason {
+    /// No data frame for the pair before the batch ended.
+    NoResponse,
+    /// Last snapshot had a zero (or missing) bid or ask.
+    ZeroBid,
+    /// Last snapshot had bid > ask.
+    Crossed,
+    /// The exchange refused the subscription.
+    Rejected(String),
+}
+
+impl fmt::Display for InvalidReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidReason::NoResponse => write!(f, "no response"),
+            InvalidReason::ZeroBid => write!(f, "zero bid/ask"),
+            InvalidReason::Crossed => write!(f, "crossed book"),
+            InvalidReason::Rejected(reason) => write!(f, "rejected: {}", reason),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidPair {
+    /// Canonical name, e.g. "BTC-USDT".
+    pub name: String,
+    pub exchange_symbol: String,
+    pub reason: InvalidReason,
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceValidation {
+    pub source: SourceId,
+    /// Candidate pairs listed on this source.
+    pub total: usize,
+    pub valid: usize,
+    pub invalid: Vec<InvalidPair>,
+    pub batches: usize,
+    pub failed_batches: usize,
+    /// Per-unit mid of the last valid snapshot of each valid pair, by
+    /// canonical name. Feeds the ticker-collision check.
+    pub mids: BTreeMap<String, f64>,
+}
+
+impl SourceValidation {
+    /// A source counts as validated unless every one of its batches failed.
+    pub fn is_healthy(&self) -> bool {
+        self.total == 0 || self.failed_batches < self.batches
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub sources: Vec<SourceValidation>,
+}
+
+impl ValidationReport {
+    /// Remove invalid pairs from `candidates`, then drop symbols that no
+    /// longer sit on both sides of a direction.
+    pub fn apply(
+        &self,
+        candidates: &mut BTreeMap<String, SymbolRecord>,
+        directions: &DirectionsConfig,
+        pairing: &Pairing,
+    ) {
+        for sv in &self.sources {
+            let idx = sv.source.index();
+            for pair in &sv.invalid {
+                if let Some(record) = candidates.get_mut(&pair.name) {
+                    record.source_names[idx] = None;
+                    record.min_qty[idx] = None;
+                    record.tick_size[idx] = None;
+                    record.price_multiplier[idx] = 1.0;
+                    record.specs[idx] = Default::default();
+                }
+            }
+        }
+        retain_paired(candidates, directions, pairing);
+    }
+
+    /// Per-unit mid observed for `name` on `source`, if it validated there.
+    pub fn mid(&self, source: SourceId, name: &str) -> Option<f64> {
+        self.sources
+            .iter()
+            .find(|sv| sv.source == source)
+            .and_then(|sv| sv.mids.get(name).copied())
+    }
+
+    pub fn num_invalid(&self) -> usize {
+        self.sources.iter().map(|s| s.invalid.len()).sum()
+    }
+
+    /// Invalid pair counts keyed by (source name, reason kind).
+    pub fn counts(&self) -> BTreeMap<(&'static str, &'static str), usize> {
+        let mut counts = BTreeMap::new();
+        for sv in &self.sources {
+            for pair in &sv.invalid {
+                let kind = match pair.reason {
+                    InvalidReason::NoResponse => "no_response",
+                    InvalidReason::ZeroBid => "zero_bid",
+                    InvalidReason::Crossed => "crossed",
+                    InvalidReason::Rejected(_) => "rejected",
+                };
+                *counts.entry((sv.source.name(), kind)).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+}
+
+/// Temporary table over `candidates` (ids in name order) so parsers can
+/// resolve symbols before real symbol_ids are assigned.
+pub fn candidate_table(candidates: &BTreeMap<String, SymbolRecord>) -> SymbolTable {
+    let records = candidates
+        .values()
+        .enumerate()
+        .map(|(i, record)| SymbolRecord {
+            symbol_id: i as u16,
+            ..record.clone()
+        })
+        .collect();
+    SymbolTable::from_records(records)
+}
+
+pub struct Validator {
+    config: ValidationConfig,
+    capture: Capture,
+}
+
+impl Validator {
+    pub fn new(config: ValidationConfig) -> Self {
+        Self {
+            config,
+            capture: Capture::Live,
+        }
+    }
+
+    pub fn with_capture(mut self, capture: Capture) -> Self {
+        self.capture = capture;
+        self
+    }
+
+    /// Validate every source in parallel.
+    /// Fails with `DiscoveryError::InsufficientValidation` if fewer than
+    /// MIN_SOURCES sources come through healthy.
+    pub async fn validate_all(
+        &self,
+        exchanges: &ExchangesConfig,
+        symbols: &SymbolTable,
+    ) -> Result<ValidationReport> {
+        let runs = SourceId::ALL.map(|source| async move {
+            let Some(entry) = exchanges.entry(source) else {
+                warn!("{}: exchange missing from exchanges config", source.name());
+                return None;
+            };
+            Some(
+                self.validate_source(
+                    source,
+                    entry.ws_url(source),
+                    entry.ws_channel.as_deref(),
+                    entry.validation_batch_size,
+                    symbols,
+                )
+                .await,
+            )
+        });
+        let sources: Vec<SourceValidation> = futures_util::future::join_all(runs)
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+
+        // Sources with no candidates (REST down) count against the total too.
+        let validated = sources
+            .iter()
+            .filter(|s| s.total > 0 && s.is_healthy())
+            .count();
+        if validated < MIN_SOURCES {
+            return Err(DiscoveryError::InsufficientValidation {
+                validated,
+                required: MIN_SOURCES,
+            }
+            .into());
+        }
+        if validated < NUM_SOURCES as usize {
+            warn!(
+                "WS validation degraded: {} sources not validated",
+                NUM_SOURCES as usize - validated
+            );
+        }
+        Ok(ValidationReport { sources })
+    }
+
+    /// Validate the candidates of one source against `url`, on the
+    /// exchange's configured `channel`.
+    pub async fn validate_source(
+        &self,
+        source: SourceId,
+        url: &str,
+        channel: Option<&str>,
+        batch_size: usize,
+        symbols: &SymbolTable,
+    ) -> SourceValidation {
+        let subs = symbols.subscription_list(source);
+        let mut result = SourceValidation {
+            source,
+            total: subs.len(),
+            valid: 0,
+            invalid: Vec::new(),
+            batches: 0,
+            failed_batches: 0,
+            mids: BTreeMap::new(),
+        };
+        if subs.is_empty() {
+            return result;
+        }
+        let mut parser = match create_parser(source, channel) {
+            Ok(Some(parser)) => parser,
+            failed => {
+                // No batches run: the source counts as failed and none of its
+                // pairs gets through unvalidated
+                match failed {
+                    Err(e) => warn!("{}: {:#}", source.name(), e),
+                    _ => warn!("{}: no parser", source.name()),
+                }
+                result.invalid = subs
+                    .iter()
+                    .map(|sub| InvalidPair {
+                        name: symbols.name(sub.symbol_id).to_string(),
+                        exchange_symbol: sub.exchange_name.clone(),
+                        reason: InvalidReason::NoResponse,
+                    })
+                    .collect();
+                return result;
+            }
+        };
+
+        let batches: Vec<&[SymbolSub]> = subs.chunks(batch_size.max(1)).collect();
+        result.batches = batches.len();
+        for (i, batch) in batches.iter().enumerate() {
+            if i > 0 && !self.capture.is_replay() {
+                tokio::time::sleep(self.config.batch_pause).await;
+            }
+            debug!(
+                "{}: batch {}/{} ({} symbols)",
+                source.name(),
+                i + 1,
+                batches.len(),
+                batch.len()
+            );
+            let outcome = self
+                .run_batch(i, url, parser.as_mut(), batch, symbols)
+                .await;
+
+            match outcome {
+                Ok(states) => {
+                    for sub in batch.iter() {
+                        let state = states.get(&sub.symbol_id);
+                        match state.and_then(PairState::invalid_reason) {
+                            None => {
+                                result.valid += 1;
+                                if let Some(snapshot) = state.and_then(|s| s.last_valid) {
+                                    result.mids.insert(
+                                        symbols.name(sub.symbol_id).to_string(),
+                                        snapshot.per_unit(sub.price_multiplier).mid(),
+                                    );
+                                }
+                            }
+                            Some(reason) => result.invalid.push(InvalidPair {
+                                name: symbols.name(sub.symbol_id).to_string(),
+                                exchange_symbol: sub.exchange_name.clone(),
+                                reason,
+                            }),
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        "{}: batch {}/{} failed: {:#}",
+                        source.name(),
+                        i + 1,
+                        batches.len(),
+                        e
+                    );
+                    result.failed_batches += 1;
+                    result.invalid.extend(batch.iter().map(|sub| InvalidPair {
+                        name: symbols.name(sub.symbol_id).to_string(),
+                        exchange_symbol: sub.exchange_name.clone(),
+                        reason: InvalidReason::NoResponse,
+                    }));
+                }
+            }
+        }
+
+        info!(
+            "{}: {}/{} valid, {} failed batches",
+            source.name(),
+            result.valid,
+            result.total,
+            result.failed_batches
+        );
+        result
+    }
+
+    /// Batch `index` of `parser.source()`: live, recorded or replayed.
+    async fn run_batch(
+        &self,
+        index: usize,
+        url: &str,
+        parser: &mut dyn Parser,
+        batch: &[SymbolSub],
+        symbols: &SymbolTable,
+    ) -> Result<HashMap<u16, PairState>> {
+        let source = parser.source();
+        if self.capture.is_replay() {
+            let recorded = self.capture.load_ws(source, index)?;
+            return replay_batch(&recorded, parser, batch, symbols);
+        }
+
+        let mut frames = Vec::new();
+        let recording = self.capture.is_recording();
+        let outcome = tokio::time::timeout(
+            self.config.batch_timeout,
+            self.validate_batch(
+                url,
+                parser,
+                batch,
+                symbols,
+                recording.then_some(&mut frames),
+            ),
+        )
+        .await
+        .unwrap_or_else(|_| Err(anyhow::anyhow!("batch timed out")));
+
+        if recording {
+            let recorded = WsCapture {
+                symbols: batch.iter().map(|s| s.exchange_name.clone()).collect(),
+                frames,
+                error: outcome.as_ref().err().map(|e| format!("{:#}", e)),
+            };
+            if let Err(e) = self.capture.save_ws(source, index, &recorded) {
+                warn!("{:#}", e);
+            }
+        }
+        outcome
+    }
+
+    /// One connection: subscribe to `batch`, collect until every pair is
+    /// settled, the collect window closes or the stream goes idle. Frames
+    /// are appended to `record` when given.
+    async fn validate_batch(
+        &self,
+        url: &str,
+        parser: &mut dyn Parser,
+        batch: &[SymbolSub],
+        symbols: &SymbolTable,
+        mut record: Option<&mut Vec<WsFrame>>,
+    ) -> Result<HashMap<u16, PairState>> {
+        let (mut ws, _) = tokio_tungstenite::connect_async(url)
+            .await
+            .with_context(|| format!("failed to connect to {}", url))?;
+        for msg in parser.subscribe_messages(batch) {
+            ws.send(Message::Text(msg))
+                .await
+                .context("subscribe failed")?;
+        }
+
+        let mut state = BatchState::new(batch);
+        let collect_deadline = Instant::now() + self.config.collect_timeout;
+        let mut last_data = Instant::now();
+        let mut ping = PingTimer::new(self.config.ping_interval);
+
+        loop {
+            let deadline = collect_deadline.min(last_data + self.config.idle_timeout);
+            let msg = tokio::select! {
+                _ = sleep_until(deadline) => break,
+                _ = ping.tick() => {
+                    if let Some(ping) = parser.ping_message() {
+                        ws.send(Message::Text(ping)).await.context("ping failed")?;
+                    }
+                    continue;
+                }
+                msg = ws.next() => msg,
+            };
+            let msg = msg.context("connection closed")?.context("read failed")?;
+            let frame = match &msg {
+                Message::Text(text) => Frame::Text(text),
+                Message::Binary(data) => Frame::Binary(data),
+                Message::Close(_) => anyhow::bail!("closed by server"),
+                _ => continue,
+            };
+
+            let received_at = unix_now_us();
+            if let Some(frames) = record.as_deref_mut() {
+                frames.push(WsFrame {
+                    received_at,
+                    data: match frame {
+                        Frame::Text(text) => FrameData::Text(text.to_string()),
+                        Frame::Binary(data) => FrameData::Binary(data.to_vec()),
+                    },
+                });
+            }
+            match state.handle(parser, frame, symbols, received_at) {
+                Some(FrameKind::Data) => last_data = Instant::now(),
+                Some(FrameKind::Reply(text)) => {
+                    ws.send(Message::Text(text)).await.context("reply failed")?;
+                }
+                _ => {}
+            }
+
+            if state.is_settled() {
+                break;
+            }
+        }
+        let _ = ws.close(None).await;
+        Ok(state.finish())
+    }
+}
+
+/// A recorded batch, frame by frame. Replies and pings have nowhere to go
+/// and are dropped.
+fn replay_batch(
+    recorded: &WsCapture,
+    parser: &mut dyn Parser,
+    batch: &[SymbolSub],
+    symbols: &SymbolTable,
+) -> Result<HashMap<u16, PairState>> {
+    let subscribed: Vec<&str> = batch.iter().map(|s| s.exchange_name.as_str()).collect();
+    if recorded.symbols != subscribed {
+        anyhow::bail!("recorded batch subscribed to other symbols");
+    }
+    let mut state = BatchState::new(batch);
+    for frame in &recorded.frames {
+        state.handle(parser, frame.frame(), symbols, frame.received_at);
+        if state.is_settled() {
+            break;
+        }
+    }
+    match &recorded.error {
+        Some(error) => Err(anyhow::anyhow!("{}", error)),
+        None => Ok(state.finish()),
+    }
+}
+
+/// What a batch has seen so far, live or replayed.
+struct BatchState {
+    states: HashMap<u16, PairState>,
+    /// A refusal that named no symbol.
+    rejection: Option<String>,
+    updates: Vec<PriceUpdate>,
+}
+
+impl BatchState {
+    fn new(batch: &[SymbolSub]) -> Self {
+        Self {
+            states: batch
+                .iter()
+                .map(|s| (s.symbol_id, PairState::default()))
+                .collect(),
+            rejection: None,
+            updates: Vec::new(),
+        }
+    }
+
+    /// Parse one frame and record what it says about the batch's pairs.
+    /// Returns the frame kind, or None if it did not parse.
+    fn handle(
+        &mut self,
+        parser: &mut dyn Parser,
+        frame: Frame<'_>,
+        symbols: &SymbolTable,
+        received_at: u64,
+    ) -> Option<FrameKind> {
+        let source = parser.source();
+        self.updates.clear();
+        let kind = match parser.parse(frame, symbols, received_at, &mut self.updates) {
+            Ok(kind) => kind,
+            Err(e) => {
+                debug!("{}: unparsed frame: {:#}", source.name(), e);
+                return None;
+            }
+        };
+        match &kind {
+            FrameKind::Data => {
+                for u in &self.updates {
+                    if let Some(state) = self.states.get_mut(&u.symbol_id) {
+                        state.observe(u.snapshot);
+                    }
+                }
+            }
+            FrameKind::Rejected {
+                symbols: names,
+                reason,
+            } => {
+                debug!("{}: subscription rejected: {}", source.name(), reason);
+                if names.is_empty() {
+                    self.rejection = Some(reason.clone());
+                }
+                for name in names {
+                    let state = symbols
+                        .resolve(source, name)
+                        .and_then(|id| self.states.get_mut(&id));
+                    if let Some(state) = state {
+                        state.rejected = Some(reason.clone());
+                    }
+                }
+            }
+            FrameKind::Reply(_) | FrameKind::Ack | FrameKind::Control => {}
+        }
+        Some(kind)
+    }
+
+    fn is_settled(&self) -> bool {
+        self.states.values().all(PairState::is_settled)
+    }
+
+    fn finish(mut self) -> HashMap<u16, PairState> {
+        // A refusal that names no symbol covers every pair that stayed silent.
+        if let Some(reason) = self.rejection {
+            for state in self.states.values_mut() {
+                if state.last.is_none() && state.rejected.is_none() {
+                    state.rejected = Some(reason.clone());
+                }
+            }
+        }
+        self.states
+    }
+}
+
+/// What one pair did during its batch.
+#[derive(Debug, Clone, Default)]
+struct PairState {
+    last: Option<PriceSnapshot>,
+    last_valid: Option<PriceSnapshot>,
+    rejected: Option<String>,
+}
+
+impl PairState {
+    fn observe(&mut self, snapshot: PriceSnapshot) {
+        if is_valid_quote(&snapshot) {
+            self.last_valid = Some(snapshot);
+        }
+        self.last = Some(snapshot);
+    }
+
+    fn is_settled(&self) -> bool {
+        self.last_valid.is_some() || self.rejected.is_some()
+    }
+
+    fn invalid_reason(&self) -> Option<InvalidReason> {
+        if self.last_valid.is_some() {
+            return None;
+        }
+        if let Some(reason) = &self.rejected {
+            return Some(InvalidReason::Rejected(reason.clone()));
+        }
+        match self.last {
+            None => Some(InvalidReason::NoResponse),
+            Some(s) if s.best_bid <= 0.0 || s.best_ask <= 0.0 => Some(InvalidReason::ZeroBid),
+            Some(_) => Some(InvalidReason::Crossed),
+        }
+    }
+}
+
+fn is_valid_quote(s: &PriceSnapshot) -> bool {
+    s.best_bid > 0.0 && s.best_ask > 0.0 && s.best_bid <= s.best_ask
+}
+
+fn unix_now_us() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::config::{DirectionConfigEntry, ExchangeEntry};
+
+    use crate::test_ws::TestWsServer;
+
+    fn record(name: &str, sources: &[(SourceId, &str)]) -> SymbolRecord {
+        sources
+            .iter()
+            .fold(SymbolRecord::new(0, name), |r, &(source, symbol)| {
+                r.with_source(source, symbol)
+            })
+    }
+
+    fn fast_config() -> ValidationConfig {
+        ValidationConfig {
+            batch_timeout: Duration::from_secs(10),
+            collect_timeout: Duration::from_secs(5),
+            idle_timeout: Duration::from_millis(300),
+            batch_pause: Duration::from_millis(10),
+            ping_interval: Duration::from_secs(20),
+        }
+    }
+
+    /// Candidates of a single source, one per (name, exchange symbol).
+    fn single_source_table(source: SourceId, pairs: &[(&str, &str)]) -> SymbolTable {
+        let candidates: BTreeMap<_, _> = pairs
+            .iter()
+            .map(|(name, symbol)| (name.to_string(), record(name, &[(source, symbol)])))
+            .collect();
+        candidate_table(&candidates)
+    }
+
+    fn binance_quote(symbol: &str, bid: &str, ask: &str) -> String {
+        format!(
+            r#"{{"stream":"{}@bookTicker","data":{{"e":"bookTicker","u":1,"s":"{}","b":"{}","B":"1","a":"{}","A":"1","T":1,"E":1}}}}"#,
+            symbol.to_lowercase(),
+            symbol,
+            bid,
+            ask
+        )
+    }
+
+    fn reasons(result: &SourceValidation) -> BTreeMap<&str, &InvalidReason> {
+        result
+            .invalid
+            .iter()
+            .map(|p| (p.name.as_str(), &p.reason))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_validate_source_and_apply() {
+        let spot = SourceId::BinanceSpot;
+        let futures = SourceId::BinanceFutures;
+        let mut candidates = BTreeMap::new();
+        candidates.insert(
+            "BTC-USDT".to_string(),
+            record("BTC-USDT", &[(spot, "BTCUSDT"), (futures, "BTCUSDT")]),
+        );
+        candidates.insert(
+            "DEAD-USDT".to_string(),
+            record("DEAD-USDT", &[(spot, "DEADUSDT"), (futures, "DEADUSDT")]),
+        );
+        let table = candidate_table(&candidates);
+
+        // BTC streams, DEAD has an empty bid
+        let server = TestWsServer::start(vec![
+            r#"{"result":null,"id":1}"#.to_string(),
+            r#"{"stream":"btcusdt@bookTicker","data":{"e":"bookTicker","u":1,"s":"BTCUSDT","b":"66880.10","B":"1","a":"66880.30","A":"1","T":1,"E":1}}"#.to_string(),
+            r#"{"stream":"deadusdt@bookTicker","data":{"e":"bookTicker","u":1,"s":"DEADUSDT","b":"0","B":"0","a":"0.5","A":"1","T":1,"E":1}}"#.to_string(),
+        ])
+        .await;
+        let validator = Validator::new(fast_config());
+        let result = validator
+            .validate_source(futures, &server.url(), None, 200, &table)
+            .await;
+        assert_eq!((result.total, result.valid, result.batches), (2, 1, 1));
+        assert_eq!(result.invalid.len(), 1);
+        assert_eq!(result.invalid[0].name, "DEAD-USDT");
+        assert_eq!(result.invalid[0].reason, InvalidReason::ZeroBid);
+        assert!((result.mids["BTC-USDT"] - 66880.2).abs() < 1e-6);
+
+        // No candidates on MEXC spot: nothing to validate
+        let empty = validator
+            .validate_source(SourceId::MexcSpot, "ws://127.0.0.1:1", None, 30, &table)
+            .await;
+        assert_eq!(empty.total, 0);
+
+        let report = ValidationReport {
+            sources: vec![result],
+        };
+        let directions = DirectionsConfig {
+            direction: vec![DirectionConfigEntry {
+                id: 0,
+                spot_source: spot as u8,
+                futures_source: futures as u8,
+                name: "binance_spot_binance_futures".to_string(),
+            }],
+        };
+        report.apply(&mut candidates, &directions, &Pairing::default());
+        assert!(candidates.contains_key("BTC-USDT"));
+        assert!(!candidates.contains_key("DEAD-USDT"));
+        assert_eq!(report.counts()[&("binance_futures", "zero_bid")], 1);
+    }
+
+    #[tokio::test]
+    async fn test_crossed_book_is_invalid() {
+        let source = SourceId::BinanceSpot;
+        let table =
+            single_source_table(source, &[("BTC-USDT", "BTCUSDT"), ("ODD-USDT", "ODDUSDT")]);
+        let server = TestWsServer::start(vec![
+            binance_quote("BTCUSDT", "66880.10", "66880.30"),
+            binance_quote("ODDUSDT", "1.2", "1.1"),
+        ])
+        .await;
+        // Keepalives off must not stop the batch
+        let config = ValidationConfig {
+            ping_interval: Duration::ZERO,
+            ..fast_config()
+        };
+        let result = Validator::new(config)
+            .validate_source(source, &server.url(), None, 50, &table)
+            .await;
+        assert_eq!((result.valid, result.failed_batches), (1, 0));
+        assert_eq!(reasons(&result)["ODD-USDT"], &InvalidReason::Crossed);
+        assert!(!result.mids.contains_key("ODD-USDT"));
+    }
+
+    #[tokio::test]
+    async fn test_source_without_parser_fails() {
+        let source = SourceId::OkxSpot;
+        let table = single_source_table(
+            source,
+            &[("BTC-USDT", "BTC-USDT"), ("ETH-USDT", "ETH-USDT")],
+        );
+        // Never dialed: the channel is refused before any batch runs
+        let result = Validator::new(fast_config())
+            .validate_source(source, "ws://127.0.0.1:1", Some("trades"), 50, &table)
+            .await;
+        assert_eq!((result.valid, result.batches), (0, 0));
+        assert_eq!(reasons(&result)["BTC-USDT"], &InvalidReason::NoResponse);
+        assert_eq!(result.invalid.len(), 2);
+        assert!(!result.is_healthy());
+    }
+
+    #[tokio::test]
+    async fn test_idle_stream_leaves_silent_pairs_without_response() {
+        let source = SourceId::BinanceFutures;
+        let table = single_source_table(
+            source,
+            &[("BTC-USDT", "BTCUSDT"), ("GONE-USDT", "GONEUSDT")],
+        );
+        let server =
+            TestWsServer::start(vec![binance_quote("BTCUSDT", "66880.10", "66880.30")]).await;
+        let started = std::time::Instant::now();
+        let result = Validator::new(fast_config())
+            .validate_source(source, &server.url(), None, 50, &table)
+            .await;
+        // The idle timeout ends the batch long before the collect window
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(
+            (result.valid, result.batches, result.failed_batches),
+            (1, 1, 0)
+        );
+        assert_eq!(reasons(&result)["GONE-USDT"], &InvalidReason::NoResponse);
+        assert!(result.is_healthy());
+    }
+
+    #[tokio::test]
+    async fn test_batch_wide_rejection_covers_silent_pairs() {
+        let source = SourceId::BinanceSpot;
+        let table =
+            single_source_table(source, &[("BTC-USDT", "BTCUSDT"), ("BAD-USDT", "BADUSDT")]);
+        let server = TestWsServer::start(vec![
+            binance_quote("BTCUSDT", "66880.10", "66880.30"),
+            r#"{"error":{"code":2,"msg":"Invalid request: invalid stream name"},"id":1}"#
+                .to_string(),
+        ])
+        .await;
+        let result = Validator::new(fast_config())
+            .validate_source(source, &server.url(), None, 50, &table)
+            .await;
+        assert_eq!(result.valid, 1);
+        assert_eq!(result.invalid.len(), 1);
+        assert_eq!(
+            reasons(&result)["BAD-USDT"],
+            &InvalidReason::Rejected("Invalid request: invalid stream name".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_per_symbol_rejection() {
+        let source = SourceId::OkxSpot;
+        let table = single_source_table(
+            source,
+            &[
+                ("BTC-USDT", "BTC-USDT"),
+                ("XYZ-USDT", "XYZ-USDT"),
+                ("QUIET-USDT", "QUIET-USDT"),
+            ],
+        );
+        let reason = "Wrong URL or channel:tickers,instId:XYZ-USDT doesn't exist.";
+        let server = TestWsServer::start(vec![
+            format!(r#"{{"event":"error","code":"60018","msg":"{}","connId":"a4d3ae55"}}"#, reason),
+            r#"{"arg":{"channel":"tickers","instId":"BTC-USDT"},"data":[{"instType":"SPOT","instId":"BTC-USDT","askPx":"66880.2","askSz":"0.61","bidPx":"66880.1","bidSz":"1.37","ts":"1718000000123"}]}"#.to_string(),
+        ])
+        .await;
+        let result = Validator::new(fast_config())
+            .validate_source(source, &server.url(), None, 50, &table)
+            .await;
+        assert_eq!(result.valid, 1);
+        let reasons = reasons(&result);
+        assert_eq!(
+            reasons["XYZ-USDT"],
+            &InvalidReason::Rejected(reason.to_string())
+        );
+        // A named rejection says nothing about the other pairs
+        assert_eq!(reasons["QUIET-USDT"], &InvalidReason::NoResponse);
+    }
+
+    #[tokio::test]
+    async fn test_batch_timeout_fails_the_batch() {
+        let source = SourceId::BinanceSpot;
+        let table =
+            single_source_table(source, &[("BTC-USDT", "BTCUSDT"), ("ETH-USDT", "ETHUSDT")]);
+        // Connected and subscribed, but nothing ever arrives
+        let server = TestWsServer::start(Vec::new()).await;
+        let config = ValidationConfig {
+            batch_timeout: Duration::from_millis(100),
+            ..fast_config()
+        };
+        let result = Validator::new(config)
+            .validate_source(source, &server.url(), None, 50, &table)
+            .await;
+        assert_eq!(
+            (result.valid, result.batches, result.failed_batches),
+            (0, 1, 1)
+        );
+        assert!(result
+            .invalid
+            .iter()
+            .all(|p| p.reason == InvalidReason::NoResponse));
+        assert!(!result.is_healthy());
+    }
+
+    #[tokio::test]
+    async fn test_connection_failure_fails_every_batch() {
+        let source = SourceId::BinanceSpot;
+        let table = single_source_table(
+            source,
+            &[
+                ("BTC-USDT", "BTCUSDT"),
+                ("ETH-USDT", "ETHUSDT"),
+                ("SOL-USDT", "SOLUSDT"),
+            ],
+        );
+        let result = Validator::new(fast_config())
+            .validate_source(source, "ws://127.0.0.1:1", None, 2, &table)
+            .await;
+        assert_eq!((result.batches, result.failed_batches), (2, 2));
+        assert_eq!(result.invalid.len(), 3);
+        assert!(result
+            .invalid
+            .iter()
+            .all(|p| p.reason == InvalidReason::NoResponse));
+        assert!(!result.is_healthy());
+    }
+
+    #[tokio::test]
+    async fn test_validate_all_needs_min_sources() {
+        let candidates: BTreeMap<_, _> = SourceId::ALL
+            .iter()
+            .map(|&source| {
+                let name = format!("{}-USDT", source.name().to_uppercase().replace('_', ""));
+                let symbol = name.replace('-', "");
+                (name.clone(), record(&name, &[(source, symbol.as_str())]))
+            })
+            .collect();
+        let table = candidate_table(&candidates);
+
+        // A silent server keeps a source healthy (its pairs idle out), a
+        // dead address fails it.
+        let silent = TestWsServer::start(Vec::new()).await;
+        let dead = "ws://127.0.0.1:1".to_string();
+        let exchanges = |dead_sources: &[SourceId]| ExchangesConfig {
+            exchange: ["binance", "bybit", "mexc", "okx"]
+                .iter()
+                .map(|&name| {
+                    let url = |source: SourceId| {
+                        if dead_sources.contains(&source) {
+                            dead.clone()
+                        } else {
+                            silent.url()
+                        }
+                    };
+                    let spot = SourceId::ALL
+                        .into_iter()
+                        .find(|s| s.exchange() == name && s.is_spot())
+                        .unwrap();
+                    let futures = SourceId::ALL
+                        .into_iter()
+                        .find(|s| s.exchange() == name && !s.is_spot())
+                        .unwrap();
+                    ExchangeEntry {
+                        name: name.to_string(),
+                        rest_spot: String::new(),
+                        rest_futures: String::new(),
+                        ws_spot: url(spot),
+                        ws_futures: url(futures),
+                        ws_spot_standby: None,
+                        ws_futures_standby: None,
+                        max_ws_subscriptions: 200,
+                        validation_batch_size: 50,
+                        instruments_path_spot: String::new(),
+                        instruments_path_futures: String::new(),
+                        ticker_path_spot: String::new(),
+                        ticker_path_futures: String::new(),
+                        ws_channel: None,
+                    }
+                })
+                .collect(),
+        };
+        let validator = Validator::new(fast_config());
+
+        let two_down = exchanges(&[SourceId::MexcSpot, SourceId::MexcFutures]);
+        let report = validator.validate_all(&two_down, &table).await.unwrap();
+        assert_eq!(report.sources.len(), NUM_SOURCES as usize);
+        assert_eq!(report.sources.iter().filter(|s| s.is_healthy()).count(), 6);
+
+        let three_down = exchanges(&[
+            SourceId::MexcSpot,
+            SourceId::MexcFutures,
+            SourceId::OkxFutures,
+        ]);
+        let err = validator
+            .validate_all(&three_down, &table)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<DiscoveryError>(),
+            Some(DiscoveryError::InsufficientValidation {
+                validated: 5,
+                required: 6
+            })
+        ));
+    }
+
+    fn recorded(symbols: &[&str], frames: &[String], error: Option<&str>) -> WsCapture {
+        WsCapture {
+            symbols: symbols.iter().map(|s| s.to_string()).collect(),
+            frames: frames
+                .iter()
+                .enumerate()
+                .map(|(i, text)| WsFrame {
+                    received_at: i as u64 + 1,
+                    data: FrameData::Text(text.clone()),
+                })
+                .collect(),
+            error: error.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_replay_batch() {
+        let source = SourceId::BinanceSpot;
+        let table =
+            single_source_table(source, &[("BTC-USDT", "BTCUSDT"), ("ETH-USDT", "ETHUSDT")]);
+        let batch = table.subscription_list(source);
+        let mut parser = create_parser(source, None).unwrap().unwrap();
+        let btc = table.resolve(source, "BTCUSDT").unwrap();
+        let eth = table.resolve(source, "ETHUSDT").unwrap();
+
+        // Frames after the batch settled are not looked at
+        let frames = [
+            binance_quote("BTCUSDT", "66880.10", "66880.30"),
+            binance_quote("ETHUSDT", "3513.39", "3513.40"),
+            binance_quote("BTCUSDT", "1", "2"),
+        ];
+        let states = replay_batch(
+            &recorded(&["BTCUSDT", "ETHUSDT"], &frames, None),
+            parser.as_mut(),
+            &batch,
+            &table,
+        )
+        .unwrap();
+        assert_eq!(states[&btc].last_valid.unwrap().best_bid, 66880.10);
+        assert_eq!(states[&eth].last_valid.unwrap().updated_at, 2);
+
+        // A recorded failure fails the replayed batch the same way
+        let err = replay_batch(
+            &recorded(
+                &["BTCUSDT", "ETHUSDT"],
+                &frames[..1],
+                Some("batch timed out"),
+            ),
+            parser.as_mut(),
+            &batch,
+            &table,
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "batch timed out");
+
+        // A capture of other candidates cannot stand in for this batch
+        let err = replay_batch(
+            &recorded(&["BTCUSDT"], &frames, None),
+            parser.as_mut(),
+            &batch,
+            &table,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("other symbols"));
+    }
+}
@@ -0,0 +1,124 @@
+//! Runtime ticker-collision guard.
+//!
+//! Discovery quarantines pairs whose venues quoted wildly different prices
+//! during validation, but a collision can also appear between two discovery
+//! runs (a venue relists a ticker for a new token). Before a spread is
+//! evaluated, the per-unit mids of both legs are compared; above
+//! `spread.collision_max_price_ratio` the pair is quarantined for the life of
+//! the guard. Runtime quarantine is never lifted here: releasing a pair is
+//! discovery's call, and a new generation means a new guard.
+
+use common::types::{price_ratio, PriceSnapshot, MAX_DIRECTIONS};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    /// Prices agree; evaluate the spread.
+    Clear,
+    /// Quarantined by this check; log it once.
+    Flagged { ratio: f64 },
+    /// Quarantined earlier.
+    Quarantined,
+}
+
+pub struct CollisionGuard {
+    max_ratio: f64,
+    num_symbols: usize,
+    /// `direction_id * num_symbols + symbol_id`
+    quarantined: Vec<bool>,
+}
+
+impl CollisionGuard {
+    pub fn new(max_ratio: f64, num_symbols: u16) -> Self {
+        Self {
+            max_ratio,
+            num_symbols: num_symbols as usize,
+            quarantined: vec![false; MAX_DIRECTIONS as usize * num_symbols as usize],
+        }
+    }
+
+    /// Check one pair given both legs in per-unit prices. Invalid snapshots
+    /// are not evidence either way and pass as `Clear` unless the pair is
+    /// already quarantined.
+    pub fn check(
+        &mut self,
+        direction_id: u8,
+        symbol_id: u16,
+        spot: &PriceSnapshot,
+        futures: &PriceSnapshot,
+    ) -> Verdict {
+        let Some(i) = self.slot(direction_id, symbol_id) else {
+            return Verdict::Clear;
+        };
+        if self.quarantined[i] {
+            return Verdict::Quarantined;
+        }
+        if !spot.is_valid() || !futures.is_valid() {
+            return Verdict::Clear;
+        }
+        let ratio = price_ratio(spot.mid(), futures.mid());
+        if ratio > self.max_ratio {
+            self.quarantined[i] = true;
+            return Verdict::Flagged { ratio };
+        }
+        Verdict::Clear
+    }
+
+    pub fn is_quarantined(&self, direction_id: u8, symbol_id: u16) -> bool {
+        self.slot(direction_id, symbol_id)
+            .is_some_and(|i| self.quarantined[i])
+    }
+
+    pub fn num_quarantined(&self) -> usize {
+        self.quarantined.iter().filter(|q| **q).count()
+    }
+
+    fn slot(&self, direction_id: u8, symbol_id: u16) -> Option<usize> {
+        if direction_id >= MAX_DIRECTIONS || symbol_id as usize >= self.num_symbols {
+            return None;
+        }
+        Some(direction_id as usize * self.num_symbols + symbol_id as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snap(bid: f64, ask: f64) -> PriceSnapshot {
+        PriceSnapshot {
+            best_bid: bid,
+            best_ask: ask,
+            updated_at: 1,
+        }
+    }
+
+    #[test]
+    fn test_collision_guard() {
+        let mut guard = CollisionGuard::new(1.5, 4);
+        let spot = snap(100.0, 100.2);
+
+        assert_eq!(
+            guard.check(0, 1, &spot, &snap(101.0, 101.2)),
+            Verdict::Clear
+        );
+        // Empty book on one leg says nothing about the token
+        assert_eq!(guard.check(0, 1, &spot, &snap(0.0, 0.0)), Verdict::Clear);
+
+        let Verdict::Flagged { ratio } = guard.check(0, 2, &spot, &snap(0.5, 0.5)) else {
+            panic!("expected a collision");
+        };
+        assert!((ratio - 200.2).abs() < 1e-9);
+        // Sticky, and only for that direction
+        assert_eq!(guard.check(0, 2, &spot, &spot), Verdict::Quarantined);
+        assert!(guard.is_quarantined(0, 2));
+        assert!(!guard.is_quarantined(1, 2));
+        assert_eq!(guard.num_quarantined(), 1);
+
+        // Out of range IDs are ignored
+        assert_eq!(
+            guard.check(MAX_DIRECTIONS, 0, &spot, &snap(1.0, 1.0)),
+            Verdict::Clear
+        );
+        assert_eq!(guard.check(0, 9, &spot, &snap(1.0, 1.0)), Verdict::Clear);
+    }
+}
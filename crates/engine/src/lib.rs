@@ -1 +1,3 @@
-
+pub mod collision;
+pub mod listings;
+pub mod quotes;
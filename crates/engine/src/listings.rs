@@ -0,0 +1,102 @@
+//! NewListing events.
+//!
+//! Discovery writes the pairs that just listed on both sources of a
+//! direction to generated/listings.json. The engine is the RingBuffer's only
+//! producer, so it is the engine that announces them: once per loaded
+//! generation, one NewListing event per pair, ahead of any spread signal on
+//! it. The tracker uses them to log listing-time spreads separately. An
+//! engine restart on the same generation announces them again; consumers
+//! key them by (direction_id, symbol_id).
+
+use common::listings::{ListingRecord, Listings};
+use common::types::{Event, EventHeader, EventType, ListingPayload};
+use shm::ring_buffer::RingBuffer;
+
+/// The NewListing event for `listing`.
+pub fn listing_event(listing: &ListingRecord, sequence: u64, now_us: u64) -> Event {
+    let mut event = Event {
+        header: EventHeader {
+            timestamp: now_us,
+            sequence,
+            event_type: EventType::NewListing as u16,
+            source_proc: 0,
+            _reserved: 0,
+            payload_len: 0,
+            _reserved2: [0; 2],
+        },
+        payload: [0u8; 40],
+    };
+    ListingPayload {
+        symbol_id: listing.symbol_id,
+        direction_id: listing.direction_id,
+        spot_source: listing.spot_source,
+        futures_source: listing.futures_source,
+        _pad: [0; 3],
+        detected_at: listing.detected_at,
+        listed_at: listing.listed_at.unwrap_or(0),
+    }
+    .write_to_event(&mut event);
+    event
+}
+
+/// Push one event per listing, numbered from `*next_sequence` on. Stops at
+/// a full buffer; returns how many were pushed.
+pub fn emit(
+    ring: &mut RingBuffer,
+    listings: &Listings,
+    next_sequence: &mut u64,
+    now_us: u64,
+) -> usize {
+    let mut pushed = 0;
+    for listing in &listings.listings {
+        if !ring.push(&listing_event(listing, *next_sequence, now_us)) {
+            break;
+        }
+        *next_sequence += 1;
+        pushed += 1;
+    }
+    pushed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::types::SignalPayload;
+    use shm::mmap;
+
+    #[test]
+    fn test_emit_listing_events() {
+        let name = "test-engine-listings";
+        let _ = mmap::remove_shm(name);
+        let mut ring = RingBuffer::create(name).unwrap();
+
+        let listings = Listings {
+            listings: vec![ListingRecord {
+                direction_id: 4,
+                symbol_id: 1234,
+                name: "XYZ-USDT".to_string(),
+                spot_source: 2,
+                futures_source: 1,
+                detected_at: 1_700_000_000_000,
+                listed_at: None,
+            }],
+        };
+        let mut seq = 10;
+        assert_eq!(emit(&mut ring, &listings, &mut seq, 99), 1);
+        assert_eq!(seq, 11);
+
+        let event = ring.pop().unwrap();
+        assert_eq!(event.header.sequence, 10);
+        assert_eq!(
+            EventType::from_u16(event.header.event_type),
+            Some(EventType::NewListing)
+        );
+        assert!(SignalPayload::from_event(&event).is_none());
+        let payload = ListingPayload::from_event(&event).unwrap();
+        assert_eq!((payload.symbol_id, payload.direction_id), (1234, 4));
+        assert_eq!(payload.detected_at, 1_700_000_000_000);
+        assert_eq!(payload.listed_at, 0);
+
+        mmap::remove_shm(name).unwrap();
+    }
+}
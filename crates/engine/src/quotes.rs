@@ -0,0 +1,207 @@
+//! Quote conversion for cross-quote comparison.
+//!
+//! Symbols are per quote ("SOL-USDC", "SOL-USDT") and a DirectionRecord pairs
+//! each symbol with itself, so directions compare within one quote. With
+//! `spread.cross_quote`, a base's spot listing in one quote is also compared
+//! with its perp in another (`cross_pairs`). Both legs are first converted
+//! into the reference quote (first of `discovery.quote_filter`) through the
+//! live rate of the "<QUOTE>-<REFERENCE>" symbol, read from PriceStore like
+//! any other price. Without a fresh rate there is no comparison.
+
+use std::collections::HashMap;
+
+use common::directions::DirectionRecord;
+use common::symbols::{rate_symbol, split_name, SymbolTable};
+use common::types::{PriceSnapshot, SourceId};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Rate {
+    /// Already in the reference quote.
+    Reference,
+    /// Through the rate symbol with this symbol_id.
+    Via(u16),
+    /// No rate symbol in this generation.
+    Unavailable,
+}
+
+pub struct QuoteConverter {
+    reference: String,
+    /// Per symbol_id.
+    rates: Vec<Rate>,
+    /// Sources listing each rate symbol.
+    rate_sources: HashMap<u16, Vec<SourceId>>,
+}
+
+impl QuoteConverter {
+    pub fn new(symbols: &SymbolTable, reference: &str) -> Self {
+        let mut rate_sources = HashMap::new();
+        let rates = symbols
+            .records
+            .iter()
+            .map(|r| {
+                let Some((_, quote)) = split_name(&r.name) else {
+                    return Rate::Unavailable;
+                };
+                if quote == reference {
+                    return Rate::Reference;
+                }
+                let rate_name = rate_symbol(quote, reference);
+                let Some(rate) = symbols.records.iter().find(|c| c.name == rate_name) else {
+                    return Rate::Unavailable;
+                };
+                rate_sources.entry(rate.symbol_id).or_insert_with(|| {
+                    SourceId::ALL
+                        .into_iter()
+                        .filter(|s| rate.source_names[s.index()].is_some())
+                        .collect()
+                });
+                Rate::Via(rate.symbol_id)
+            })
+            .collect();
+        Self {
+            reference: reference.to_string(),
+            rates,
+            rate_sources,
+        }
+    }
+
+    pub fn reference(&self) -> &str {
+        &self.reference
+    }
+
+    /// Reference-quote units per unit of `symbol_id`'s quote: 1 for the
+    /// reference itself, else the mid of the freshest valid rate snapshot no
+    /// older than `max_age_us`. `read` is `PriceStore::read`.
+    pub fn rate<F>(&self, symbol_id: u16, now_us: u64, max_age_us: u64, read: F) -> Option<f64>
+    where
+        F: Fn(u16, u8) -> Option<PriceSnapshot>,
+    {
+        match self.rates.get(symbol_id as usize)? {
+            Rate::Reference => Some(1.0),
+            Rate::Unavailable => None,
+            Rate::Via(rate_id) => self.rate_sources[rate_id]
+                .iter()
+                .filter_map(|s| read(*rate_id, *s as u8))
+                .filter(|p| p.is_valid() && now_us.saturating_sub(p.updated_at) <= max_age_us)
+                .max_by_key(|p| p.updated_at)
+                .map(|p| p.mid()),
+        }
+    }
+
+    /// `snapshot` priced in the reference quote.
+    pub fn to_reference(snapshot: PriceSnapshot, rate: f64) -> PriceSnapshot {
+        PriceSnapshot {
+            best_bid: snapshot.best_bid * rate,
+            best_ask: snapshot.best_ask * rate,
+            ..snapshot
+        }
+    }
+}
+
+/// A spot and a futures listing of one base under different quotes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrossPair {
+    pub spot_symbol: u16,
+    pub futures_symbol: u16,
+}
+
+/// Cross-quote pairs of `direction`: every base listed on its spot source
+/// under one quote and on its futures source under another.
+pub fn cross_pairs(direction: &DirectionRecord, symbols: &SymbolTable) -> Vec<CrossPair> {
+    let listed_on = |source: u8| {
+        let mut by_base: HashMap<&str, Vec<(u16, &str)>> = HashMap::new();
+        for r in &symbols.records {
+            if r.source_names[source as usize].is_none() {
+                continue;
+            }
+            if let Some((base, quote)) = split_name(&r.name) {
+                by_base.entry(base).or_default().push((r.symbol_id, quote));
+            }
+        }
+        by_base
+    };
+    let spot = listed_on(direction.spot_source);
+    let futures = listed_on(direction.futures_source);
+
+    let mut pairs = Vec::new();
+    for (base, spot_listings) in &spot {
+        let Some(futures_listings) = futures.get(base) else {
+            continue;
+        };
+        for &(spot_symbol, spot_quote) in spot_listings {
+            for &(futures_symbol, futures_quote) in futures_listings {
+                if spot_quote != futures_quote {
+                    pairs.push(CrossPair {
+                        spot_symbol,
+                        futures_symbol,
+                    });
+                }
+            }
+        }
+    }
+    pairs.sort_by_key(|p| (p.spot_symbol, p.futures_symbol));
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::symbols::SymbolRecord;
+
+    fn record(id: u16, name: &str, sources: &[SourceId]) -> SymbolRecord {
+        sources.iter().fold(SymbolRecord::new(id, name), |r, &s| {
+            r.with_source(s, name.replace('-', ""))
+        })
+    }
+
+    fn snap(bid: f64, ask: f64, updated_at: u64) -> PriceSnapshot {
+        PriceSnapshot {
+            best_bid: bid,
+            best_ask: ask,
+            updated_at,
+        }
+    }
+
+    #[test]
+    fn test_cross_quote_conversion() {
+        let spot = SourceId::OkxSpot;
+        let fut = SourceId::BybitFutures;
+        let table = SymbolTable::from_records(vec![
+            record(0, "SOL-USDT", &[fut]),
+            record(1, "SOL-USDC", &[spot]),
+            record(2, "USDC-USDT", &[spot, SourceId::BinanceSpot]),
+            record(3, "ETH-FDUSD", &[spot]),
+        ]);
+        let direction = DirectionRecord {
+            direction_id: 1,
+            spot_source: spot as u8,
+            futures_source: fut as u8,
+            name: "okx_spot_bybit_futures".to_string(),
+            symbols: vec![],
+        };
+        assert_eq!(
+            cross_pairs(&direction, &table),
+            vec![CrossPair {
+                spot_symbol: 1,
+                futures_symbol: 0
+            }]
+        );
+
+        let conv = QuoteConverter::new(&table, "USDT");
+        let read = |symbol_id: u16, source: u8| match (symbol_id, SourceId::from_u8(source)) {
+            (2, Some(SourceId::OkxSpot)) => Some(snap(0.9990, 0.9992, 1_000)),
+            (2, Some(SourceId::BinanceSpot)) => Some(snap(0.9996, 0.9998, 2_000)),
+            _ => None,
+        };
+        assert_eq!(conv.rate(0, 2_000, 500, read), Some(1.0));
+        // Freshest valid rate wins; stale ones are ignored
+        assert_eq!(conv.rate(1, 2_000, 5_000, read), Some(0.9997));
+        assert_eq!(conv.rate(1, 10_000, 500, read), None);
+        // No FDUSD-USDT in this generation
+        assert_eq!(conv.rate(3, 2_000, 5_000, read), None);
+
+        let usdt = QuoteConverter::to_reference(snap(150.0, 150.1, 7), 0.9997);
+        assert!((usdt.best_bid - 149.955).abs() < 1e-9);
+        assert_eq!(usdt.updated_at, 7);
+    }
+}
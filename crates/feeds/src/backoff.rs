@@ -0,0 +1,93 @@
+//! Reconnect delays — exponential backoff with full jitter.
+//!
+//! The n-th consecutive failure waits a uniformly random time in
+//! `[0, min(max, base · 2ⁿ)]`. Spreading the whole range, rather than
+//! jittering around the exponential value, keeps the connections of a
+//! feed (and of several feeds behind one IP) from redialing an exchange in
+//! lockstep after it drops them all at once.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub struct Backoff {
+    base: Duration,
+    max: Duration,
+    failures: u32,
+    /// xorshift64 state; never 0.
+    rng: u64,
+}
+
+impl Backoff {
+    /// `seed` tells apart backoffs created at the same instant.
+    pub fn new(base: Duration, max: Duration, seed: u64) -> Self {
+        let clock = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        let rng = (clock ^ seed.wrapping_mul(0x9E37_79B9_7F4A_7C15)) | 1;
+        Self {
+            base,
+            max: max.max(base),
+            failures: 0,
+            rng,
+        }
+    }
+
+    /// Delay before the next attempt; each call counts one more failure.
+    pub fn next_delay(&mut self) -> Duration {
+        let cap = self
+            .base
+            .saturating_mul(1u32 << self.failures.min(31))
+            .min(self.max);
+        self.failures = self.failures.saturating_add(1);
+        cap.mul_f64(self.next_unit())
+    }
+
+    /// Start over from `base` once a connection has proven healthy.
+    pub fn reset(&mut self) {
+        self.failures = 0;
+    }
+
+    /// Uniform in [0, 1].
+    fn next_unit(&mut self) -> f64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_jitter_bounds() {
+        let base = Duration::from_millis(100);
+        let max = Duration::from_millis(1000);
+        let mut backoff = Backoff::new(base, max, 7);
+        // Caps 100, 200, 400, 800, then 1000 for good
+        let caps = [100, 200, 400, 800, 1000, 1000, 1000];
+        let mut delays = Vec::new();
+        for cap in caps {
+            let delay = backoff.next_delay();
+            assert!(delay <= Duration::from_millis(cap), "{:?} > {}", delay, cap);
+            delays.push(delay);
+        }
+        // Jittered, not constant
+        assert!(delays.windows(2).any(|w| w[0] != w[1]));
+
+        for _ in 0..100 {
+            backoff.next_delay();
+        }
+        assert!(backoff.next_delay() <= max);
+        backoff.reset();
+        assert!(backoff.next_delay() <= base);
+
+        // Two connections starting together still spread out
+        let mut a = Backoff::new(base, max, 0);
+        let mut b = Backoff::new(base, max, 1);
+        let a: Vec<_> = (0..5).map(|_| a.next_delay()).collect();
+        let b: Vec<_> = (0..5).map(|_| b.next_delay()).collect();
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,179 @@
+//! Binance spot and USD-M futures — `<symbol>@bookTicker` on the combined
+//! `/stream` endpoint.
+//!
+//! Data frames are `{"stream":"btcusdt@bookTicker","data":{...}}` with the
+//! symbol in `s`, best bid / ask in `b` / `a` and the book update id in
+//! `u`; futures add the event time `E` (ms). Subscriptions are answered
+//! with `{"result":null,"id":n}`; a refused SUBSCRIBE comes back as
+//! `{"error":{"code":..,"msg":..},"id":n}` without naming the stream, and a
+//! malformed request as a bare `{"code":..,"msg":..}`. The server pings
+//! with WS ping frames, so there is no application keepalive.
+
+use anyhow::{Context, Result};
+
+use common::symbols::{SymbolSub, SymbolTable};
+use common::types::{PriceSnapshot, SourceId};
+
+use crate::parser::{Frame, FrameKind, Parser, PriceUpdate};
+use crate::scan;
+
+/// Streams per SUBSCRIBE message, keeping each request well below the
+/// frame size limit.
+const STREAMS_PER_MESSAGE: usize = 200;
+
+pub struct BinanceParser {
+    source: SourceId,
+}
+
+impl BinanceParser {
+    pub fn new(source: SourceId) -> Self {
+        Self { source }
+    }
+}
+
+impl Parser for BinanceParser {
+    fn source(&self) -> SourceId {
+        self.source
+    }
+
+    /// 1024 streams per connection on spot, 200 on futures.
+    fn max_subscriptions_per_conn(&self) -> usize {
+        if self.source.is_spot() {
+            1024
+        } else {
+            200
+        }
+    }
+
+    fn subscribe_messages(&self, subs: &[SymbolSub]) -> Vec<String> {
+        subs.chunks(STREAMS_PER_MESSAGE)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let params: Vec<String> = chunk
+                    .iter()
+                    .map(|s| format!("\"{}@bookTicker\"", s.exchange_name.to_lowercase()))
+                    .collect();
+                format!(
+                    r#"{{"method":"SUBSCRIBE","params":[{}],"id":{}}}"#,
+                    params.join(","),
+                    i + 1
+                )
+            })
+            .collect()
+    }
+
+    fn parse(
+        &mut self,
+        frame: Frame<'_>,
+        symbols: &SymbolTable,
+        recv_us: u64,
+        out: &mut Vec<PriceUpdate>,
+    ) -> Result<FrameKind> {
+        let Frame::Text(text) = frame else {
+            anyhow::bail!("unexpected binary frame");
+        };
+
+        if let Some(data) = scan::after(text, "data") {
+            let symbol = scan::field(data, "s").context("bookTicker without s")?;
+            let Some(symbol_id) = symbols.resolve(self.source, symbol) else {
+                return Ok(FrameKind::Data);
+            };
+            out.push(PriceUpdate {
+                symbol_id,
+                snapshot: PriceSnapshot {
+                    best_bid: scan::f64_field(data, "b").context("bookTicker without b")?,
+                    best_ask: scan::f64_field(data, "a").context("bookTicker without a")?,
+                    updated_at: recv_us,
+                },
+                update_id: scan::u64_field(data, "u").unwrap_or(0),
+                exchange_ts: scan::u64_field(data, "E").map_or(0, |ms| ms * 1000),
+            });
+            return Ok(FrameKind::Data);
+        }
+
+        if let Some(error) = scan::after(text, "error") {
+            return Ok(FrameKind::Rejected {
+                symbols: Vec::new(),
+                reason: scan::field(error, "msg").unwrap_or(error).to_string(),
+            });
+        }
+        if scan::after(text, "result").is_some() {
+            return Ok(FrameKind::Ack);
+        }
+        if let Some(msg) = scan::field(text, "msg") {
+            return Ok(FrameKind::Rejected {
+                symbols: Vec::new(),
+                reason: msg.to_string(),
+            });
+        }
+        anyhow::bail!("unknown frame: {}", text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::symbols::SymbolRecord;
+
+    use crate::test_parse;
+
+    fn table() -> SymbolTable {
+        let btc = SymbolRecord::new(0, "BTC-USDT").with_source(SourceId::BinanceSpot, "BTCUSDT");
+        let mut pepe =
+            SymbolRecord::new(1, "PEPE-USDT").with_source(SourceId::BinanceFutures, "1000PEPEUSDT");
+        pepe.price_multiplier[SourceId::BinanceFutures.index()] = 1000.0;
+        SymbolTable::from_records(vec![btc, pepe])
+    }
+
+    fn parse(parser: &mut BinanceParser, name: &str) -> (FrameKind, Vec<PriceUpdate>) {
+        test_parse::parse(parser, &table(), "binance", name)
+    }
+
+    #[test]
+    fn test_binance_frames() {
+        let mut spot = BinanceParser::new(SourceId::BinanceSpot);
+        let (kind, out) = parse(&mut spot, "spot_book_ticker.json");
+        assert_eq!(kind, FrameKind::Data);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].symbol_id, 0);
+        assert_eq!(out[0].snapshot.best_bid, 66881.99);
+        assert_eq!(out[0].snapshot.best_ask, 66882.0);
+        assert_eq!(out[0].snapshot.updated_at, 7);
+        assert_eq!((out[0].update_id, out[0].exchange_ts), (52398574383, 0));
+
+        // Not in the table: data, nothing to publish
+        let (kind, out) = parse(&mut spot, "spot_book_ticker_unknown.json");
+        assert_eq!((kind, out.len()), (FrameKind::Data, 0));
+
+        // Futures: event time; prices stay as quoted (per 1000 PEPE)
+        let mut futures = BinanceParser::new(SourceId::BinanceFutures);
+        let (_, out) = parse(&mut futures, "futures_book_ticker.json");
+        assert_eq!(out[0].symbol_id, 1);
+        assert_eq!(out[0].snapshot.best_bid, 0.011234);
+        assert_eq!(out[0].update_id, 5019873614823);
+        assert_eq!(out[0].exchange_ts, 1718358087416000);
+
+        assert_eq!(parse(&mut spot, "subscribe_ack.json").0, FrameKind::Ack);
+        assert_eq!(
+            parse(&mut spot, "subscribe_error.json").0,
+            FrameKind::Rejected {
+                symbols: vec![],
+                reason: "Invalid request: invalid stream name 'btcusdt@booktick'".to_string()
+            }
+        );
+        assert!(matches!(
+            parse(&mut spot, "invalid_json.json").0,
+            FrameKind::Rejected { .. }
+        ));
+        assert!(spot
+            .parse(Frame::Text("{}"), &table(), 0, &mut Vec::new())
+            .is_err());
+
+        let subs = table().subscription_list(SourceId::BinanceSpot);
+        assert_eq!(
+            spot.subscribe_messages(&subs),
+            vec![r#"{"method":"SUBSCRIBE","params":["btcusdt@bookTicker"],"id":1}"#]
+        );
+        assert_eq!(futures.max_subscriptions_per_conn(), 200);
+    }
+}
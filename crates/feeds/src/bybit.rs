@@ -0,0 +1,272 @@
+//! Bybit v5 spot and linear.
+//!
+//! Spot `tickers` carry no book, so spot subscribes to `orderbook.1.<symbol>`
+//! (`b` / `a` as `[["price","size"]]`, update id `u`). Linear subscribes to
+//! `tickers.<symbol>`, which sends one snapshot and then deltas holding only
+//! the fields that changed: a delta that moves the bid has no `ask1Price`.
+//! The parser therefore keeps the last known bid and ask per symbol, merges
+//! every snapshot or delta onto them and publishes nothing for a symbol
+//! until both sides are known. Every later frame for it is published, so a
+//! quiet book still refreshes `updated_at`. `cs` (linear) and `u` (spot)
+//! become the update id, `ts` the exchange time.
+//!
+//! Keepalive is a client `{"op":"ping"}`; pongs come back as
+//! `"ret_msg":"pong"` (spot) or `"op":"pong"` (linear). A refused
+//! subscription names the bad topics in `ret_msg`: `Invalid symbol
+//! :[tickers.XYZUSDT]`.
+
+use anyhow::{Context, Result};
+
+use common::symbols::{SymbolSub, SymbolTable};
+use common::types::{PriceSnapshot, SourceId};
+
+use crate::parser::{Frame, FrameKind, Parser, PriceUpdate};
+use crate::scan;
+
+/// Spot accepts at most 10 topics per subscribe request.
+const TOPICS_PER_MESSAGE: usize = 10;
+
+/// Last known side prices of one symbol; None until seen.
+#[derive(Debug, Clone, Copy, Default)]
+struct Book {
+    bid: Option<f64>,
+    ask: Option<f64>,
+}
+
+pub struct BybitParser {
+    source: SourceId,
+    /// Indexed by symbol_id.
+    books: Vec<Book>,
+}
+
+impl BybitParser {
+    pub fn new(source: SourceId) -> Self {
+        Self {
+            source,
+            books: Vec::new(),
+        }
+    }
+
+    fn topic(&self, exchange_name: &str) -> String {
+        if self.source.is_spot() {
+            format!("orderbook.1.{}", exchange_name)
+        } else {
+            format!("tickers.{}", exchange_name)
+        }
+    }
+
+    fn book(&mut self, symbol_id: u16) -> &mut Book {
+        let idx = symbol_id as usize;
+        if idx >= self.books.len() {
+            self.books.resize(idx + 1, Book::default());
+        }
+        &mut self.books[idx]
+    }
+}
+
+impl Parser for BybitParser {
+    fn source(&self) -> SourceId {
+        self.source
+    }
+
+    fn subscribe_messages(&self, subs: &[SymbolSub]) -> Vec<String> {
+        subs.chunks(TOPICS_PER_MESSAGE)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let args: Vec<String> = chunk
+                    .iter()
+                    .map(|s| format!("\"{}\"", self.topic(&s.exchange_name)))
+                    .collect();
+                format!(
+                    r#"{{"op":"subscribe","args":[{}],"req_id":"{}"}}"#,
+                    args.join(","),
+                    i + 1
+                )
+            })
+            .collect()
+    }
+
+    fn ping_message(&self) -> Option<String> {
+        Some(r#"{"op":"ping"}"#.to_string())
+    }
+
+    fn parse(
+        &mut self,
+        frame: Frame<'_>,
+        symbols: &SymbolTable,
+        recv_us: u64,
+        out: &mut Vec<PriceUpdate>,
+    ) -> Result<FrameKind> {
+        let Frame::Text(text) = frame else {
+            anyhow::bail!("unexpected binary frame");
+        };
+
+        if scan::field(text, "topic").is_some() {
+            let data = scan::after(text, "data").context("topic without data")?;
+            let snapshot = scan::field(text, "type") == Some("snapshot");
+            let (symbol, bid, ask, update_id) = if self.source.is_spot() {
+                (
+                    scan::field(data, "s").context("orderbook without s")?,
+                    top_level(data, "b", snapshot)?,
+                    top_level(data, "a", snapshot)?,
+                    scan::u64_field(data, "u"),
+                )
+            } else {
+                let side = |key| match scan::field(data, key) {
+                    Some(p) => p.parse().map(Some).context("bad price"),
+                    None if snapshot => Ok(Some(0.0)),
+                    None => Ok(None),
+                };
+                (
+                    scan::field(data, "symbol").context("tickers without symbol")?,
+                    side("bid1Price")?,
+                    side("ask1Price")?,
+                    scan::u64_field(text, "cs"),
+                )
+            };
+            let Some(symbol_id) = symbols.resolve(self.source, symbol) else {
+                return Ok(FrameKind::Data);
+            };
+
+            let book = self.book(symbol_id);
+            book.bid = bid.or(book.bid);
+            book.ask = ask.or(book.ask);
+            if let (Some(best_bid), Some(best_ask)) = (book.bid, book.ask) {
+                out.push(PriceUpdate {
+                    symbol_id,
+                    snapshot: PriceSnapshot {
+                        best_bid,
+                        best_ask,
+                        updated_at: recv_us,
+                    },
+                    update_id: update_id.unwrap_or(0),
+                    exchange_ts: scan::u64_field(text, "ts").map_or(0, |ms| ms * 1000),
+                });
+            }
+            return Ok(FrameKind::Data);
+        }
+
+        let ret_msg = scan::field(text, "ret_msg").unwrap_or("");
+        if ret_msg == "pong" || scan::field(text, "op") == Some("pong") {
+            return Ok(FrameKind::Control);
+        }
+        match scan::field(text, "success") {
+            Some("true") => Ok(FrameKind::Ack),
+            Some(_) => Ok(FrameKind::Rejected {
+                symbols: rejected_symbols(ret_msg),
+                reason: ret_msg.to_string(),
+            }),
+            None => anyhow::bail!("unknown frame: {}", text),
+        }
+    }
+}
+
+/// Price of the first level of the book side `key`. An empty side, or a
+/// size of 0, is a price of 0. An absent side is None in a delta and 0 in a
+/// snapshot.
+fn top_level(data: &str, key: &str, snapshot: bool) -> Result<Option<f64>> {
+    if scan::after(data, key).is_none() {
+        return Ok(snapshot.then_some(0.0));
+    }
+    match scan::first_level(data, key).context("malformed book side")? {
+        Some((price, size)) if size != 0.0 => Ok(Some(price)),
+        _ => Ok(Some(0.0)),
+    }
+}
+
+/// Exchange symbols named in "Invalid symbol :[tickers.A, tickers.B]".
+fn rejected_symbols(ret_msg: &str) -> Vec<String> {
+    let Some((_, rest)) = ret_msg.split_once('[') else {
+        return Vec::new();
+    };
+    let list = rest.split(']').next().unwrap_or("");
+    list.split(',')
+        .map(|topic| topic.trim())
+        .filter(|topic| !topic.is_empty())
+        .map(|topic| topic.rsplit('.').next().unwrap_or(topic).to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::symbols::SymbolRecord;
+
+    use crate::test_parse;
+
+    fn table() -> SymbolTable {
+        let records = ["BTC", "ETH"]
+            .iter()
+            .enumerate()
+            .map(|(i, base)| {
+                SymbolRecord::new(i as u16, format!("{}-USDT", base))
+                    .with_source(SourceId::BybitSpot, format!("{}USDT", base))
+                    .with_source(SourceId::BybitFutures, format!("{}USDT", base))
+            })
+            .collect();
+        SymbolTable::from_records(records)
+    }
+
+    fn parse(parser: &mut BybitParser, name: &str) -> (FrameKind, Vec<PriceUpdate>) {
+        test_parse::parse(parser, &table(), "bybit", name)
+    }
+
+    fn quote(out: &[PriceUpdate]) -> (f64, f64) {
+        (out[0].snapshot.best_bid, out[0].snapshot.best_ask)
+    }
+
+    #[test]
+    fn test_bybit_linear_deltas_and_spot_book() {
+        let mut linear = BybitParser::new(SourceId::BybitFutures);
+        // Delta ahead of any snapshot: ask only, nothing to publish
+        let (kind, out) = parse(&mut linear, "linear_delta_before_snapshot.json");
+        assert_eq!((kind, out.len()), (FrameKind::Data, 0));
+
+        let (_, out) = parse(&mut linear, "linear_snapshot.json");
+        assert_eq!(quote(&out), (66880.10, 66880.20));
+        assert_eq!(out[0].update_id, 24987956059);
+        assert_eq!(out[0].exchange_ts, 1718358087506000);
+
+        // Bid moved, ask omitted: the ask is carried over, not zeroed
+        let (_, out) = parse(&mut linear, "linear_delta_bid.json");
+        assert_eq!(quote(&out), (66879.90, 66880.20));
+        assert_eq!(out[0].update_id, 24987956104);
+
+        // No book fields at all: same quote, fresh timestamp
+        let (_, out) = parse(&mut linear, "linear_delta_mark.json");
+        assert_eq!(quote(&out), (66879.90, 66880.20));
+        assert_eq!(out[0].exchange_ts, 1718358087706000);
+
+        let mut spot = BybitParser::new(SourceId::BybitSpot);
+        let (_, out) = parse(&mut spot, "spot_orderbook.json");
+        assert_eq!(quote(&out), (66881.99, 66882.0));
+        assert_eq!(out[0].update_id, 18521288);
+        // Empty bid side in a snapshot is a zero bid
+        let (_, out) = parse(&mut spot, "spot_orderbook_empty_bid.json");
+        assert_eq!(quote(&out), (0.0, 66882.10));
+
+        assert_eq!(parse(&mut spot, "subscribe_ack.json").0, FrameKind::Ack);
+        assert_eq!(parse(&mut spot, "pong_spot.json").0, FrameKind::Control);
+        assert_eq!(parse(&mut linear, "pong_linear.json").0, FrameKind::Control);
+        match parse(&mut linear, "subscribe_error.json").0 {
+            FrameKind::Rejected { symbols, .. } => {
+                assert_eq!(symbols, vec!["XYZUSDT", "ABCUSDT"])
+            }
+            other => panic!("{:?}", other),
+        }
+
+        let subs: Vec<_> = (0..12)
+            .map(|i| SymbolSub {
+                symbol_id: i,
+                exchange_name: format!("T{}USDT", i),
+                price_multiplier: 1.0,
+            })
+            .collect();
+        let messages = spot.subscribe_messages(&subs);
+        assert_eq!(messages.len(), 2);
+        assert!(messages[1].starts_with(
+            r#"{"op":"subscribe","args":["orderbook.1.T10USDT","orderbook.1.T11USDT"]"#
+        ));
+        assert_eq!(linear.ping_message().unwrap(), r#"{"op":"ping"}"#);
+    }
+}
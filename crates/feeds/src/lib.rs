@@ -1 +1,14 @@
+pub mod backoff;
+pub mod binance;
+pub mod bybit;
+pub mod mexc_futures;
+pub mod mexc_spot;
+pub mod okx;
+pub mod parser;
+pub mod scan;
+pub mod ws;
 
+#[cfg(test)]
+mod test_parse;
+#[cfg(test)]
+mod test_ws;
@@ -0,0 +1,233 @@
+//! MEXC futures — `sub.ticker` or `sub.depth.full` on
+//! `wss://contract.mexc.com/edge`.
+//!
+//! Every frame is JSON `{"channel":..,"data":..,"symbol":"BTC_USDT","ts":..}`
+//! with numbers unquoted. `push.ticker` carries `bid1` / `ask1`;
+//! `push.depth.full` at limit 5 carries `bids` / `asks` as
+//! `[price, contracts, orders]` levels and a `version` that becomes the
+//! update id. Symbols keep their `BTC_USDT` form end to end: discovery
+//! records them that way, so they resolve as sent.
+//!
+//! Prices are per coin of the base asset. `contractSize` only converts
+//! volumes (counted in contracts) and never touches a price, so a contract
+//! quote is comparable with spot as is. Denominated contracts
+//! ("1000PEPE_USDT") quote 1000 coins and are scaled down by their
+//! `price_multiplier` in `to_per_unit`, like every other source.
+//!
+//! One subscribe message per symbol, acknowledged by
+//! `{"channel":"rs.sub.<channel>","data":"success"}` without naming it; a
+//! refusal is `rs.error`. Keepalive is `{"method":"ping"}`, answered on the
+//! `pong` channel.
+
+use anyhow::{Context, Result};
+
+use common::symbols::{SymbolSub, SymbolTable};
+use common::types::{PriceSnapshot, SourceId};
+
+use crate::parser::{Frame, FrameKind, Parser, PriceUpdate};
+use crate::scan;
+
+/// Levels requested on `sub.depth.full`; only the first is used.
+const DEPTH_LIMIT: usize = 5;
+
+/// Market data channel; `ws_channel` in exchanges.toml.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MexcFuturesChannel {
+    #[default]
+    Ticker,
+    DepthFull,
+}
+
+impl MexcFuturesChannel {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ticker" => Some(Self::Ticker),
+            "depth.full" => Some(Self::DepthFull),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ticker => "ticker",
+            Self::DepthFull => "depth.full",
+        }
+    }
+}
+
+pub struct MexcFuturesParser {
+    channel: MexcFuturesChannel,
+}
+
+impl MexcFuturesParser {
+    pub fn new(channel: MexcFuturesChannel) -> Self {
+        Self { channel }
+    }
+}
+
+impl Parser for MexcFuturesParser {
+    fn source(&self) -> SourceId {
+        SourceId::MexcFutures
+    }
+
+    fn subscribe_messages(&self, subs: &[SymbolSub]) -> Vec<String> {
+        subs.iter()
+            .map(|s| match self.channel {
+                MexcFuturesChannel::Ticker => format!(
+                    r#"{{"method":"sub.ticker","param":{{"symbol":"{}"}},"gzip":false}}"#,
+                    s.exchange_name
+                ),
+                MexcFuturesChannel::DepthFull => format!(
+                    r#"{{"method":"sub.depth.full","param":{{"symbol":"{}","limit":{}}},"gzip":false}}"#,
+                    s.exchange_name, DEPTH_LIMIT
+                ),
+            })
+            .collect()
+    }
+
+    fn ping_message(&self) -> Option<String> {
+        Some(r#"{"method":"ping"}"#.to_string())
+    }
+
+    fn parse(
+        &mut self,
+        frame: Frame<'_>,
+        symbols: &SymbolTable,
+        recv_us: u64,
+        out: &mut Vec<PriceUpdate>,
+    ) -> Result<FrameKind> {
+        let Frame::Text(text) = frame else {
+            anyhow::bail!("unexpected binary frame (gzip push?)");
+        };
+        let channel =
+            scan::field(text, "channel").with_context(|| format!("unknown frame: {}", text))?;
+
+        let (best_bid, best_ask, update_id) = match channel {
+            "push.ticker" => {
+                let data = scan::after(text, "data").context("ticker without data")?;
+                (
+                    scan::f64_field(data, "bid1").context("ticker without bid1")?,
+                    scan::f64_field(data, "ask1").context("ticker without ask1")?,
+                    0,
+                )
+            }
+            "push.depth.full" => {
+                let data = scan::after(text, "data").context("depth without data")?;
+                let price = |key| {
+                    let level = scan::first_level(data, key)
+                        .with_context(|| format!("depth without {}", key))?;
+                    Ok::<_, anyhow::Error>(level.map_or(0.0, |(price, _)| price))
+                };
+                (
+                    price("bids")?,
+                    price("asks")?,
+                    scan::u64_field(data, "version").unwrap_or(0),
+                )
+            }
+            "pong" => return Ok(FrameKind::Control),
+            "rs.error" => {
+                return Ok(FrameKind::Rejected {
+                    symbols: Vec::new(),
+                    reason: scan::field(text, "data").unwrap_or("").to_string(),
+                })
+            }
+            c if c.starts_with("rs.sub.") => {
+                return match scan::field(text, "data") {
+                    Some("success") => Ok(FrameKind::Ack),
+                    reason => Ok(FrameKind::Rejected {
+                        symbols: Vec::new(),
+                        reason: reason.unwrap_or("").to_string(),
+                    }),
+                };
+            }
+            _ => return Ok(FrameKind::Control),
+        };
+
+        let symbol = scan::field(text, "symbol").context("push without symbol")?;
+        let Some(symbol_id) = symbols.resolve(SourceId::MexcFutures, symbol) else {
+            return Ok(FrameKind::Data);
+        };
+        out.push(PriceUpdate {
+            symbol_id,
+            snapshot: PriceSnapshot {
+                best_bid,
+                best_ask,
+                updated_at: recv_us,
+            },
+            update_id,
+            exchange_ts: scan::u64_field(text, "ts").map_or(0, |ms| ms * 1000),
+        });
+        Ok(FrameKind::Data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::to_per_unit;
+    use common::symbols::SymbolRecord;
+
+    use crate::test_parse;
+
+    fn table() -> SymbolTable {
+        let btc = SymbolRecord::new(0, "BTC-USDT").with_source(SourceId::MexcFutures, "BTC_USDT");
+        let mut pepe =
+            SymbolRecord::new(1, "PEPE-USDT").with_source(SourceId::MexcFutures, "1000PEPE_USDT");
+        pepe.price_multiplier[SourceId::MexcFutures.index()] = 1000.0;
+        SymbolTable::from_records(vec![btc, pepe])
+    }
+
+    fn parse(parser: &mut MexcFuturesParser, name: &str) -> (FrameKind, Vec<PriceUpdate>) {
+        test_parse::parse(parser, &table(), "mexc_futures", name)
+    }
+
+    #[test]
+    fn test_mexc_futures_channels() {
+        let mut ticker = MexcFuturesParser::new(MexcFuturesChannel::Ticker);
+        let (kind, out) = parse(&mut ticker, "ticker.json");
+        assert_eq!(kind, FrameKind::Data);
+        assert_eq!(out[0].symbol_id, 0);
+        assert_eq!(
+            (out[0].snapshot.best_bid, out[0].snapshot.best_ask),
+            (66874.5, 66874.6)
+        );
+        assert_eq!(
+            (out[0].update_id, out[0].exchange_ts),
+            (0, 1718358087321000)
+        );
+
+        // Quoted per 1000 PEPE, stored per PEPE after to_per_unit
+        let (_, mut out) = parse(&mut ticker, "ticker_denominated.json");
+        assert_eq!(out[0].snapshot.best_bid, 0.011234);
+        to_per_unit(SourceId::MexcFutures, &table(), &mut out);
+        assert!((out[0].snapshot.best_bid - 0.000011234).abs() < 1e-15);
+
+        let mut depth = MexcFuturesParser::new(MexcFuturesChannel::DepthFull);
+        let (_, out) = parse(&mut depth, "depth_full.json");
+        assert_eq!(
+            (out[0].snapshot.best_bid, out[0].snapshot.best_ask),
+            (66874.5, 66874.6)
+        );
+        assert_eq!(out[0].update_id, 15736398765);
+        let (_, out) = parse(&mut depth, "depth_full_empty_bids.json");
+        assert_eq!(
+            (out[0].snapshot.best_bid, out[0].snapshot.best_ask),
+            (0.0, 66874.6)
+        );
+
+        assert_eq!(parse(&mut ticker, "subscribe_ack.json").0, FrameKind::Ack);
+        assert_eq!(parse(&mut ticker, "pong.json").0, FrameKind::Control);
+        assert!(matches!(
+            parse(&mut ticker, "subscribe_error.json").0,
+            FrameKind::Rejected { .. }
+        ));
+
+        let subs = table().subscription_list(SourceId::MexcFutures);
+        assert_eq!(
+            depth.subscribe_messages(&subs)[0],
+            r#"{"method":"sub.depth.full","param":{"symbol":"BTC_USDT","limit":5},"gzip":false}"#
+        );
+        assert_eq!(ticker.subscribe_messages(&subs).len(), 2);
+        assert_eq!(ticker.ping_message().unwrap(), r#"{"method":"ping"}"#);
+    }
+}
@@ -0,0 +1,292 @@
+//! MEXC spot — protobuf book tickers on `wss://wbs-api.mexc.com/ws`.
+//!
+//! Market data arrives as binary `PushDataV3ApiWrapper` messages (schemas in
+//! `proto/mexc/`); the subscribed channel
+//! `spot@public.aggre.bookTicker.v3.api.pb@100ms@<symbol>` fills its
+//! `publicAggreBookTicker` body with decimal strings, an empty side left
+//! empty. Control replies stay JSON text: `{"id":0,"code":0,"msg":..}` where
+//! `msg` echoes the subscribed channels, reads `PONG`, or starts with "Not
+//! Subscribed successfully! [<channels>]" on a refusal. One connection
+//! carries at most 30 subscriptions, and the server drops connections that
+//! send no `{"method":"PING"}` for a minute.
+
+use anyhow::{Context, Result};
+use prost::Message;
+
+use common::symbols::{SymbolSub, SymbolTable};
+use common::types::{PriceSnapshot, SourceId};
+
+use crate::parser::{Frame, FrameKind, Parser, PriceUpdate};
+use crate::scan;
+
+/// Subscriptions the server accepts per connection.
+const MAX_SUBSCRIPTIONS: usize = 30;
+
+const CHANNEL_PREFIX: &str = "spot@public.aggre.bookTicker.v3.api.pb@100ms@";
+
+/// prost messages for `proto/mexc/*.proto`, written out by hand so the build
+/// needs no protoc. Keep tags in step with the vendored schemas.
+mod pb {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct PushDataV3ApiWrapper {
+        #[prost(string, tag = "1")]
+        pub channel: String,
+        #[prost(oneof = "push_data_v3_api_wrapper::Body", tags = "305, 311, 315")]
+        pub body: Option<push_data_v3_api_wrapper::Body>,
+        #[prost(string, optional, tag = "3")]
+        pub symbol: Option<String>,
+        #[prost(string, optional, tag = "4")]
+        pub symbol_id: Option<String>,
+        #[prost(int64, optional, tag = "5")]
+        pub create_time: Option<i64>,
+        #[prost(int64, optional, tag = "6")]
+        pub send_time: Option<i64>,
+    }
+
+    pub mod push_data_v3_api_wrapper {
+        #[derive(Clone, PartialEq, prost::Oneof)]
+        pub enum Body {
+            #[prost(message, tag = "305")]
+            BookTicker(super::PublicBookTickerV3Api),
+            #[prost(message, tag = "311")]
+            BookTickerBatch(super::PublicBookTickerBatchV3Api),
+            #[prost(message, tag = "315")]
+            AggreBookTicker(super::PublicAggreBookTickerV3Api),
+        }
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct PublicBookTickerV3Api {
+        #[prost(string, tag = "1")]
+        pub bid_price: String,
+        #[prost(string, tag = "2")]
+        pub bid_quantity: String,
+        #[prost(string, tag = "3")]
+        pub ask_price: String,
+        #[prost(string, tag = "4")]
+        pub ask_quantity: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct PublicBookTickerBatchV3Api {
+        #[prost(message, repeated, tag = "1")]
+        pub items: Vec<PublicBookTickerV3Api>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct PublicAggreBookTickerV3Api {
+        #[prost(string, tag = "1")]
+        pub bid_price: String,
+        #[prost(string, tag = "2")]
+        pub bid_quantity: String,
+        #[prost(string, tag = "3")]
+        pub ask_price: String,
+        #[prost(string, tag = "4")]
+        pub ask_quantity: String,
+    }
+}
+
+use pb::push_data_v3_api_wrapper::Body;
+
+pub struct MexcSpotParser;
+
+impl MexcSpotParser {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Decode one binary push.
+    fn parse_push(
+        &self,
+        data: &[u8],
+        symbols: &SymbolTable,
+        recv_us: u64,
+        out: &mut Vec<PriceUpdate>,
+    ) -> Result<FrameKind> {
+        let push = pb::PushDataV3ApiWrapper::decode(data).context("bad PushDataV3ApiWrapper")?;
+        let (bid, ask) = match &push.body {
+            Some(Body::AggreBookTicker(t)) => (&t.bid_price, &t.ask_price),
+            Some(Body::BookTicker(t)) => (&t.bid_price, &t.ask_price),
+            Some(Body::BookTickerBatch(batch)) => match batch.items.last() {
+                Some(t) => (&t.bid_price, &t.ask_price),
+                None => return Ok(FrameKind::Data),
+            },
+            // Another channel's body, not subscribed by this parser
+            None => return Ok(FrameKind::Data),
+        };
+        let symbol = match &push.symbol {
+            Some(symbol) => symbol.as_str(),
+            None => push.channel.rsplit('@').next().unwrap_or(""),
+        };
+        let Some(symbol_id) = symbols.resolve(SourceId::MexcSpot, symbol) else {
+            return Ok(FrameKind::Data);
+        };
+        let price = |p: &str| -> Result<f64> {
+            if p.is_empty() {
+                Ok(0.0)
+            } else {
+                p.parse().context("bad price")
+            }
+        };
+        let event_ms = push.create_time.or(push.send_time).unwrap_or(0);
+        out.push(PriceUpdate {
+            symbol_id,
+            snapshot: PriceSnapshot {
+                best_bid: price(bid)?,
+                best_ask: price(ask)?,
+                updated_at: recv_us,
+            },
+            update_id: 0,
+            exchange_ts: event_ms.max(0) as u64 * 1000,
+        });
+        Ok(FrameKind::Data)
+    }
+}
+
+impl Default for MexcSpotParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Parser for MexcSpotParser {
+    fn source(&self) -> SourceId {
+        SourceId::MexcSpot
+    }
+
+    fn max_subscriptions_per_conn(&self) -> usize {
+        MAX_SUBSCRIPTIONS
+    }
+
+    fn subscribe_messages(&self, subs: &[SymbolSub]) -> Vec<String> {
+        subs.chunks(MAX_SUBSCRIPTIONS)
+            .map(|chunk| {
+                let params: Vec<String> = chunk
+                    .iter()
+                    .map(|s| format!("\"{}{}\"", CHANNEL_PREFIX, s.exchange_name))
+                    .collect();
+                format!(
+                    r#"{{"method":"SUBSCRIPTION","params":[{}]}}"#,
+                    params.join(",")
+                )
+            })
+            .collect()
+    }
+
+    fn ping_message(&self) -> Option<String> {
+        Some(r#"{"method":"PING"}"#.to_string())
+    }
+
+    fn parse(
+        &mut self,
+        frame: Frame<'_>,
+        symbols: &SymbolTable,
+        recv_us: u64,
+        out: &mut Vec<PriceUpdate>,
+    ) -> Result<FrameKind> {
+        let text = match frame {
+            Frame::Binary(data) => return self.parse_push(data, symbols, recv_us, out),
+            Frame::Text(text) => text,
+        };
+
+        let msg = scan::field(text, "msg").with_context(|| format!("unknown frame: {}", text))?;
+        if msg == "PONG" {
+            return Ok(FrameKind::Control);
+        }
+        if msg.starts_with("Not Subscribed") {
+            return Ok(FrameKind::Rejected {
+                symbols: rejected_symbols(msg),
+                reason: msg.to_string(),
+            });
+        }
+        match scan::field(text, "code") {
+            Some("0") => Ok(FrameKind::Ack),
+            _ => Ok(FrameKind::Rejected {
+                symbols: Vec::new(),
+                reason: msg.to_string(),
+            }),
+        }
+    }
+}
+
+/// Symbols of the channels in "Not Subscribed successfully! [ch1,ch2]. ...".
+fn rejected_symbols(msg: &str) -> Vec<String> {
+    let Some((_, rest)) = msg.split_once('[') else {
+        return Vec::new();
+    };
+    let list = rest.split(']').next().unwrap_or("");
+    list.split(',')
+        .filter_map(|channel| channel.trim().rsplit('@').next())
+        .filter(|symbol| !symbol.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// The .bin fixtures were encoded with `pb` itself, not captured, so these
+// tests do not check `pb` against the wire; see fixtures/README.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::symbols::SymbolRecord;
+
+    use crate::test_parse;
+
+    fn table() -> SymbolTable {
+        let btc = SymbolRecord::new(0, "BTC-USDT").with_source(SourceId::MexcSpot, "BTCUSDT");
+        SymbolTable::from_records(vec![btc])
+    }
+
+    fn parse(name: &str) -> (FrameKind, Vec<PriceUpdate>) {
+        test_parse::parse(&mut MexcSpotParser::new(), &table(), "mexc_spot", name)
+    }
+
+    #[test]
+    fn test_mexc_spot_protobuf_frames() {
+        let (kind, out) = parse("aggre_book_ticker.bin");
+        assert_eq!(kind, FrameKind::Data);
+        assert_eq!(out[0].symbol_id, 0);
+        assert_eq!(
+            (out[0].snapshot.best_bid, out[0].snapshot.best_ask),
+            (66881.99, 66882.0)
+        );
+        assert_eq!(out[0].snapshot.updated_at, 7);
+        assert_eq!(out[0].exchange_ts, 1718358087441000);
+
+        let (_, out) = parse("aggre_book_ticker_empty_bid.bin");
+        assert_eq!(
+            (out[0].snapshot.best_bid, out[0].snapshot.best_ask),
+            (0.0, 66882.1)
+        );
+        let (kind, out) = parse("aggre_book_ticker_unknown.bin");
+        assert_eq!((kind, out.len()), (FrameKind::Data, 0));
+        // Another channel's body decodes to nothing
+        let (kind, out) = parse("aggre_deals.bin");
+        assert_eq!((kind, out.len()), (FrameKind::Data, 0));
+
+        assert_eq!(parse("subscribe_ack.json").0, FrameKind::Ack);
+        assert_eq!(parse("pong.json").0, FrameKind::Control);
+        match parse("subscribe_error.json").0 {
+            FrameKind::Rejected { symbols, .. } => assert_eq!(symbols, vec!["XYZUSDT"]),
+            other => panic!("{:?}", other),
+        }
+        assert!(MexcSpotParser::new()
+            .parse(Frame::Binary(b"\xff\xff"), &table(), 0, &mut Vec::new())
+            .is_err());
+
+        let subs: Vec<_> = (0..31)
+            .map(|i| SymbolSub {
+                symbol_id: i,
+                exchange_name: format!("T{}USDT", i),
+                price_multiplier: 1.0,
+            })
+            .collect();
+        let parser = MexcSpotParser::new();
+        let messages = parser.subscribe_messages(&subs);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(
+            messages[1],
+            r#"{"method":"SUBSCRIPTION","params":["spot@public.aggre.bookTicker.v3.api.pb@100ms@T30USDT"]}"#
+        );
+        assert_eq!(parser.max_subscriptions_per_conn(), 30);
+    }
+}
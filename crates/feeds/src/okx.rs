@@ -0,0 +1,262 @@
+//! OKX v5 spot and swap — `tickers` or `bbo-tbt` on `/ws/v5/public`.
+//!
+//! Both channels push full top of book, never deltas:
+//! `{"arg":{"channel":..,"instId":..},"data":[{..}]}`. `tickers` quotes
+//! `bidPx` / `askPx` (empty strings for an empty side) at most every 100 ms;
+//! `bbo-tbt` sends every top-of-book change as `bids` / `asks` level arrays
+//! with a `seqId`. The instrument comes from `arg.instId`, which is the
+//! exchange symbol as discovery recorded it: "BTC-USDT" on spot,
+//! "BTC-USDT-SWAP" on swap, so the suffix never has to be added or stripped.
+//!
+//! Keepalive is the text frame `ping`, answered with a bare `pong`.
+//! Subscriptions are answered with `{"event":"subscribe",...}` per argument;
+//! a refused one comes back as `{"event":"error","msg":..}` naming the
+//! `instId` in the message.
+
+use anyhow::{Context, Result};
+
+use common::symbols::{SymbolSub, SymbolTable};
+use common::types::{PriceSnapshot, SourceId};
+
+use crate::parser::{Frame, FrameKind, Parser, PriceUpdate};
+use crate::scan;
+
+/// Arguments per subscribe request. OKX caps one request at 64 KB of
+/// arguments; 100 stays far below it.
+const ARGS_PER_MESSAGE: usize = 100;
+
+/// Market data channel; `ws_channel` in exchanges.toml.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OkxChannel {
+    #[default]
+    Tickers,
+    BboTbt,
+}
+
+impl OkxChannel {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "tickers" => Some(Self::Tickers),
+            "bbo-tbt" => Some(Self::BboTbt),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Tickers => "tickers",
+            Self::BboTbt => "bbo-tbt",
+        }
+    }
+}
+
+pub struct OkxParser {
+    source: SourceId,
+    channel: OkxChannel,
+}
+
+impl OkxParser {
+    pub fn new(source: SourceId, channel: OkxChannel) -> Self {
+        Self { source, channel }
+    }
+}
+
+impl Parser for OkxParser {
+    fn source(&self) -> SourceId {
+        self.source
+    }
+
+    fn subscribe_messages(&self, subs: &[SymbolSub]) -> Vec<String> {
+        subs.chunks(ARGS_PER_MESSAGE)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let args: Vec<String> = chunk
+                    .iter()
+                    .map(|s| {
+                        format!(
+                            r#"{{"channel":"{}","instId":"{}"}}"#,
+                            self.channel.name(),
+                            s.exchange_name
+                        )
+                    })
+                    .collect();
+                format!(
+                    r#"{{"id":"{}","op":"subscribe","args":[{}]}}"#,
+                    i + 1,
+                    args.join(",")
+                )
+            })
+            .collect()
+    }
+
+    fn ping_message(&self) -> Option<String> {
+        Some("ping".to_string())
+    }
+
+    fn parse(
+        &mut self,
+        frame: Frame<'_>,
+        symbols: &SymbolTable,
+        recv_us: u64,
+        out: &mut Vec<PriceUpdate>,
+    ) -> Result<FrameKind> {
+        let Frame::Text(text) = frame else {
+            anyhow::bail!("unexpected binary frame");
+        };
+        if text == "pong" {
+            return Ok(FrameKind::Control);
+        }
+
+        if let Some(data) = scan::after(text, "data") {
+            let arg = scan::after(text, "arg").context("data without arg")?;
+            let inst_id = scan::field(arg, "instId").context("arg without instId")?;
+            let Some(symbol_id) = symbols.resolve(self.source, inst_id) else {
+                return Ok(FrameKind::Data);
+            };
+            let (best_bid, best_ask) = match scan::field(arg, "channel") {
+                Some("bbo-tbt") => {
+                    let price = |key| {
+                        let level = scan::first_level(data, key)
+                            .with_context(|| format!("bbo-tbt without {}", key))?;
+                        Ok::<_, anyhow::Error>(level.map_or(0.0, |(price, _)| price))
+                    };
+                    (price("bids")?, price("asks")?)
+                }
+                _ => {
+                    let price = |key| match scan::field(data, key) {
+                        Some("") => Ok(0.0),
+                        Some(p) => p.parse().context("bad price"),
+                        None => anyhow::bail!("tickers without {}", key),
+                    };
+                    (price("bidPx")?, price("askPx")?)
+                }
+            };
+            out.push(PriceUpdate {
+                symbol_id,
+                snapshot: PriceSnapshot {
+                    best_bid,
+                    best_ask,
+                    updated_at: recv_us,
+                },
+                update_id: scan::u64_field(data, "seqId").unwrap_or(0),
+                exchange_ts: scan::u64_field(data, "ts").map_or(0, |ms| ms * 1000),
+            });
+            return Ok(FrameKind::Data);
+        }
+
+        match scan::field(text, "event") {
+            Some("subscribe") => Ok(FrameKind::Ack),
+            Some("error") => {
+                let msg = scan::field(text, "msg").unwrap_or("");
+                Ok(FrameKind::Rejected {
+                    symbols: rejected_symbols(msg),
+                    reason: msg.to_string(),
+                })
+            }
+            Some(_) => Ok(FrameKind::Control),
+            None => anyhow::bail!("unknown frame: {}", text),
+        }
+    }
+}
+
+/// Instrument named in "...channel:tickers,instId:XYZ-USDT doesn't exist...".
+fn rejected_symbols(msg: &str) -> Vec<String> {
+    let Some((_, rest)) = msg.split_once("instId:") else {
+        return Vec::new();
+    };
+    let inst_id = rest
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .next()
+        .unwrap_or("");
+    if inst_id.is_empty() {
+        Vec::new()
+    } else {
+        vec![inst_id.to_string()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::symbols::SymbolRecord;
+
+    use crate::test_parse;
+
+    fn table() -> SymbolTable {
+        let btc = SymbolRecord::new(0, "BTC-USDT")
+            .with_source(SourceId::OkxSpot, "BTC-USDT")
+            .with_source(SourceId::OkxFutures, "BTC-USDT-SWAP");
+        SymbolTable::from_records(vec![btc])
+    }
+
+    fn parse(parser: &mut OkxParser, name: &str) -> (FrameKind, Vec<PriceUpdate>) {
+        test_parse::parse(parser, &table(), "okx", name)
+    }
+
+    #[test]
+    fn test_okx_channels() {
+        let mut spot = OkxParser::new(SourceId::OkxSpot, OkxChannel::Tickers);
+        let (kind, out) = parse(&mut spot, "spot_tickers.json");
+        assert_eq!(kind, FrameKind::Data);
+        assert_eq!(
+            (out[0].snapshot.best_bid, out[0].snapshot.best_ask),
+            (66880.1, 66880.2)
+        );
+        assert_eq!(
+            (out[0].update_id, out[0].exchange_ts),
+            (0, 1718358087390000)
+        );
+
+        // Swap instIds resolve with their -SWAP suffix
+        let mut swap = OkxParser::new(SourceId::OkxFutures, OkxChannel::Tickers);
+        let (_, out) = parse(&mut swap, "swap_tickers.json");
+        assert_eq!(out[0].symbol_id, 0);
+        assert_eq!(out[0].snapshot.best_ask, 66874.6);
+        let (_, out) = parse(&mut swap, "swap_tickers_empty_bid.json");
+        assert_eq!(
+            (out[0].snapshot.best_bid, out[0].snapshot.best_ask),
+            (0.0, 66874.6)
+        );
+
+        let mut bbo = OkxParser::new(SourceId::OkxFutures, OkxChannel::BboTbt);
+        let (_, out) = parse(&mut bbo, "swap_bbo_tbt.json");
+        assert_eq!(
+            (out[0].snapshot.best_bid, out[0].snapshot.best_ask),
+            (66874.5, 66874.6)
+        );
+        assert_eq!(
+            (out[0].update_id, out[0].exchange_ts),
+            (39117453382, 1718358087372000)
+        );
+        let (kind, out) = parse(&mut bbo, "spot_bbo_tbt_unknown.json");
+        assert_eq!((kind, out.len()), (FrameKind::Data, 0));
+
+        assert_eq!(parse(&mut spot, "subscribe_ack.json").0, FrameKind::Ack);
+        assert_eq!(parse(&mut spot, "notice.json").0, FrameKind::Control);
+        match parse(&mut swap, "subscribe_error.json").0 {
+            FrameKind::Rejected { symbols, .. } => assert_eq!(symbols, vec!["XYZ-USDT-SWAP"]),
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(
+            spot.parse(Frame::Text("pong"), &table(), 0, &mut Vec::new())
+                .unwrap(),
+            FrameKind::Control
+        );
+        assert_eq!(spot.ping_message().as_deref(), Some("ping"));
+
+        let subs: Vec<_> = (0..250)
+            .map(|i| SymbolSub {
+                symbol_id: i,
+                exchange_name: format!("T{}-USDT-SWAP", i),
+                price_multiplier: 1.0,
+            })
+            .collect();
+        let messages = bbo.subscribe_messages(&subs);
+        assert_eq!(messages.len(), 3);
+        assert!(messages[2].starts_with(
+            r#"{"id":"3","op":"subscribe","args":[{"channel":"bbo-tbt","instId":"T200-USDT-SWAP"},"#
+        ));
+        assert_eq!(OkxChannel::from_name("bbo-tbt"), Some(OkxChannel::BboTbt));
+        assert_eq!(OkxChannel::from_name("books5"), None);
+    }
+}
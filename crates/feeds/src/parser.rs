@@ -0,0 +1,175 @@
+//! Parser — the per-exchange part of a feed.
+//!
+//! A parser knows how to subscribe to a set of symbols, how to keep the
+//! connection alive and how to turn one WS frame into price updates. It owns
+//! no socket: the feed runtime and the discovery validator both drive it, so
+//! a pair that validates in discovery parses the same way in production.
+
+use anyhow::{Context, Result};
+
+use common::symbols::{SymbolSub, SymbolTable};
+use common::types::{PriceSnapshot, SourceId};
+
+use crate::binance::BinanceParser;
+use crate::bybit::BybitParser;
+use crate::mexc_futures::{MexcFuturesChannel, MexcFuturesParser};
+use crate::mexc_spot::MexcSpotParser;
+use crate::okx::{OkxChannel, OkxParser};
+
+/// One WS data frame as received.
+#[derive(Debug, Clone, Copy)]
+pub enum Frame<'a> {
+    Text(&'a str),
+    Binary(&'a [u8]),
+}
+
+/// Top of book for one symbol, parsed from a frame. Prices are as quoted
+/// by the exchange until `to_per_unit` runs.
+#[derive(Debug, Clone, Copy)]
+pub struct PriceUpdate {
+    pub symbol_id: u16,
+    pub snapshot: PriceSnapshot,
+    /// Exchange book update id, increasing per symbol; 0 if not sent.
+    pub update_id: u64,
+    /// Exchange event time in unix microseconds; 0 if not sent.
+    pub exchange_ts: u64,
+}
+
+/// Scale parsed updates to per-unit prices (`SymbolRecord::price_multiplier`).
+/// The feed runtime calls this before writing to PriceStore, so every source
+/// stores prices for one unit of the base asset.
+pub fn to_per_unit(source: SourceId, symbols: &SymbolTable, updates: &mut [PriceUpdate]) {
+    for u in updates {
+        u.snapshot = u
+            .snapshot
+            .per_unit(symbols.price_multiplier(source, u.symbol_id));
+    }
+}
+
+/// What a frame turned out to be.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrameKind {
+    /// Price data; resolved updates were pushed to `out`.
+    Data,
+    /// Subscription confirmed.
+    Ack,
+    /// Subscription refused. `symbols` holds the exchange symbols named in the
+    /// reply; empty when the exchange does not say which ones.
+    Rejected {
+        symbols: Vec<String>,
+        reason: String,
+    },
+    /// The server pinged us; send this text back.
+    Reply(String),
+    /// Pong or other control traffic that needs no action.
+    Control,
+}
+
+pub trait Parser: Send {
+    fn source(&self) -> SourceId;
+
+    /// Most subscriptions the exchange allows on one connection.
+    fn max_subscriptions_per_conn(&self) -> usize {
+        usize::MAX
+    }
+
+    /// Text messages that subscribe to `subs` on one connection.
+    fn subscribe_messages(&self, subs: &[SymbolSub]) -> Vec<String>;
+
+    /// Application-level keepalive to send every `ping_interval_sec`, if the
+    /// exchange needs one on top of WS ping frames.
+    fn ping_message(&self) -> Option<String> {
+        None
+    }
+
+    /// Parse one frame, appending updates for symbols known to `symbols`.
+    /// `recv_us` is the local receive time, stored as `updated_at`.
+    fn parse(
+        &mut self,
+        frame: Frame<'_>,
+        symbols: &SymbolTable,
+        recv_us: u64,
+        out: &mut Vec<PriceUpdate>,
+    ) -> Result<FrameKind>;
+}
+
+/// Builds a source's parser from its exchange's `ws_channel`
+/// (exchanges.toml); an unknown channel is an error.
+pub type ParserFactory = fn(SourceId, Option<&str>) -> Result<Box<dyn Parser>>;
+
+/// Every parser, keyed by `SourceId::name()`. A new exchange plugs in here;
+/// the feed runtime, feed-runner and the discovery validator all look
+/// parsers up through it.
+pub const REGISTRY: &[(&str, ParserFactory)] = &[
+    ("binance_spot", binance),
+    ("binance_futures", binance),
+    ("bybit_spot", bybit),
+    ("bybit_futures", bybit),
+    ("mexc_spot", mexc_spot),
+    ("mexc_futures", mexc_futures),
+    ("okx_spot", okx),
+    ("okx_futures", okx),
+];
+
+/// Registry entry for the source named `name`.
+pub fn parser_factory(name: &str) -> Option<ParserFactory> {
+    REGISTRY
+        .iter()
+        .find(|(key, _)| *key == name)
+        .map(|&(_, factory)| factory)
+}
+
+/// Parser for `source`, or None if the registry has none. `channel` is the
+/// exchange's `ws_channel` from exchanges.toml; an unknown one is an error.
+pub fn create_parser(source: SourceId, channel: Option<&str>) -> Result<Option<Box<dyn Parser>>> {
+    match parser_factory(source.name()) {
+        Some(factory) => factory(source, channel).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn binance(source: SourceId, _channel: Option<&str>) -> Result<Box<dyn Parser>> {
+    Ok(Box::new(BinanceParser::new(source)))
+}
+
+fn bybit(source: SourceId, _channel: Option<&str>) -> Result<Box<dyn Parser>> {
+    Ok(Box::new(BybitParser::new(source)))
+}
+
+fn mexc_spot(_source: SourceId, _channel: Option<&str>) -> Result<Box<dyn Parser>> {
+    Ok(Box::new(MexcSpotParser::new()))
+}
+
+fn mexc_futures(_source: SourceId, channel: Option<&str>) -> Result<Box<dyn Parser>> {
+    let channel = match channel {
+        Some(name) => MexcFuturesChannel::from_name(name)
+            .with_context(|| format!("unknown mexc ws_channel {:?}", name))?,
+        None => MexcFuturesChannel::default(),
+    };
+    Ok(Box::new(MexcFuturesParser::new(channel)))
+}
+
+fn okx(source: SourceId, channel: Option<&str>) -> Result<Box<dyn Parser>> {
+    let channel = match channel {
+        Some(name) => OkxChannel::from_name(name)
+            .with_context(|| format!("unknown okx ws_channel {:?}", name))?,
+        None => OkxChannel::default(),
+    };
+    Ok(Box::new(OkxParser::new(source, channel)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_covers_every_source() {
+        for source in SourceId::ALL {
+            let parser = create_parser(source, None).unwrap().unwrap();
+            assert_eq!(parser.source(), source);
+        }
+        assert_eq!(REGISTRY.len(), SourceId::ALL.len());
+        assert!(parser_factory("okx_swap").is_none());
+        assert!(create_parser(SourceId::OkxSpot, Some("books5")).is_err());
+    }
+}
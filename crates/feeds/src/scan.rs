@@ -0,0 +1,95 @@
+//! Zero-copy field extraction from flat JSON frames.
+//!
+//! Ticker frames are small objects with a fixed set of keys. Finding
+//! `"key":` and slicing out the value is much cheaper than building a
+//! document and allocates nothing. This is not a JSON parser: a key must be
+//! unique within the scanned slice (scope it with `after`), and string
+//! values must not contain escaped quotes, which symbols, decimal strings
+//! and ids never do.
+
+/// The raw value of `key`: a string's contents without the quotes, or the
+/// literal token (number, `true`, `false`, `null`). Objects and arrays are
+/// not supported.
+pub fn field<'a>(text: &'a str, key: &str) -> Option<&'a str> {
+    let value = after(text, key)?;
+    match value.strip_prefix('"') {
+        Some(s) => s.find('"').map(|end| &s[..end]),
+        None => {
+            let end = value
+                .find(|c: char| matches!(c, ',' | '}' | ']') || c.is_whitespace())
+                .unwrap_or(value.len());
+            Some(&value[..end])
+        }
+    }
+}
+
+/// `key`'s value parsed as f64; quoted decimals ("25.35") included.
+pub fn f64_field(text: &str, key: &str) -> Option<f64> {
+    field(text, key)?.parse().ok()
+}
+
+/// `key`'s value parsed as u64; quoted integers included.
+pub fn u64_field(text: &str, key: &str) -> Option<u64> {
+    field(text, key)?.parse().ok()
+}
+
+/// Price and size of the first level of the book side `key`, an array of
+/// `["price","size",...]` arrays. Some(None) for an empty side; None if the
+/// key is missing or the level is malformed.
+pub fn first_level(text: &str, key: &str) -> Option<Option<(f64, f64)>> {
+    let side = after(text, key)?.strip_prefix('[')?;
+    let Some(level) = side.trim_start().strip_prefix('[') else {
+        return Some(None);
+    };
+    let level = &level[..level.find(']')?];
+    let mut values = level.split(',').map(|v| v.trim().trim_matches('"'));
+    let price = values.next()?.parse().ok()?;
+    let size = values.next()?.parse().ok()?;
+    Some(Some((price, size)))
+}
+
+/// Everything after `"key":`, leading whitespace trimmed.
+pub fn after<'a>(text: &'a str, key: &str) -> Option<&'a str> {
+    let bytes = text.as_bytes();
+    let mut from = 0;
+    while let Some(pos) = text[from..].find(key) {
+        let start = from + pos;
+        let end = start + key.len();
+        from = end.max(start + 1);
+        if start == 0 || bytes[start - 1] != b'"' || bytes.get(end) != Some(&b'"') {
+            continue;
+        }
+        if let Some(rest) = text[end + 1..].trim_start().strip_prefix(':') {
+            return Some(rest.trim_start());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_extraction() {
+        let text = r#"{"stream":"btcusdt@bookTicker","data":{"e":"s","u":400900217,"s":"BNBUSDT","b":"25.35190000", "B" : "31.21","ok":true,"x":null}}"#;
+        assert_eq!(field(text, "s"), Some("BNBUSDT"));
+        assert_eq!(field(text, "stream"), Some("btcusdt@bookTicker"));
+        assert_eq!(u64_field(text, "u"), Some(400900217));
+        assert_eq!(f64_field(text, "b"), Some(25.3519));
+        assert_eq!(f64_field(text, "B"), Some(31.21));
+        assert_eq!(field(text, "ok"), Some("true"));
+        assert_eq!(field(text, "x"), Some("null"));
+        assert_eq!(field(text, "a"), None);
+        // Scoped lookup
+        let data = after(text, "data").unwrap();
+        assert!(data.starts_with("{\"e\""));
+        assert_eq!(field(data, "stream"), None);
+
+        let book = r#"{"b":[["66881.99","0.716"],["66881.98","2"]],"a":[ ],"c":[[1]]}"#;
+        assert_eq!(first_level(book, "b"), Some(Some((66881.99, 0.716))));
+        assert_eq!(first_level(book, "a"), Some(None));
+        assert_eq!(first_level(book, "c"), None);
+        assert_eq!(first_level(book, "d"), None);
+    }
+}
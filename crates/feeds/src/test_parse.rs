@@ -0,0 +1,31 @@
+//! Fixture plumbing shared by the parser tests (tests only).
+//!
+//! Fixtures live in `fixtures/<venue>/`: `.bin` files are binary frames,
+//! everything else one text frame. Parsed frames are stamped received at 7.
+
+use common::symbols::SymbolTable;
+
+use crate::parser::{Frame, FrameKind, Parser, PriceUpdate};
+
+pub fn fixture(venue: &str, name: &str) -> Vec<u8> {
+    let path = format!("{}/fixtures/{}/{}", env!("CARGO_MANIFEST_DIR"), venue, name);
+    std::fs::read(&path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+/// Feed fixture `venue/name` to `parser`.
+pub fn parse(
+    parser: &mut dyn Parser,
+    symbols: &SymbolTable,
+    venue: &str,
+    name: &str,
+) -> (FrameKind, Vec<PriceUpdate>) {
+    let data = fixture(venue, name);
+    let frame = if name.ends_with(".bin") {
+        Frame::Binary(&data)
+    } else {
+        Frame::Text(std::str::from_utf8(&data).unwrap().trim_end())
+    };
+    let mut out = Vec::new();
+    let kind = parser.parse(frame, symbols, 7, &mut out).unwrap();
+    (kind, out)
+}
@@ -0,0 +1,102 @@
+//! Minimal WebSocket stand-in for exchange feeds (tests only).
+//!
+//! Each accepted connection follows a scripted `Session`: serve text
+//! frames and hold on, serve them and hang up, or go silent. Text messages
+//! received from clients are kept for inspection.
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use futures_util::{SinkExt, StreamExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::Message;
+
+type Received = Arc<Mutex<Vec<String>>>;
+
+/// What the server does with one connection.
+#[derive(Debug, Clone)]
+pub enum Session {
+    /// Send the frames, then hold the connection until the client leaves.
+    Serve(Vec<String>),
+    /// Send the frames, then close the connection.
+    Drop(Vec<String>),
+    /// Complete the handshake, then never send a frame.
+    Stall,
+}
+
+pub struct TestWsServer {
+    addr: SocketAddr,
+    received: Received,
+    connections: Arc<AtomicUsize>,
+}
+
+impl TestWsServer {
+    /// Every connection is sent `frames`, then held open.
+    pub async fn start(frames: Vec<String>) -> Self {
+        Self::scripted(vec![Session::Serve(frames)]).await
+    }
+
+    /// The n-th connection follows `sessions[n]`; the last one repeats.
+    pub async fn scripted(sessions: Vec<Session>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let received: Received = Arc::default();
+        let connections = Arc::new(AtomicUsize::new(0));
+
+        let accept_received = received.clone();
+        let accept_connections = connections.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let n = accept_connections.fetch_add(1, Ordering::Relaxed);
+                let session = sessions[n.min(sessions.len() - 1)].clone();
+                tokio::spawn(handle(stream, session, accept_received.clone()));
+            }
+        });
+
+        Self {
+            addr,
+            received,
+            connections,
+        }
+    }
+
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    /// Text messages received so far, over all connections.
+    pub fn received(&self) -> Vec<String> {
+        self.received.lock().unwrap().clone()
+    }
+
+    /// Connections accepted so far.
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::Relaxed)
+    }
+}
+
+async fn handle(stream: TcpStream, session: Session, received: Received) {
+    let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    let (frames, hang_up) = match session {
+        Session::Serve(frames) => (frames, false),
+        Session::Drop(frames) => (frames, true),
+        Session::Stall => (Vec::new(), false),
+    };
+    for frame in frames {
+        if ws.send(Message::Text(frame)).await.is_err() {
+            return;
+        }
+    }
+    if hang_up {
+        let _ = ws.close(None).await;
+        return;
+    }
+    while let Some(Ok(msg)) = ws.next().await {
+        if let Message::Text(text) = msg {
+            received.lock().unwrap().push(text);
+        }
+    }
+}
@@ -0,0 +1,1112 @@
+//! Feed runtime — drives one source's parser over as many WS connections as
+//! its subscription list needs.
+//!
+//! The source's `SymbolTable::subscription_list` is split into shards of at
+//! most `ws.max_subscriptions_per_conn` symbols (less if the parser's
+//! exchange allows fewer), one connection per shard. Every connection gets
+//! its own parser, so parsers may keep per-symbol state. Parsed updates are
+//! scaled to per-unit prices, written to PriceStore and flagged in the
+//! UpdateBitmap, in that order, so the engine never sees a bit before its
+//! price.
+//!
+//! Each connection cycles through `ConnState`: connecting, subscribing,
+//! live, and on failure backoff before dialing again. A live connection
+//! that receives no frame at all for `ws.heartbeat_timeout_sec` is stale:
+//! it is dropped and redialed like a failed one. The parser's keepalive
+//! goes out every `ws.ping_interval_sec`, or never if that is 0. Redials wait
+//! `ws.reconnect_base_ms` doubling up to `ws.reconnect_max_ms`, fully
+//! jittered (see `Backoff`), and start over once the connection delivers
+//! data again.
+//!
+//! With a hot-standby endpoint configured (`ws_spot_standby` /
+//! `ws_futures_standby`), every shard runs a second, independent connection
+//! to it. Both publish the same symbols; an update is written only if it is
+//! newer than the stored one (see `Written`), so whichever copy arrives
+//! first wins and the other is dropped. If either socket dies, the other
+//! keeps the prices flowing while it reconnects. How often each connection
+//! wins is logged every `monitoring.stats_log_interval_sec`.
+//!
+//! All connections run in one task; a feed spends its time waiting on
+//! sockets. The feed's HealthTable slot gets a heartbeat and its uptime
+//! every second, its live connection count, a message / error count and
+//! reconnect / stale counts. The feed stops when the ControlStore asks
+//! every process to stop.
+
+use std::convert::Infallible;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tracing::{debug, info, warn};
+
+use common::config::{AppConfig, ExchangesConfig};
+use common::symbols::{SymbolSub, SymbolTable};
+use common::types::{SourceId, NUM_SOURCES};
+use shm::bitmap::UpdateBitmap;
+use shm::control::ControlStore;
+use shm::health::{HealthTable, ProcessStatus};
+use shm::price_store::PriceStore;
+
+use crate::backoff::Backoff;
+use crate::parser::{create_parser, to_per_unit, Frame, FrameKind, Parser, PriceUpdate};
+
+/// How often the health slot is refreshed and the stop flags are checked.
+const HEALTH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Feeds own HealthTable slots 0..8, one per source.
+pub fn health_slot(source: SourceId) -> usize {
+    source.index()
+}
+
+#[derive(Debug, Clone)]
+pub struct FeedConfig {
+    pub source: SourceId,
+    pub url: String,
+    /// Hot-standby endpoint; a second connection per shard when set.
+    pub standby_url: Option<String>,
+    pub max_subscriptions_per_conn: usize,
+    /// Application-level keepalive interval for exchanges that need one.
+    pub ping_interval: Duration,
+    /// A connection that receives no frame for this long is redialed. Also
+    /// bounds the connect handshake.
+    pub heartbeat_timeout: Duration,
+    /// First reconnect delay cap, doubled per consecutive failure.
+    pub reconnect_base: Duration,
+    /// Ceiling for the reconnect delay cap.
+    pub reconnect_max: Duration,
+    /// Market data channel, see `ExchangeEntry::ws_channel`.
+    pub channel: Option<String>,
+    /// How often hot-standby race statistics are logged; zero disables.
+    pub stats_interval: Duration,
+}
+
+impl FeedConfig {
+    pub fn from_config(
+        source: SourceId,
+        config: &AppConfig,
+        exchanges: &ExchangesConfig,
+    ) -> Result<Self> {
+        let entry = exchanges.entry(source).with_context(|| {
+            format!(
+                "exchange {} missing from exchanges config",
+                source.exchange()
+            )
+        })?;
+        Ok(Self {
+            source,
+            url: entry.ws_url(source).to_string(),
+            standby_url: entry.ws_standby_url(source).map(str::to_string),
+            max_subscriptions_per_conn: config.ws.max_subscriptions_per_conn,
+            ping_interval: Duration::from_secs(config.ws.ping_interval_sec),
+            heartbeat_timeout: Duration::from_secs(config.ws.heartbeat_timeout_sec),
+            reconnect_base: Duration::from_millis(config.ws.reconnect_base_ms),
+            reconnect_max: Duration::from_millis(config.ws.reconnect_max_ms),
+            channel: entry.ws_channel.clone(),
+            stats_interval: Duration::from_secs(config.monitoring.stats_log_interval_sec),
+        })
+    }
+}
+
+/// Split `subs` into connections of at most `per_conn` symbols each.
+pub fn shard(subs: &[SymbolSub], per_conn: usize) -> Vec<&[SymbolSub]> {
+    subs.chunks(per_conn.max(1)).collect()
+}
+
+/// Where a feed's prices go.
+pub struct FeedOutput {
+    state: Mutex<OutputState>,
+    bitmap: UpdateBitmap,
+}
+
+struct OutputState {
+    store: PriceStore,
+    /// Last write per source and symbol_id.
+    written: Vec<Vec<Option<Written>>>,
+}
+
+/// What was last written for one (source, symbol): the bar a later update
+/// has to clear.
+#[derive(Debug, Clone, Copy)]
+struct Written {
+    update_id: u64,
+    exchange_ts: u64,
+    conn: usize,
+}
+
+impl Written {
+    /// Whether `u`, received on connection `conn`, replaces this. The exchange
+    /// time decides first (it survives exchange-side id resets), then the
+    /// update id. When both tie, or are missing, only the connection that
+    /// wrote last may write again: its stream is in order, while the other
+    /// connection is delivering a copy. An update carrying neither is
+    /// always written, since there is nothing to compare.
+    fn is_superseded_by(&self, u: &PriceUpdate, conn: usize) -> bool {
+        if u.exchange_ts == 0 && u.update_id == 0 {
+            return true;
+        }
+        if u.exchange_ts != 0 && self.exchange_ts != 0 && u.exchange_ts != self.exchange_ts {
+            return u.exchange_ts > self.exchange_ts;
+        }
+        if u.update_id != 0 && self.update_id != 0 && u.update_id != self.update_id {
+            return u.update_id > self.update_id;
+        }
+        conn == self.conn
+    }
+}
+
+impl FeedOutput {
+    pub fn new(store: PriceStore, bitmap: UpdateBitmap) -> Self {
+        Self {
+            state: Mutex::new(OutputState {
+                store,
+                written: vec![Vec::new(); NUM_SOURCES as usize],
+            }),
+            bitmap,
+        }
+    }
+
+    /// Write the `updates` of `source` received on connection `conn` that
+    /// are newer than what is stored, and flag them for the engine. Returns
+    /// how many were written.
+    pub fn publish(&self, source: SourceId, conn: usize, updates: &[PriceUpdate]) -> usize {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let OutputState { store, written } = &mut *state;
+        let written = &mut written[source.index()];
+        let mut count = 0;
+        for u in updates {
+            let idx = u.symbol_id as usize;
+            if idx >= written.len() {
+                written.resize(idx + 1, None);
+            }
+            if written[idx].is_some_and(|w| !w.is_superseded_by(u, conn)) {
+                continue;
+            }
+            written[idx] = Some(Written {
+                update_id: u.update_id,
+                exchange_ts: u.exchange_ts,
+                conn,
+            });
+            store.write(u.symbol_id, source as u8, &u.snapshot);
+            self.bitmap.set(source as u8, u.symbol_id);
+            count += 1;
+        }
+        count
+    }
+}
+
+/// Load config, symbols and shared memory for `source` and run its feed
+/// until the ControlStore says stop. exchanges.toml is read from the
+/// directory of `config_path`. The source's HealthTable slot is claimed for
+/// this process first; the feed refuses to start if another live process
+/// holds it.
+pub async fn run_feed(source: SourceId, config_path: &Path) -> Result<()> {
+    let config = AppConfig::load(config_path)?;
+    let config_dir = config_path.parent().unwrap_or(Path::new("."));
+    let exchanges = ExchangesConfig::load(&config_dir.join("exchanges.toml"))?;
+    let feed = FeedConfig::from_config(source, &config, &exchanges)?;
+    let channel = feed.channel.as_deref();
+    if create_parser(source, channel)?.is_none() {
+        anyhow::bail!("no parser for {}", source.name());
+    }
+
+    let symbols = SymbolTable::load(Path::new(&config.general.generated_dir))?;
+    let g = &config.general;
+    let output = FeedOutput::new(
+        PriceStore::open(&g.shm_seqs, &g.shm_data)?,
+        UpdateBitmap::open(&g.shm_bitmap)?,
+    );
+    let health = HealthTable::open(&g.shm_health)?;
+    let control = ControlStore::open(&g.shm_control)?;
+    let slot = health_slot(source);
+    // Held until the feed returns or its future is dropped
+    let _claim = SlotClaim::take(&health, slot, std::process::id()).map_err(|owner| {
+        anyhow::anyhow!(
+            "health slot {} of {} is held by pid {}",
+            slot,
+            source.name(),
+            owner
+        )
+    })?;
+
+    let make_parser = || {
+        create_parser(source, channel)
+            .ok()
+            .flatten()
+            .expect("parser checked above")
+    };
+    feed_loop(&feed, &symbols, &make_parser, &output, &health, &control).await;
+    Ok(())
+}
+
+/// A HealthTable slot claimed by this process. Dropping it marks the slot
+/// stopped and gives it up, also when the feed is cancelled mid-run (a
+/// sibling feed of the same feed-runner failed to start).
+struct SlotClaim<'a> {
+    health: &'a HealthTable,
+    slot: usize,
+    pid: u32,
+}
+
+impl<'a> SlotClaim<'a> {
+    /// Claim `slot` for `pid`, or the pid of the live process holding it.
+    fn take(health: &'a HealthTable, slot: usize, pid: u32) -> Result<Self, u32> {
+        health.claim(slot, pid)?;
+        Ok(Self { health, slot, pid })
+    }
+}
+
+impl Drop for SlotClaim<'_> {
+    fn drop(&mut self) {
+        self.health.set_ws_connections(self.slot, 0);
+        self.health.set_status(self.slot, ProcessStatus::Stopped);
+        self.health.release(self.slot, self.pid);
+    }
+}
+
+/// Stream every symbol `symbols` lists for `config.source` into `output`
+/// until `control` says stop.
+pub async fn feed_loop(
+    config: &FeedConfig,
+    symbols: &SymbolTable,
+    make_parser: &dyn Fn() -> Box<dyn Parser>,
+    output: &FeedOutput,
+    health: &HealthTable,
+    control: &ControlStore,
+) {
+    let source = config.source;
+    let subs = symbols.subscription_list(source);
+    let per_conn = config
+        .max_subscriptions_per_conn
+        .min(make_parser().max_subscriptions_per_conn());
+    let shards = shard(&subs, per_conn);
+    // Every shard on the primary, and again on the standby if there is one
+    let mut endpoints = vec![("primary", config.url.as_str())];
+    if let Some(standby) = &config.standby_url {
+        endpoints.push(("standby", standby.as_str()));
+    }
+    let conns: Vec<_> = shards
+        .iter()
+        .enumerate()
+        .flat_map(|(i, subs)| {
+            endpoints
+                .iter()
+                .map(move |&(role, url)| (format!("shard {} {}", i, role), url, *subs))
+        })
+        .collect();
+    info!(
+        "{}: {} symbols over {} shards, {} connections",
+        source.name(),
+        subs.len(),
+        shards.len(),
+        conns.len()
+    );
+
+    let labels = conns.iter().map(|(label, _, _)| label.clone()).collect();
+    let stats_interval = (endpoints.len() > 1).then_some(config.stats_interval);
+    let feed_health = FeedHealth::new(health, source, labels, stats_interval);
+    health.set_status(feed_health.slot, ProcessStatus::Starting);
+    let connections = conns.iter().enumerate().map(|(i, (_, url, subs))| {
+        connection_loop(
+            i,
+            config,
+            url,
+            subs,
+            make_parser(),
+            symbols,
+            output,
+            &feed_health,
+        )
+    });
+
+    tokio::select! {
+        _ = futures_util::future::join_all(connections) => {}
+        _ = feed_health.run(control) => {}
+    }
+    health.set_ws_connections(feed_health.slot, 0);
+    health.set_status(feed_health.slot, ProcessStatus::Stopped);
+    info!("{}: feed stopped", source.name());
+}
+
+/// Where one connection is in its life cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConnState {
+    /// Dialing the exchange.
+    Connecting,
+    /// Connected, sending the subscribe messages.
+    Subscribing,
+    /// Streaming frames into the store.
+    Live,
+    /// Live, but silent past the heartbeat timeout.
+    Stale,
+    /// Waiting out a jittered delay before dialing again.
+    Backoff,
+}
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// No frame arrived within the heartbeat timeout.
+#[derive(Debug)]
+struct Stale(Duration);
+
+impl std::fmt::Display for Stale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no frame for {:?}", self.0)
+    }
+}
+
+impl std::error::Error for Stale {}
+
+/// Keep one shard connected to `url`, forever.
+#[allow(clippy::too_many_arguments)]
+async fn connection_loop(
+    conn: usize,
+    config: &FeedConfig,
+    url: &str,
+    subs: &[SymbolSub],
+    mut parser: Box<dyn Parser>,
+    symbols: &SymbolTable,
+    output: &FeedOutput,
+    health: &FeedHealth<'_>,
+) {
+    let name = config.source.name();
+    let mut backoff = Backoff::new(config.reconnect_base, config.reconnect_max, conn as u64);
+    let mut ws: Option<WsStream> = None;
+    let mut state = ConnState::Connecting;
+    loop {
+        debug!("{} conn {}: {:?}", name, conn, state);
+        let result = match state {
+            ConnState::Connecting => connect(url, config.heartbeat_timeout).await.map(|stream| {
+                ws = Some(stream);
+                ConnState::Subscribing
+            }),
+            ConnState::Subscribing => {
+                let stream = ws.as_mut().expect("connected");
+                subscribe(stream, parser.subscribe_messages(subs))
+                    .await
+                    .map(|()| ConnState::Live)
+            }
+            ConnState::Live => {
+                let stream = ws.as_mut().expect("connected");
+                let _live = health.connected();
+                stream_frames(
+                    conn,
+                    config,
+                    stream,
+                    parser.as_mut(),
+                    symbols,
+                    output,
+                    health,
+                    &mut backoff,
+                )
+                .await
+                .map(|never| match never {})
+            }
+            ConnState::Stale => {
+                health.stale();
+                Ok(ConnState::Backoff)
+            }
+            ConnState::Backoff => {
+                ws = None;
+                tokio::time::sleep(backoff.next_delay()).await;
+                health.reconnect();
+                Ok(ConnState::Connecting)
+            }
+        };
+        state = match result {
+            Ok(next) => next,
+            Err(e) if e.is::<Stale>() => {
+                warn!("{} conn {}: {}, reconnecting", name, conn, e);
+                ConnState::Stale
+            }
+            Err(e) => {
+                warn!("{} conn {}: {:#}, reconnecting", name, conn, e);
+                health.error();
+                ConnState::Backoff
+            }
+        };
+    }
+}
+
+/// Dial `url`, giving up after `timeout`.
+async fn connect(url: &str, timeout: Duration) -> Result<WsStream> {
+    let dial = tokio_tungstenite::connect_async(url);
+    let (ws, _) = tokio::time::timeout(timeout, dial)
+        .await
+        .map_err(|_| anyhow::anyhow!("connect timed out"))
+        .and_then(|r| r.map_err(anyhow::Error::from))
+        .with_context(|| format!("failed to connect to {}", url))?;
+    Ok(ws)
+}
+
+async fn subscribe(ws: &mut WsStream, messages: Vec<String>) -> Result<()> {
+    for msg in messages {
+        ws.send(Message::Text(msg))
+            .await
+            .context("subscribe failed")?;
+    }
+    Ok(())
+}
+
+/// Fires the parser's keepalive every `ws.ping_interval_sec`, first one
+/// interval after connecting. An interval of 0 turns keepalives off: the
+/// timer never fires.
+pub struct PingTimer(Option<tokio::time::Interval>);
+
+impl PingTimer {
+    pub fn new(interval: Duration) -> Self {
+        Self(
+            (!interval.is_zero()).then(|| {
+                tokio::time::interval_at(tokio::time::Instant::now() + interval, interval)
+            }),
+        )
+    }
+
+    pub async fn tick(&mut self) {
+        match &mut self.0 {
+            Some(interval) => {
+                interval.tick().await;
+            }
+            None => std::future::pending().await,
+        }
+    }
+}
+
+/// Publish frames until the socket fails or goes silent (`Stale`).
+#[allow(clippy::too_many_arguments)]
+async fn stream_frames(
+    conn: usize,
+    config: &FeedConfig,
+    ws: &mut WsStream,
+    parser: &mut dyn Parser,
+    symbols: &SymbolTable,
+    output: &FeedOutput,
+    health: &FeedHealth<'_>,
+    backoff: &mut Backoff,
+) -> Result<Infallible> {
+    let source = config.source;
+    let mut ping = PingTimer::new(config.ping_interval);
+    let watchdog = tokio::time::sleep(config.heartbeat_timeout);
+    tokio::pin!(watchdog);
+    let mut updates = Vec::new();
+    loop {
+        let msg = tokio::select! {
+            _ = ping.tick() => {
+                if let Some(ping) = parser.ping_message() {
+                    ws.send(Message::Text(ping)).await.context("ping failed")?;
+                }
+                continue;
+            }
+            _ = &mut watchdog => return Err(Stale(config.heartbeat_timeout).into()),
+            msg = ws.next() => msg,
+        };
+        let msg = msg.context("connection closed")?.context("read failed")?;
+        watchdog
+            .as_mut()
+            .reset(tokio::time::Instant::now() + config.heartbeat_timeout);
+        let frame = match &msg {
+            Message::Text(text) => Frame::Text(text),
+            Message::Binary(data) => Frame::Binary(data),
+            Message::Close(_) => anyhow::bail!("closed by server"),
+            _ => continue,
+        };
+
+        updates.clear();
+        match parser.parse(frame, symbols, unix_now_us(), &mut updates) {
+            Ok(FrameKind::Data) => {
+                to_per_unit(source, symbols, &mut updates);
+                let won = output.publish(source, conn, &updates);
+                health.race(conn, won, updates.len() - won);
+                health.message();
+                backoff.reset();
+            }
+            Ok(FrameKind::Reply(text)) => {
+                ws.send(Message::Text(text)).await.context("reply failed")?;
+            }
+            Ok(FrameKind::Rejected { symbols, reason }) => {
+                warn!(
+                    "{}: subscription rejected ({}): {:?}",
+                    source.name(),
+                    reason,
+                    symbols
+                );
+            }
+            Ok(FrameKind::Ack) | Ok(FrameKind::Control) => {}
+            Err(e) => {
+                debug!("{}: unparsed frame: {:#}", source.name(), e);
+                health.error();
+            }
+        }
+    }
+}
+
+/// The feed's HealthTable slot, plus the race statistics of its
+/// connections.
+struct FeedHealth<'a> {
+    table: &'a HealthTable,
+    name: &'static str,
+    slot: usize,
+    conns: Vec<ConnStats>,
+    live: AtomicUsize,
+    /// Race statistics are logged this often, if at all.
+    stats_interval: Option<Duration>,
+}
+
+/// Updates one connection got written first (`won`) or found already
+/// written by another connection (`lost`).
+struct ConnStats {
+    label: String,
+    won: AtomicU64,
+    lost: AtomicU64,
+}
+
+impl<'a> FeedHealth<'a> {
+    fn new(
+        table: &'a HealthTable,
+        source: SourceId,
+        labels: Vec<String>,
+        stats_interval: Option<Duration>,
+    ) -> Self {
+        let conns = labels
+            .into_iter()
+            .map(|label| ConnStats {
+                label,
+                won: AtomicU64::new(0),
+                lost: AtomicU64::new(0),
+            })
+            .collect();
+        Self {
+            table,
+            name: source.name(),
+            slot: health_slot(source),
+            conns,
+            live: AtomicUsize::new(0),
+            stats_interval: stats_interval.filter(|i| !i.is_zero()),
+        }
+    }
+
+    /// Count a connection as live until the guard drops.
+    fn connected(&self) -> LiveConnection<'_, 'a> {
+        let live = self.live.fetch_add(1, Ordering::Relaxed) + 1;
+        self.set_live(live);
+        LiveConnection { health: self }
+    }
+
+    fn set_live(&self, live: usize) {
+        self.table
+            .set_ws_connections(self.slot, live.min(u8::MAX as usize) as u8);
+        let status = if live >= self.conns.len() {
+            ProcessStatus::Running
+        } else {
+            ProcessStatus::Degraded
+        };
+        self.table.set_status(self.slot, status);
+    }
+
+    fn message(&self) {
+        self.table.inc_msg_count(self.slot);
+    }
+
+    fn error(&self) {
+        self.table.inc_error_count(self.slot);
+    }
+
+    fn reconnect(&self) {
+        self.table.inc_reconnect_count(self.slot);
+    }
+
+    fn stale(&self) {
+        self.table.inc_stale_count(self.slot);
+    }
+
+    fn race(&self, conn: usize, won: usize, lost: usize) {
+        let stats = &self.conns[conn];
+        stats.won.fetch_add(won as u64, Ordering::Relaxed);
+        stats.lost.fetch_add(lost as u64, Ordering::Relaxed);
+    }
+
+    /// (won, lost) of connection `conn` so far.
+    fn races(&self, conn: usize) -> (u64, u64) {
+        let stats = &self.conns[conn];
+        (
+            stats.won.load(Ordering::Relaxed),
+            stats.lost.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Heartbeat every HEALTH_INTERVAL, race statistics every
+    /// `stats_interval`; returns once `control` says stop.
+    async fn run(&self, control: &ControlStore) {
+        let started = Instant::now();
+        let mut last_stats = started;
+        let mut tick = tokio::time::interval(HEALTH_INTERVAL);
+        loop {
+            tick.tick().await;
+            if control.should_stop() {
+                return;
+            }
+            self.table.heartbeat(self.slot, unix_now_us());
+            self.table
+                .set_uptime(self.slot, started.elapsed().as_secs() as u32);
+            if let Some(interval) = self.stats_interval {
+                if last_stats.elapsed() >= interval {
+                    last_stats = Instant::now();
+                    self.log_races();
+                }
+            }
+        }
+    }
+
+    fn log_races(&self) {
+        let races: Vec<String> = (0..self.conns.len())
+            .map(|i| {
+                let (won, lost) = self.races(i);
+                let total = (won + lost).max(1);
+                format!(
+                    "{} won {}/{} ({:.1}%)",
+                    self.conns[i].label,
+                    won,
+                    won + lost,
+                    won as f64 * 100.0 / total as f64
+                )
+            })
+            .collect();
+        info!("{}: races won: {}", self.name, races.join(", "));
+    }
+}
+
+struct LiveConnection<'h, 'a> {
+    health: &'h FeedHealth<'a>,
+}
+
+impl Drop for LiveConnection<'_, '_> {
+    fn drop(&mut self) {
+        let live = self.health.live.fetch_sub(1, Ordering::Relaxed) - 1;
+        self.health.set_live(live);
+    }
+}
+
+fn unix_now_us() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::symbols::SymbolRecord;
+    use common::types::PriceSnapshot;
+    use shm::mmap;
+
+    use crate::test_ws::{Session, TestWsServer};
+
+    /// "<symbol> <bid> <ask> [update_id]" per frame; "ping" is answered
+    /// with "pong".
+    struct LineParser;
+
+    impl Parser for LineParser {
+        fn source(&self) -> SourceId {
+            SourceId::OkxSpot
+        }
+
+        fn subscribe_messages(&self, subs: &[SymbolSub]) -> Vec<String> {
+            let names: Vec<_> = subs.iter().map(|s| s.exchange_name.as_str()).collect();
+            vec![format!("sub {}", names.join(","))]
+        }
+
+        fn parse(
+            &mut self,
+            frame: Frame<'_>,
+            symbols: &SymbolTable,
+            recv_us: u64,
+            out: &mut Vec<PriceUpdate>,
+        ) -> Result<FrameKind> {
+            let Frame::Text(text) = frame else {
+                anyhow::bail!("binary frame");
+            };
+            if text == "ping" {
+                return Ok(FrameKind::Reply("pong".to_string()));
+            }
+            let mut parts = text.split(' ');
+            let (Some(name), Some(bid), Some(ask)) = (parts.next(), parts.next(), parts.next())
+            else {
+                anyhow::bail!("bad frame {}", text);
+            };
+            if let Some(symbol_id) = symbols.resolve(SourceId::OkxSpot, name) {
+                out.push(PriceUpdate {
+                    symbol_id,
+                    snapshot: PriceSnapshot {
+                        best_bid: bid.parse()?,
+                        best_ask: ask.parse()?,
+                        updated_at: recv_us,
+                    },
+                    update_id: parts.next().map_or(Ok(0), str::parse)?,
+                    exchange_ts: 0,
+                });
+            }
+            Ok(FrameKind::Data)
+        }
+    }
+
+    fn table() -> SymbolTable {
+        let mut records: Vec<_> = ["BTC-USDT", "ETH-USDT", "PEPE-USDT"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                SymbolRecord::new(i as u16, *name).with_source(SourceId::OkxSpot, *name)
+            })
+            .collect();
+        // 1000PEPE-style contract: quoted per 1000 units
+        records[2].price_multiplier[SourceId::OkxSpot.index()] = 1000.0;
+        SymbolTable::from_records(records)
+    }
+
+    fn feed_config(server: &TestWsServer) -> FeedConfig {
+        FeedConfig {
+            source: SourceId::OkxSpot,
+            url: server.url(),
+            standby_url: None,
+            max_subscriptions_per_conn: 200,
+            ping_interval: Duration::from_secs(20),
+            heartbeat_timeout: Duration::from_secs(10),
+            reconnect_base: Duration::from_millis(10),
+            reconnect_max: Duration::from_millis(40),
+            channel: None,
+            stats_interval: Duration::ZERO,
+        }
+    }
+
+    fn update(symbol_id: u16, bid: f64, update_id: u64, exchange_ts: u64) -> PriceUpdate {
+        PriceUpdate {
+            symbol_id,
+            snapshot: PriceSnapshot {
+                best_bid: bid,
+                best_ask: bid + 1.0,
+                updated_at: 1,
+            },
+            update_id,
+            exchange_ts,
+        }
+    }
+
+    #[test]
+    fn test_publish_writes_only_fresher_updates() {
+        let names = ["test-fresh-seqs", "test-fresh-data", "test-fresh-bitmap"];
+        for name in names {
+            let _ = mmap::remove_shm(name);
+        }
+        let output = FeedOutput::new(
+            PriceStore::create(names[0], names[1], 16).unwrap(),
+            UpdateBitmap::create(names[2]).unwrap(),
+        );
+        let reader = PriceStore::open(names[0], names[1]).unwrap();
+        let source = SourceId::OkxSpot;
+        let publish = |conn, u| output.publish(source, conn, &[u]);
+        let bid = |symbol_id| reader.read(symbol_id, source as u8).unwrap().best_bid;
+
+        // By update id: the copy from the other connection loses
+        assert_eq!(publish(0, update(0, 100.0, 5, 0)), 1);
+        assert_eq!(publish(1, update(0, 100.0, 5, 0)), 0);
+        assert_eq!(publish(1, update(0, 101.0, 6, 0)), 1);
+        assert_eq!(publish(0, update(0, 100.0, 5, 0)), 0);
+        assert_eq!(bid(0), 101.0);
+
+        // Exchange time first, so an id reset does not freeze the price
+        assert_eq!(publish(0, update(1, 10.0, 9, 1_000)), 1);
+        assert_eq!(publish(1, update(1, 11.0, 1, 2_000)), 1);
+        assert_eq!(publish(0, update(1, 11.0, 1, 2_000)), 0);
+        assert_eq!(publish(0, update(1, 12.0, 2, 2_000)), 1);
+        // A tie on a coarse clock goes to the connection that wrote last
+        assert_eq!(publish(1, update(1, 13.0, 0, 3_000)), 1);
+        assert_eq!(publish(1, update(1, 14.0, 0, 3_000)), 1);
+        assert_eq!(publish(0, update(1, 14.0, 0, 3_000)), 0);
+        assert_eq!(bid(1), 14.0);
+
+        // Nothing to compare: always written
+        assert_eq!(publish(0, update(2, 1.0, 0, 0)), 1);
+        assert_eq!(publish(1, update(2, 2.0, 0, 0)), 1);
+        assert_eq!(bid(2), 2.0);
+
+        // Per symbol, within one batch
+        let batch = [update(0, 99.0, 4, 0), update(2, 3.0, 0, 0)];
+        assert_eq!(output.publish(source, 1, &batch), 1);
+        assert_eq!(bid(0), 101.0);
+
+        for name in names {
+            mmap::remove_shm(name).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ping_timer() {
+        let fires = |interval| async move {
+            let mut ping = PingTimer::new(interval);
+            tokio::time::timeout(Duration::from_millis(200), ping.tick())
+                .await
+                .is_ok()
+        };
+        assert!(fires(Duration::from_millis(10)).await);
+        // 0 turns keepalives off instead of panicking
+        assert!(!fires(Duration::ZERO).await);
+    }
+
+    #[tokio::test]
+    async fn test_feed_loop_shards_and_publishes() {
+        let names = [
+            "test-feed-seqs",
+            "test-feed-data",
+            "test-feed-bitmap",
+            "test-feed-health",
+            "test-feed-control",
+        ];
+        for name in names {
+            let _ = mmap::remove_shm(name);
+        }
+        let output = FeedOutput::new(
+            PriceStore::create(names[0], names[1], 16).unwrap(),
+            UpdateBitmap::create(names[2]).unwrap(),
+        );
+        let health = HealthTable::create(names[3]).unwrap();
+        let control = ControlStore::create(names[4]).unwrap();
+
+        let server = TestWsServer::start(vec![
+            "ping".to_string(),
+            "BTC-USDT 100 101".to_string(),
+            "PEPE-USDT 10 11".to_string(),
+            "garbage".to_string(),
+        ])
+        .await;
+        // Keepalives off: LineParser has none, and 0 must not stop the loop
+        let config = FeedConfig {
+            max_subscriptions_per_conn: 2,
+            ping_interval: Duration::ZERO,
+            ..feed_config(&server)
+        };
+        let symbols = table();
+        let make_parser = || Box::new(LineParser) as Box<dyn Parser>;
+
+        let reader = PriceStore::open(names[0], names[1]).unwrap();
+        let bitmap = UpdateBitmap::open(names[2]).unwrap();
+        let slot = health_slot(SourceId::OkxSpot);
+        // "garbage" comes last on both connections
+        let stop = async {
+            while health.read(slot).error_count < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            let slot = health.read(slot);
+            assert_eq!(slot.ws_connections, 2);
+            assert_eq!(slot.status, ProcessStatus::Running);
+            control.set_shutdown(true);
+        };
+        tokio::time::timeout(Duration::from_secs(10), async {
+            tokio::join!(
+                feed_loop(&config, &symbols, &make_parser, &output, &health, &control),
+                stop
+            );
+            while server.received().len() < 4 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        // 3 symbols, 2 per connection
+        let mut subscribed = server.received();
+        subscribed.sort();
+        assert_eq!(
+            subscribed,
+            vec!["pong", "pong", "sub BTC-USDT,ETH-USDT", "sub PEPE-USDT"]
+        );
+        let btc = reader.read(0, SourceId::OkxSpot as u8).unwrap();
+        assert_eq!((btc.best_bid, btc.best_ask), (100.0, 101.0));
+        // Per-unit prices in the store
+        let pepe = reader.read(2, SourceId::OkxSpot as u8).unwrap();
+        assert_eq!((pepe.best_bid, pepe.best_ask), (0.01, 0.011));
+        assert_eq!(bitmap.swap_word(SourceId::OkxSpot as u8, 0), 0b101);
+
+        let slot = health.read(slot);
+        assert_eq!(slot.status, ProcessStatus::Stopped);
+        assert_eq!(slot.msg_count, 4);
+        assert_eq!(slot.error_count, 2);
+
+        for name in names {
+            mmap::remove_shm(name).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_dropped_and_stalled_connections() {
+        let names = [
+            "test-reconnect-seqs",
+            "test-reconnect-data",
+            "test-reconnect-bitmap",
+            "test-reconnect-health",
+            "test-reconnect-control",
+        ];
+        for name in names {
+            let _ = mmap::remove_shm(name);
+        }
+        let output = FeedOutput::new(
+            PriceStore::create(names[0], names[1], 16).unwrap(),
+            UpdateBitmap::create(names[2]).unwrap(),
+        );
+        let health = HealthTable::create(names[3]).unwrap();
+        let control = ControlStore::create(names[4]).unwrap();
+
+        // Hung up on, then silent, then served
+        let server = TestWsServer::scripted(vec![
+            Session::Drop(vec!["BTC-USDT 100 101".to_string()]),
+            Session::Stall,
+            Session::Serve(vec!["BTC-USDT 102 103".to_string()]),
+        ])
+        .await;
+        let config = FeedConfig {
+            heartbeat_timeout: Duration::from_millis(300),
+            ..feed_config(&server)
+        };
+        let symbols = table();
+        let make_parser = || Box::new(LineParser) as Box<dyn Parser>;
+
+        let reader = PriceStore::open(names[0], names[1]).unwrap();
+        let slot = health_slot(SourceId::OkxSpot);
+        let stop = async {
+            loop {
+                let btc = reader.read(0, SourceId::OkxSpot as u8);
+                if btc.is_some_and(|b| b.best_bid == 102.0) {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            // Served connection is the third; it goes stale too later on
+            assert_eq!(server.connections(), 3);
+            let slot = health.read(slot);
+            assert_eq!(slot.ws_connections, 1);
+            assert_eq!(slot.status, ProcessStatus::Running);
+            assert_eq!((slot.reconnect_count, slot.stale_count), (2, 1));
+            // The hang-up is an error, the stall is not
+            assert_eq!(slot.error_count, 1);
+            control.set_shutdown(true);
+        };
+        tokio::time::timeout(Duration::from_secs(10), async {
+            tokio::join!(
+                feed_loop(&config, &symbols, &make_parser, &output, &health, &control),
+                stop
+            );
+        })
+        .await
+        .unwrap();
+        assert_eq!(health.read(slot).ws_connections, 0);
+
+        for name in names {
+            mmap::remove_shm(name).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_standby_fills_in_for_dropped_primary() {
+        let names = [
+            "test-standby-seqs",
+            "test-standby-data",
+            "test-standby-bitmap",
+            "test-standby-health",
+            "test-standby-control",
+        ];
+        for name in names {
+            let _ = mmap::remove_shm(name);
+        }
+        let output = FeedOutput::new(
+            PriceStore::create(names[0], names[1], 16).unwrap(),
+            UpdateBitmap::create(names[2]).unwrap(),
+        );
+        let health = HealthTable::create(names[3]).unwrap();
+        let control = ControlStore::create(names[4]).unwrap();
+
+        // The primary hangs up after one update and then stays silent; the
+        // standby repeats it and carries on
+        let primary = TestWsServer::scripted(vec![
+            Session::Drop(vec!["BTC-USDT 102 103 2".to_string()]),
+            Session::Stall,
+        ])
+        .await;
+        let standby = TestWsServer::start(vec![
+            "BTC-USDT 100 101 1".to_string(),
+            "BTC-USDT 102 103 2".to_string(),
+            "ETH-USDT 5 6 7".to_string(),
+        ])
+        .await;
+        let config = FeedConfig {
+            standby_url: Some(standby.url()),
+            ..feed_config(&primary)
+        };
+        let symbols = table();
+        let make_parser = || Box::new(LineParser) as Box<dyn Parser>;
+
+        let reader = PriceStore::open(names[0], names[1]).unwrap();
+        let slot = health_slot(SourceId::OkxSpot);
+        let stop = async {
+            while health.read(slot).msg_count < 4 || primary.connections() < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            control.set_shutdown(true);
+        };
+        tokio::time::timeout(Duration::from_secs(10), async {
+            tokio::join!(
+                feed_loop(&config, &symbols, &make_parser, &output, &health, &control),
+                stop
+            );
+        })
+        .await
+        .unwrap();
+
+        // Update 1 never overwrites update 2, whichever arrived first
+        let btc = reader.read(0, SourceId::OkxSpot as u8).unwrap();
+        assert_eq!((btc.best_bid, btc.best_ask), (102.0, 103.0));
+        // Only the standby had ETH
+        let eth = reader.read(1, SourceId::OkxSpot as u8).unwrap();
+        assert_eq!(eth.best_bid, 5.0);
+        assert_eq!(standby.received(), vec!["sub BTC-USDT,ETH-USDT,PEPE-USDT"]);
+        assert_eq!(health.read(slot).reconnect_count, 1);
+
+        for name in names {
+            mmap::remove_shm(name).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_feed_gives_up_its_slot() {
+        let name = "test-feed-claim-health";
+        let _ = mmap::remove_shm(name);
+        let health = HealthTable::create(name).unwrap();
+        let slot = health_slot(SourceId::OkxSpot);
+
+        // Claimed for a pid that is certainly alive: our parent
+        let owner = std::os::unix::process::parent_id();
+        let feed = async {
+            let _claim = SlotClaim::take(&health, slot, owner).unwrap();
+            health.set_status(slot, ProcessStatus::Running);
+            health.set_ws_connections(slot, 3);
+            // Not ours to take while the owner lives
+            assert_eq!(
+                SlotClaim::take(&health, slot, std::process::id()).err(),
+                Some(owner)
+            );
+            std::future::pending::<()>().await;
+        };
+        // Dropped mid-run, the way try_join_all drops a sibling
+        assert!(tokio::time::timeout(Duration::from_millis(50), feed)
+            .await
+            .is_err());
+
+        let snapshot = health.read(slot);
+        assert_eq!(snapshot.status, ProcessStatus::Stopped);
+        assert_eq!((snapshot.ws_connections, snapshot.owner_pid), (0, 0));
+        mmap::remove_shm(name).unwrap();
+    }
+}
@@ -2,6 +2,10 @@
 //!
 //! Each process writes its slot periodically with heartbeat timestamp, status,
 //! message count, error count, etc. Supervisor/CLI reads all slots.
+//!
+//! A process claims its slot with its pid before writing to it, so two
+//! processes serving the same slot refuse to start rather than interleave.
+//! A claim held by a pid that no longer exists is taken over.
 
 use std::sync::atomic::{AtomicU64, AtomicU32, AtomicU8, Ordering};
 
@@ -31,7 +35,13 @@ pub struct HealthSlot {
     pub _pad2: [u8; 3],
     /// Uptime in seconds
     pub uptime_sec: AtomicU32,
-    pub _pad3: [u8; 24],
+    /// WS reconnect attempts (for feeds)
+    pub reconnect_count: AtomicU32,
+    /// WS connections dropped for missing the heartbeat timeout (for feeds)
+    pub stale_count: AtomicU32,
+    /// Pid of the process that claimed the slot, 0 if unclaimed
+    pub owner_pid: AtomicU32,
+    pub _pad3: [u8; 12],
 }
 
 const _: () = {
@@ -69,6 +79,9 @@ pub struct HealthSnapshot {
     pub error_count: u32,
     pub ws_connections: u8,
     pub uptime_sec: u32,
+    pub reconnect_count: u32,
+    pub stale_count: u32,
+    pub owner_pid: u32,
 }
 
 pub struct HealthTable {
@@ -126,6 +139,46 @@ impl HealthTable {
             .store(count, Ordering::Relaxed);
     }
 
+    /// Increment WS reconnect counter.
+    pub fn inc_reconnect_count(&self, slot_id: usize) {
+        self.slot(slot_id)
+            .reconnect_count
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Increment stale WS connection counter.
+    pub fn inc_stale_count(&self, slot_id: usize) {
+        self.slot(slot_id)
+            .stale_count
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Claim a slot for process `pid`. Fails with the owner's pid if another
+    /// live process holds it; a claim left behind by a dead process is
+    /// taken over. Claiming a slot `pid` already holds succeeds.
+    pub fn claim(&self, slot_id: usize, pid: u32) -> Result<(), u32> {
+        let owner_pid = &self.slot(slot_id).owner_pid;
+        let mut owner = 0;
+        loop {
+            match owner_pid.compare_exchange(owner, pid, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return Ok(()),
+                Err(current) if current == pid => return Ok(()),
+                Err(current) if current != 0 && process_alive(current) => return Err(current),
+                Err(current) => owner = current,
+            }
+        }
+    }
+
+    /// Give up a slot claimed by `pid`; a no-op if someone else holds it.
+    pub fn release(&self, slot_id: usize, pid: u32) {
+        let _ = self.slot(slot_id).owner_pid.compare_exchange(
+            pid,
+            0,
+            Ordering::AcqRel,
+            Ordering::Relaxed,
+        );
+    }
+
     /// Set uptime.
     pub fn set_uptime(&self, slot_id: usize, sec: u32) {
         self.slot(slot_id)
@@ -143,6 +196,9 @@ impl HealthTable {
             error_count: s.error_count.load(Ordering::Relaxed),
             ws_connections: s.ws_connections.load(Ordering::Relaxed),
             uptime_sec: s.uptime_sec.load(Ordering::Relaxed),
+            reconnect_count: s.reconnect_count.load(Ordering::Relaxed),
+            stale_count: s.stale_count.load(Ordering::Relaxed),
+            owner_pid: s.owner_pid.load(Ordering::Acquire),
         }
     }
 
@@ -156,6 +212,16 @@ impl HealthTable {
     }
 }
 
+/// Whether a process with this pid exists (signal 0 checks without sending).
+fn process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    let sent = unsafe { libc::kill(pid, 0) } == 0;
+    // EPERM: it exists, it just is not ours to signal
+    sent || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -172,18 +238,33 @@ mod tests {
         ht.inc_msg_count(0);
         ht.inc_msg_count(0);
         ht.inc_error_count(0);
+        ht.inc_reconnect_count(0);
+        ht.inc_stale_count(0);
 
         let snap = ht.read(0);
         assert_eq!(snap.status, ProcessStatus::Running);
         assert_eq!(snap.heartbeat_us, 1234567890);
         assert_eq!(snap.msg_count, 2);
         assert_eq!(snap.error_count, 1);
+        assert_eq!((snap.reconnect_count, snap.stale_count), (1, 1));
 
         // Slot 1 should be default
         let snap1 = ht.read(1);
         assert_eq!(snap1.status, ProcessStatus::Unknown);
         assert_eq!(snap1.msg_count, 0);
 
+        // Claims: one live owner per slot, dead owners are replaced
+        let me = std::process::id();
+        assert_eq!(ht.claim(2, me), Ok(()));
+        assert_eq!(ht.claim(2, me), Ok(()));
+        assert_eq!(ht.claim(2, 1), Err(me));
+        ht.release(2, 1);
+        assert_eq!(ht.read(2).owner_pid, me);
+        ht.release(2, me);
+        assert_eq!(ht.read(2).owner_pid, 0);
+        ht.slot(3).owner_pid.store(i32::MAX as u32, Ordering::Relaxed);
+        assert_eq!(ht.claim(3, me), Ok(()));
+
         mmap::remove_shm(name).unwrap();
     }
 }
@@ -111,11 +111,6 @@ impl PriceStore {
         unsafe { &*(self.data.as_ptr().add(offset) as *const PriceDataEntry) }
     }
 
-    fn data_entry_mut(&mut self, symbol_id: u16, source_id: u8) -> &mut PriceDataEntry {
-        let offset = Self::slot_offset(symbol_id, source_id);
-        unsafe { &mut *(self.data.as_mut_ptr().add(offset) as *mut PriceDataEntry) }
-    }
-
     /// Write a price update for (symbol, source) under SeqLock protection.
     pub fn write(&mut self, symbol_id: u16, source_id: u8, snapshot: &PriceSnapshot) {
         let offset = Self::slot_offset(symbol_id, source_id);
@@ -173,7 +173,7 @@ mod tests {
                 };
                 let mut d = data_w.lock().unwrap();
                 unsafe {
-                    seqlock_write(&seq_w, &mut *d, &snap);
+                    seqlock_write(&seq_w, &mut d, &snap);
                 }
                 i += 1;
             }
@@ -188,7 +188,7 @@ mod tests {
             let mut failed = 0u64;
             for _ in 0..100_000 {
                 let d = data_r.lock().unwrap();
-                let result = unsafe { seqlock_read(&seq_r, &*d) };
+                let result = unsafe { seqlock_read(&seq_r, &d) };
                 if let Some(snap) = result {
                     reads += 1;
                     // Verify consistency: ask should be bid + 1
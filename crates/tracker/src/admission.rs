@@ -0,0 +1,114 @@
+//! Session admission.
+//!
+//! A tracking session follows a pair for `tracker.tracking_duration_hours`.
+//! Discovery already drops listings that delist within its horizon, but a
+//! generation can be hours old and a session long, so before a session
+//! starts the announced delist time of both legs (`InstrumentSpec::delist_at`
+//! in symbols.bin) is checked: a session that would still run when either
+//! leg stops trading is refused.
+
+use std::fmt;
+use std::time::Duration;
+
+use common::config::TrackerConfig;
+use common::symbols::SymbolTable;
+use common::types::SourceId;
+
+/// Why a session was refused.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Refusal {
+    pub symbol: String,
+    pub source: SourceId,
+    /// Unix milliseconds.
+    pub delist_at: u64,
+    /// Time left until `delist_at`.
+    pub remaining: Duration,
+    pub session: Duration,
+}
+
+impl fmt::Display for Refusal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} delists on {} in {}m, session needs {}m",
+            self.symbol,
+            self.source.name(),
+            self.remaining.as_secs() / 60,
+            self.session.as_secs() / 60
+        )
+    }
+}
+
+impl std::error::Error for Refusal {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Admission {
+    session: Duration,
+}
+
+impl Admission {
+    pub fn new(session: Duration) -> Self {
+        Self { session }
+    }
+
+    pub fn from_config(config: &TrackerConfig) -> Self {
+        Self::new(Duration::from_secs(config.tracking_duration_hours * 3600))
+    }
+
+    /// Whether a session on `symbol_id` between `spot` and `futures` may
+    /// start at `now_ms` (unix milliseconds).
+    pub fn check(
+        &self,
+        symbols: &SymbolTable,
+        symbol_id: u16,
+        spot: SourceId,
+        futures: SourceId,
+        now_ms: u64,
+    ) -> Result<(), Refusal> {
+        let ends_at = now_ms.saturating_add(self.session.as_millis() as u64);
+        let leg = [spot, futures]
+            .into_iter()
+            .filter_map(|s| symbols.delist_at(s, symbol_id).map(|at| (s, at)))
+            .filter(|&(_, at)| at <= ends_at)
+            .min_by_key(|&(_, at)| at);
+        match leg {
+            Some((source, delist_at)) => Err(Refusal {
+                symbol: symbols.name(symbol_id).to_string(),
+                source,
+                delist_at,
+                remaining: Duration::from_millis(delist_at.saturating_sub(now_ms)),
+                session: self.session,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::symbols::SymbolRecord;
+
+    #[test]
+    fn test_refuses_session_past_delisting() {
+        let spot = SourceId::BybitSpot;
+        let futures = SourceId::BinanceFutures;
+        let now = 1_700_000_000_000;
+        let mut record = SymbolRecord::new(0, "XEM-USDT")
+            .with_source(spot, "XEMUSDT")
+            .with_source(futures, "XEMUSDT");
+        record.specs[futures.index()].delist_at = Some(now + 30 * 60_000);
+        let table = SymbolTable::from_records(vec![record]);
+
+        let admission = Admission::new(Duration::from_secs(3 * 3600));
+        let refusal = admission.check(&table, 0, spot, futures, now).unwrap_err();
+        assert_eq!(refusal.source, futures);
+        assert_eq!(
+            refusal.to_string(),
+            "XEM-USDT delists on binance_futures in 30m, session needs 180m"
+        );
+        // A short enough session still fits
+        let short = Admission::new(Duration::from_secs(20 * 60));
+        assert!(short.check(&table, 0, spot, futures, now).is_ok());
+    }
+}
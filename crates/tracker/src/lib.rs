@@ -1 +1 @@
-
+pub mod admission;